@@ -0,0 +1,643 @@
+//! Bulls and Cows: the secret is a short code of digits, and every guess
+//! is answered with how many digits are right and in the right place
+//! ("bulls") and how many are right but in the wrong place ("cows").
+//!
+//! By default the code has four different digits. Allowing repeats turns
+//! it into Mastermind, where `0012` is a valid code.
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+
+use crate::guess::{read_input, GuessError};
+
+/// The longest code the game supports.
+pub const MAX_LENGTH: usize = 6;
+
+/// The shape of the secret and how many tries the player gets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    pub length: usize,
+    /// Digits run from `0` to `symbols - 1`.
+    pub symbols: u8,
+    pub allow_repeats: bool,
+    pub max_attempts: u32,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules::bulls_and_cows()
+    }
+}
+
+impl Rules {
+    /// Four different digits out of ten, ten attempts.
+    pub fn bulls_and_cows() -> Rules {
+        Rules {
+            length: 4,
+            symbols: 10,
+            allow_repeats: false,
+            max_attempts: 10,
+        }
+    }
+
+    /// Classic Mastermind: four pegs in six colours, written `0` to `5`,
+    /// repeats allowed.
+    pub fn mastermind() -> Rules {
+        Rules {
+            length: 4,
+            symbols: 6,
+            allow_repeats: true,
+            max_attempts: 10,
+        }
+    }
+
+    pub fn with_repeats(self, allow_repeats: bool) -> Rules {
+        Rules {
+            allow_repeats,
+            ..self
+        }
+    }
+
+    /// Checks that codes can be made under these rules.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_LENGTH).contains(&self.length) {
+            return Err(format!("codes must be 1 to {} digits long", MAX_LENGTH));
+        }
+        if !(1..=10).contains(&self.symbols) {
+            return Err("codes can use 1 to 10 different digits".to_string());
+        }
+        if !self.allow_repeats && self.length > usize::from(self.symbols) {
+            return Err(format!(
+                "{} different digits do not fit in a {}-digit code",
+                self.symbols, self.length
+            ));
+        }
+        if self.max_attempts == 0 {
+            return Err("the player needs at least one attempt".to_string());
+        }
+        Ok(())
+    }
+
+    /// Reads a guess such as `0123`. Spaces between digits are ignored.
+    pub fn parse(&self, text: &str) -> Result<Code, CodeError> {
+        let chars: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+        if let Some(&c) = chars.iter().find(|c| !c.is_ascii_digit()) {
+            return Err(CodeError::NotADigit(c));
+        }
+        if chars.len() != self.length {
+            return Err(CodeError::WrongLength {
+                expected: self.length,
+                got: chars.len(),
+            });
+        }
+        let mut digits = [0; MAX_LENGTH];
+        for (slot, c) in digits.iter_mut().zip(&chars) {
+            *slot = *c as u8 - b'0';
+        }
+        let code = Code {
+            digits,
+            len: self.length as u8,
+        };
+        self.check(&code)?;
+        Ok(code)
+    }
+
+    /// Checks that `code` follows these rules.
+    pub fn check(&self, code: &Code) -> Result<(), CodeError> {
+        if code.len() != self.length {
+            return Err(CodeError::WrongLength {
+                expected: self.length,
+                got: code.len(),
+            });
+        }
+        let mut seen = [false; 10];
+        for &digit in code.digits() {
+            if digit >= self.symbols {
+                return Err(CodeError::OutOfRange {
+                    digit,
+                    highest: self.symbols - 1,
+                });
+            }
+            if seen[usize::from(digit)] && !self.allow_repeats {
+                return Err(CodeError::Repeated(digit));
+            }
+            seen[usize::from(digit)] = true;
+        }
+        Ok(())
+    }
+
+    /// Every code these rules allow, in ascending order.
+    pub fn all_codes(&self) -> Vec<Code> {
+        let total = u32::from(self.symbols).pow(self.length as u32);
+        (0..total)
+            .map(|mut n| {
+                let mut digits = [0; MAX_LENGTH];
+                for slot in digits[..self.length].iter_mut().rev() {
+                    *slot = (n % u32::from(self.symbols)) as u8;
+                    n /= u32::from(self.symbols);
+                }
+                Code {
+                    digits,
+                    len: self.length as u8,
+                }
+            })
+            .filter(|code| self.check(code).is_ok())
+            .collect()
+    }
+
+    /// Draws a secret.
+    pub fn random_code<G: RngCore + ?Sized>(&self, rng: &mut G) -> Code {
+        let mut digits = [0; MAX_LENGTH];
+        if self.allow_repeats {
+            for slot in &mut digits[..self.length] {
+                *slot = rng.gen_range(0..self.symbols);
+            }
+        } else {
+            let mut pool: Vec<u8> = (0..self.symbols).collect();
+            pool.shuffle(rng);
+            digits[..self.length].copy_from_slice(&pool[..self.length]);
+        }
+        Code {
+            digits,
+            len: self.length as u8,
+        }
+    }
+}
+
+impl fmt::Display for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({} digits 0-{}, {}, {} attempts)",
+            if self.allow_repeats {
+                "mastermind"
+            } else {
+                "bulls and cows"
+            },
+            self.length,
+            self.symbols - 1,
+            if self.allow_repeats {
+                "repeats allowed"
+            } else {
+                "no repeats"
+            },
+            self.max_attempts
+        )
+    }
+}
+
+/// A secret or a guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Code {
+    digits: [u8; MAX_LENGTH],
+    len: u8,
+}
+
+impl Code {
+    pub fn digits(&self) -> &[u8] {
+        &self.digits[..self.len()]
+    }
+
+    pub fn len(&self) -> usize {
+        usize::from(self.len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for digit in self.digits() {
+            write!(f, "{}", digit)?;
+        }
+        Ok(())
+    }
+}
+
+/// Why a guess is not a valid code. A refused guess does not cost an
+/// attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodeError {
+    WrongLength {
+        expected: usize,
+        got: usize,
+    },
+    NotADigit(char),
+    /// A digit the rules do not use, e.g. `7` in six-colour Mastermind.
+    OutOfRange {
+        digit: u8,
+        highest: u8,
+    },
+    /// The same digit twice when repeats are not allowed.
+    Repeated(u8),
+}
+
+impl fmt::Display for CodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodeError::WrongLength { expected, got } => {
+                write!(f, "Enter {} digits, not {}.", expected, got)
+            }
+            CodeError::NotADigit(c) => write!(f, "'{}' is not a digit.", c),
+            CodeError::OutOfRange { digit, highest } => {
+                write!(f, "{} is not used, digits go from 0 to {}.", digit, highest)
+            }
+            CodeError::Repeated(digit) => {
+                write!(f, "{} appears twice, every digit must be different.", digit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CodeError {}
+
+/// The answer to one guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Score {
+    /// Right digit, right place.
+    pub bulls: u8,
+    /// Right digit, wrong place.
+    pub cows: u8,
+}
+
+impl Score {
+    /// Scores `guess` against `secret`. A repeated digit in one code only
+    /// matches as many times as it appears in the other.
+    pub fn of(secret: &Code, guess: &Code) -> Score {
+        let len = secret.len();
+        let mut bulls = 0;
+        let (mut in_secret, mut in_guess) = (0u16, 0u16);
+        for i in 0..len {
+            let (s, g) = (secret.digits[i], guess.digits[i]);
+            bulls += u8::from(s == g);
+            in_secret |= 1 << s;
+            in_guess |= 1 << g;
+        }
+        // Without repeats every shared digit is a bull or a cow. This is
+        // the solver's inner loop, so it is worth the shortcut.
+        if in_secret.count_ones() as usize == len && in_guess.count_ones() as usize == len {
+            let shared = (in_secret & in_guess).count_ones() as u8;
+            return Score {
+                bulls,
+                cows: shared - bulls,
+            };
+        }
+
+        let mut left_in_secret = [0u8; 10];
+        let mut left_in_guess = [0u8; 10];
+        for i in 0..len {
+            let (s, g) = (secret.digits[i], guess.digits[i]);
+            if s != g {
+                left_in_secret[usize::from(s)] += 1;
+                left_in_guess[usize::from(g)] += 1;
+            }
+        }
+        let cows = (0..10)
+            .map(|d| left_in_secret[d].min(left_in_guess[d]))
+            .sum();
+        Score { bulls, cows }
+    }
+
+    /// A small number unique to each score of codes `length` long, for
+    /// counting scores in an array.
+    pub fn index(&self, length: usize) -> usize {
+        usize::from(self.bulls) * (length + 1) + usize::from(self.cows)
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |n: u8| if n == 1 { "" } else { "s" };
+        write!(
+            f,
+            "{} bull{}, {} cow{}",
+            self.bulls,
+            plural(self.bulls),
+            self.cows,
+            plural(self.cows)
+        )
+    }
+}
+
+/// How a game of Bulls and Cows ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BullsOutcome {
+    Won { secret: Code, attempts: u32 },
+    Lost { secret: Code, attempts: u32 },
+    Quit { secret: Code, attempts: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BullsError {
+    Invalid(CodeError),
+    /// The round is already won, lost or abandoned.
+    GameOver(BullsOutcome),
+}
+
+impl fmt::Display for BullsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BullsError::Invalid(e) => e.fmt(f),
+            BullsError::GameOver(_) => write!(f, "The game is already over."),
+        }
+    }
+}
+
+impl std::error::Error for BullsError {}
+
+/// One game with no I/O, the counterpart of [`crate::Round`].
+#[derive(Debug, Clone)]
+pub struct BullsRound {
+    rules: Rules,
+    secret: Code,
+    attempts: u32,
+    outcome: Option<BullsOutcome>,
+}
+
+impl BullsRound {
+    pub fn new<G: RngCore + ?Sized>(rules: Rules, rng: &mut G) -> BullsRound {
+        let secret = rules.random_code(rng);
+        BullsRound::with_secret(rules, secret)
+    }
+
+    /// Starts a round with a known secret. Panics if the rules are invalid
+    /// or the secret breaks them.
+    pub fn with_secret(rules: Rules, secret: Code) -> BullsRound {
+        if let Err(e) = rules.validate() {
+            panic!("invalid rules: {}", e);
+        }
+        if let Err(e) = rules.check(&secret) {
+            panic!("secret {} breaks the rules: {}", secret, e);
+        }
+        BullsRound {
+            rules,
+            secret,
+            attempts: 0,
+            outcome: None,
+        }
+    }
+
+    /// Scores a guess, counting it as an attempt.
+    pub fn guess(&mut self, guess: &Code) -> Result<Score, BullsError> {
+        if let Some(outcome) = self.outcome {
+            return Err(BullsError::GameOver(outcome));
+        }
+        self.rules.check(guess).map_err(BullsError::Invalid)?;
+
+        self.attempts += 1;
+        let (secret, attempts) = (self.secret, self.attempts);
+        let score = Score::of(&secret, guess);
+        if usize::from(score.bulls) == self.rules.length {
+            self.outcome = Some(BullsOutcome::Won { secret, attempts });
+        } else if attempts >= self.rules.max_attempts {
+            self.outcome = Some(BullsOutcome::Lost { secret, attempts });
+        }
+        Ok(score)
+    }
+
+    /// Abandons the round. Does nothing if it is already over.
+    pub fn quit(&mut self) -> BullsOutcome {
+        *self.outcome.get_or_insert(BullsOutcome::Quit {
+            secret: self.secret,
+            attempts: self.attempts,
+        })
+    }
+
+    pub fn outcome(&self) -> Option<BullsOutcome> {
+        self.outcome
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn secret(&self) -> Code {
+        self.secret
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn attempts_left(&self) -> u32 {
+        self.rules.max_attempts.saturating_sub(self.attempts)
+    }
+}
+
+/// Plays one game on a terminal, like [`crate::Game::play`] does for the
+/// number game.
+pub fn play<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    rng: &mut dyn RngCore,
+    rules: Rules,
+) -> io::Result<BullsOutcome> {
+    writeln!(output, "Bulls and Cows!")?;
+    writeln!(
+        output,
+        "Find the {}-digit code using digits 0-{}{}. You have {} attempts.",
+        rules.length,
+        rules.symbols - 1,
+        if rules.allow_repeats {
+            ", digits may repeat"
+        } else {
+            ", all different"
+        },
+        rules.max_attempts
+    )?;
+    writeln!(
+        output,
+        "A bull is a right digit in the right place, a cow a right digit in the wrong place."
+    )?;
+    let mut round = BullsRound::new(rules, rng);
+
+    loop {
+        writeln!(output, "Please input your guess.")?;
+        output.flush()?;
+        let line = match read_input(input) {
+            Ok(line) => line,
+            Err(GuessError::Eof) => {
+                writeln!(output, "No more input. The code was {}.", round.secret())?;
+                return Ok(round.quit());
+            }
+            Err(e) => {
+                writeln!(output, "{}", e)?;
+                continue;
+            }
+        };
+        let score = match round.rules().parse(&line).map_err(BullsError::Invalid) {
+            Ok(code) => round.guess(&code),
+            Err(e) => Err(e),
+        };
+        match score {
+            Ok(score) => writeln!(output, "{}", score)?,
+            Err(e) => {
+                writeln!(output, "{}", e)?;
+                continue;
+            }
+        }
+
+        match round.outcome() {
+            Some(outcome @ BullsOutcome::Won { attempts, .. }) => {
+                writeln!(output, "You win! It took you {} attempts.", attempts)?;
+                return Ok(outcome);
+            }
+            Some(outcome @ BullsOutcome::Lost { secret, .. }) => {
+                writeln!(output, "You lose! The code was {}.", secret)?;
+                return Ok(outcome);
+            }
+            Some(outcome) => return Ok(outcome),
+            None => writeln!(output, "{} attempts left.", round.attempts_left())?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn code(text: &str) -> Code {
+        Rules::bulls_and_cows()
+            .with_repeats(true)
+            .parse(text)
+            .unwrap()
+    }
+
+    #[test]
+    fn scores_bulls_and_cows() {
+        let score = |secret, guess| {
+            let Score { bulls, cows } = Score::of(&code(secret), &code(guess));
+            (bulls, cows)
+        };
+        assert_eq!(score("1234", "1234"), (4, 0));
+        assert_eq!(score("1234", "4321"), (0, 4));
+        assert_eq!(score("1234", "1359"), (1, 1));
+        assert_eq!(score("1234", "5678"), (0, 0));
+        // Repeats only match as often as the digit is in the secret.
+        assert_eq!(score("1123", "1111"), (2, 0));
+        assert_eq!(score("1123", "3311"), (0, 3));
+        assert_eq!(score("0012", "1200"), (0, 4));
+    }
+
+    #[test]
+    fn validates_guesses() {
+        let rules = Rules::bulls_and_cows();
+        assert_eq!(
+            rules.parse(" 0 1 2 3 ").map(|c| c.to_string()),
+            Ok("0123".to_string())
+        );
+        assert_eq!(
+            rules.parse("123"),
+            Err(CodeError::WrongLength {
+                expected: 4,
+                got: 3
+            })
+        );
+        assert_eq!(
+            rules.parse("12345"),
+            Err(CodeError::WrongLength {
+                expected: 4,
+                got: 5
+            })
+        );
+        assert_eq!(rules.parse("12a4"), Err(CodeError::NotADigit('a')));
+        assert_eq!(rules.parse("1231"), Err(CodeError::Repeated(1)));
+        assert!(rules.clone().with_repeats(true).parse("1231").is_ok());
+        assert_eq!(
+            Rules::mastermind().parse("1627"),
+            Err(CodeError::OutOfRange {
+                digit: 6,
+                highest: 5
+            })
+        );
+        assert_eq!(
+            CodeError::Repeated(1).to_string(),
+            "1 appears twice, every digit must be different."
+        );
+    }
+
+    #[test]
+    fn counts_every_code() {
+        assert_eq!(Rules::bulls_and_cows().all_codes().len(), 5040);
+        assert_eq!(Rules::mastermind().all_codes().len(), 1296);
+        assert_eq!(
+            Rules::bulls_and_cows().with_repeats(true).all_codes().len(),
+            10_000
+        );
+        let mut rng = StdRng::seed_from_u64(5);
+        for rules in [Rules::bulls_and_cows(), Rules::mastermind()] {
+            for _ in 0..100 {
+                assert_eq!(rules.check(&rules.random_code(&mut rng)), Ok(()));
+            }
+        }
+        assert!(Rules {
+            length: 11,
+            ..Rules::bulls_and_cows()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn round_ends_on_four_bulls_or_no_attempts() {
+        let rules = Rules {
+            max_attempts: 2,
+            ..Rules::bulls_and_cows()
+        };
+        let mut round = BullsRound::with_secret(rules.clone(), code("4271"));
+        assert_eq!(
+            round.guess(&code("1111")),
+            Err(BullsError::Invalid(CodeError::Repeated(1)))
+        );
+        assert_eq!(round.attempts(), 0);
+        assert_eq!(round.guess(&code("1247")), Ok(Score { bulls: 1, cows: 3 }));
+        assert_eq!(round.guess(&code("4271")), Ok(Score { bulls: 4, cows: 0 }));
+        let won = BullsOutcome::Won {
+            secret: code("4271"),
+            attempts: 2,
+        };
+        assert_eq!(round.outcome(), Some(won));
+        assert_eq!(round.guess(&code("4271")), Err(BullsError::GameOver(won)));
+
+        let mut round = BullsRound::with_secret(rules, code("4271"));
+        round.guess(&code("0123")).unwrap();
+        round.guess(&code("5678")).unwrap();
+        assert!(matches!(round.outcome(), Some(BullsOutcome::Lost { .. })));
+    }
+
+    #[test]
+    fn plays_on_a_terminal() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let secret = Rules::bulls_and_cows().random_code(&mut StdRng::seed_from_u64(1));
+        let input = format!("12\n0123\n{}\n", secret);
+        let mut output = Vec::new();
+        let outcome = play(
+            &mut input.as_bytes(),
+            &mut output,
+            &mut rng,
+            Rules::bulls_and_cows(),
+        )
+        .unwrap();
+        assert_eq!(
+            outcome,
+            BullsOutcome::Won {
+                secret,
+                attempts: 2
+            }
+        );
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Enter 4 digits, not 2."), "{}", output);
+        assert!(
+            output.contains("You win! It took you 2 attempts."),
+            "{}",
+            output
+        );
+    }
+}
@@ -0,0 +1,689 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::difficulty::{optimal_attempts, Difficulty};
+use crate::words;
+
+pub const USAGE: &str = "\
+Usage: guessing_game [OPTIONS]
+       guessing_game scores [--difficulty <LEVEL>] [--scores-file <PATH>]
+       guessing_game replay <FILE>
+       guessing_game solve [--strategy <NAME>] [--games <N>] [--seed <N>] [DIFFICULTY OPTIONS]
+       guessing_game bulls [--repeats] [--solve] [--name <NAME>] [--scores-file <PATH>] [--seed <N>]
+       guessing_game tui [--name <NAME>] [--scores-file <PATH>] [--seed <N>]
+       guessing_game words [--hard] [--daily | --date <YYYY-MM-DD> | --seed <N>] [--no-color] [--name <NAME>] [--scores-file <PATH>]
+
+Options:
+  -d, --difficulty <LEVEL>  easy, medium, hard or custom (asks interactively if omitted)
+      --min <N>             lowest secret for the custom level [default: 1]
+      --max <N>             highest secret for the custom level
+      --attempts <N>        attempts allowed on the custom level [default: enough for a perfect player]
+  -n, --name <NAME>         name to put on the high-score table [default: $USER]
+      --scores-file <PATH>  where high scores are kept [default: <data dir>/guessing_game/scores.json]
+      --seed <N>            seed for picking the secret, to make a game reproducible
+      --record <FILE>       where to write the replay [default: <data dir>/guessing_game/replays/]
+  -h, --help                print this help
+
+Commands:
+  scores                    print the leaderboard for each difficulty
+  replay <FILE>             re-run a recorded session and check it ends the same way
+  solve                     let the computer play itself and report how many attempts it needed
+  bulls                     play Bulls and Cows: find a 4-digit code from bulls and cows
+  tui                       play the number game full-screen (needs the `tui` feature)
+  words                     find a five-letter word from green, yellow and grey letters
+
+Bulls options:
+      --repeats             let digits repeat, Mastermind style
+      --solve               run the minimax solver against every code instead of playing
+
+Words options:
+      --hard                every revealed hint must be used in later guesses
+      --daily               play today's word, the same for everyone (days change at midnight UTC)
+      --date <YYYY-MM-DD>   play the daily word of another day
+      --no-color            mark letters with squares instead of colours (also set by NO_COLOR)
+
+Solve options:
+  -s, --strategy <NAME>     binary, random, linear, human or all [default: all]
+  -g, --games <N>           games to simulate per strategy [default: 1000]";
+
+/// What the player asked the binary to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Play(PlayOptions),
+    Scores(ScoresOptions),
+    Replay(PathBuf),
+    Solve(SolveOptions),
+    Bulls(BullsOptions),
+    Words(WordsOptions),
+    Tui(TuiOptions),
+    Help,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlayOptions {
+    /// `None` means the player should pick from the menu.
+    pub difficulty: Option<Difficulty>,
+    pub name: Option<String>,
+    pub scores_file: Option<PathBuf>,
+    pub seed: Option<u64>,
+    pub record: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScoresOptions {
+    /// Only show difficulties starting with this, e.g. `easy` or `custom`.
+    pub difficulty: Option<String>,
+    pub scores_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolveOptions {
+    pub difficulty: Difficulty,
+    /// `None` runs every strategy.
+    pub strategy: Option<String>,
+    pub games: u32,
+    pub seed: Option<u64>,
+}
+
+impl Default for SolveOptions {
+    fn default() -> Self {
+        SolveOptions {
+            difficulty: Difficulty::default(),
+            strategy: None,
+            games: 1000,
+            seed: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BullsOptions {
+    pub allow_repeats: bool,
+    /// Report how the solver does on every code instead of playing.
+    pub solve: bool,
+    pub name: Option<String>,
+    pub scores_file: Option<PathBuf>,
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WordsOptions {
+    pub hard: bool,
+    pub daily: bool,
+    /// The day to play with `--daily`, in days since 1970-01-01. `None`
+    /// means today.
+    pub date: Option<i64>,
+    pub no_color: bool,
+    pub name: Option<String>,
+    pub scores_file: Option<PathBuf>,
+    pub seed: Option<u64>,
+}
+
+/// The full-screen game picks its difficulty from a menu, so only the
+/// score and seed flags apply.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TuiOptions {
+    pub name: Option<String>,
+    pub scores_file: Option<PathBuf>,
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    UnknownArgument(String),
+    MissingValue(&'static str),
+    InvalidValue {
+        flag: &'static str,
+        value: String,
+    },
+    /// `--min`, `--max` or `--attempts` without `--difficulty custom`, or a
+    /// custom level that cannot be played.
+    InvalidCustom(String),
+    /// Two flags that cannot be used together.
+    Conflicting(&'static str, &'static str),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::UnknownArgument(arg) => write!(f, "unknown argument '{}'", arg),
+            CliError::MissingValue(flag) => write!(f, "{} needs a value", flag),
+            CliError::InvalidValue { flag, value } => {
+                write!(f, "'{}' is not a valid value for {}", value, flag)
+            }
+            CliError::InvalidCustom(reason) => write!(f, "{}", reason),
+            CliError::Conflicting(a, b) => write!(f, "{} and {} cannot be used together", a, b),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+/// Parses the arguments that follow the program name.
+pub fn parse_args<I>(args: I) -> Result<Command, CliError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter().peekable();
+    match args.peek().map(String::as_str) {
+        Some("scores") => {
+            args.next();
+            return parse_scores(args);
+        }
+        Some("replay") => {
+            args.next();
+            return parse_replay(args);
+        }
+        Some("solve") => {
+            args.next();
+            return parse_solve(args);
+        }
+        Some("bulls") => {
+            args.next();
+            return parse_bulls(args);
+        }
+        Some("tui") => {
+            args.next();
+            return parse_tui(args);
+        }
+        Some("words") => {
+            args.next();
+            return parse_words(args);
+        }
+        _ => {}
+    }
+
+    let mut difficulty = DifficultyArgs::default();
+    let mut name: Option<String> = None;
+    let mut scores_file: Option<PathBuf> = None;
+    let mut seed: Option<u64> = None;
+    let mut record: Option<PathBuf> = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            _ if difficulty.parse(&arg, &mut args)? => {}
+            "-n" | "--name" => name = Some(value(&mut args, "--name")?),
+            "--scores-file" => scores_file = Some(value(&mut args, "--scores-file")?.into()),
+            "--seed" => seed = Some(number(&mut args, "--seed")?),
+            "--record" => record = Some(value(&mut args, "--record")?.into()),
+            _ => return Err(CliError::UnknownArgument(arg)),
+        }
+    }
+
+    Ok(Command::Play(PlayOptions {
+        difficulty: difficulty.build()?,
+        name,
+        scores_file,
+        seed,
+        record,
+    }))
+}
+
+/// The `--difficulty`, `--min`, `--max` and `--attempts` flags, shared by
+/// playing and solving.
+#[derive(Default)]
+struct DifficultyArgs {
+    level: Option<String>,
+    min: Option<u32>,
+    max: Option<u32>,
+    attempts: Option<u32>,
+}
+
+impl DifficultyArgs {
+    /// Takes `arg` (and its value) if it is one of the difficulty flags.
+    fn parse<I: Iterator<Item = String>>(
+        &mut self,
+        arg: &str,
+        args: &mut I,
+    ) -> Result<bool, CliError> {
+        match arg {
+            "-d" | "--difficulty" => self.level = Some(value(args, "--difficulty")?),
+            "--min" => self.min = Some(number(args, "--min")?),
+            "--max" => self.max = Some(number(args, "--max")?),
+            "--attempts" => self.attempts = Some(number(args, "--attempts")?),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    fn build(self) -> Result<Option<Difficulty>, CliError> {
+        let difficulty = match self.level.as_deref() {
+            Some(name) if name.eq_ignore_ascii_case("custom") => {
+                let max = self.max.ok_or_else(|| {
+                    CliError::InvalidCustom("the custom level needs --max".to_string())
+                })?;
+                let range = self.min.unwrap_or(1)..=max;
+                let attempts = self.attempts.unwrap_or_else(|| optimal_attempts(&range));
+                let custom = Difficulty::custom(range, attempts).ok_or_else(|| {
+                    CliError::InvalidCustom(
+                        "the custom level needs --min <= --max and at least one attempt"
+                            .to_string(),
+                    )
+                })?;
+                Some(custom)
+            }
+            _ if self.min.is_some() || self.max.is_some() || self.attempts.is_some() => {
+                return Err(CliError::InvalidCustom(
+                    "--min, --max and --attempts only apply to --difficulty custom".to_string(),
+                ))
+            }
+            Some(name) => Some(name.parse().map_err(|_| CliError::InvalidValue {
+                flag: "--difficulty",
+                value: name.to_string(),
+            })?),
+            None => None,
+        };
+        Ok(difficulty)
+    }
+}
+
+fn parse_scores<I: Iterator<Item = String>>(mut args: I) -> Result<Command, CliError> {
+    let mut options = ScoresOptions::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-d" | "--difficulty" => {
+                options.difficulty = Some(value(&mut args, "--difficulty")?.to_ascii_lowercase())
+            }
+            "--scores-file" => {
+                options.scores_file = Some(value(&mut args, "--scores-file")?.into())
+            }
+            _ => return Err(CliError::UnknownArgument(arg)),
+        }
+    }
+    Ok(Command::Scores(options))
+}
+
+fn parse_replay<I: Iterator<Item = String>>(mut args: I) -> Result<Command, CliError> {
+    let file = match args.next() {
+        Some(arg) if arg == "-h" || arg == "--help" => return Ok(Command::Help),
+        Some(file) => file,
+        None => return Err(CliError::MissingValue("replay")),
+    };
+    match args.next() {
+        Some(extra) => Err(CliError::UnknownArgument(extra)),
+        None => Ok(Command::Replay(file.into())),
+    }
+}
+
+fn parse_solve<I: Iterator<Item = String>>(mut args: I) -> Result<Command, CliError> {
+    let mut options = SolveOptions::default();
+    let mut difficulty = DifficultyArgs::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            _ if difficulty.parse(&arg, &mut args)? => {}
+            "-s" | "--strategy" => {
+                let name = value(&mut args, "--strategy")?.to_ascii_lowercase();
+                options.strategy = match name.as_str() {
+                    "all" => None,
+                    "binary" | "random" | "linear" | "human" => Some(name),
+                    _ => {
+                        return Err(CliError::InvalidValue {
+                            flag: "--strategy",
+                            value: name,
+                        })
+                    }
+                };
+            }
+            "-g" | "--games" => options.games = number(&mut args, "--games")?,
+            "--seed" => options.seed = Some(number(&mut args, "--seed")?),
+            _ => return Err(CliError::UnknownArgument(arg)),
+        }
+    }
+    if let Some(difficulty) = difficulty.build()? {
+        options.difficulty = difficulty;
+    }
+    Ok(Command::Solve(options))
+}
+
+fn parse_bulls<I: Iterator<Item = String>>(mut args: I) -> Result<Command, CliError> {
+    let mut options = BullsOptions::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--repeats" => options.allow_repeats = true,
+            "--solve" => options.solve = true,
+            "-n" | "--name" => options.name = Some(value(&mut args, "--name")?),
+            "--scores-file" => {
+                options.scores_file = Some(value(&mut args, "--scores-file")?.into())
+            }
+            "--seed" => options.seed = Some(number(&mut args, "--seed")?),
+            _ => return Err(CliError::UnknownArgument(arg)),
+        }
+    }
+    Ok(Command::Bulls(options))
+}
+
+fn parse_words<I: Iterator<Item = String>>(mut args: I) -> Result<Command, CliError> {
+    let mut options = WordsOptions::default();
+    let mut daily_flag = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--hard" => options.hard = true,
+            "--daily" => {
+                options.daily = true;
+                daily_flag = Some("--daily");
+            }
+            "--date" => {
+                let value = value(&mut args, "--date")?;
+                options.date = Some(words::parse_date(&value).ok_or(CliError::InvalidValue {
+                    flag: "--date",
+                    value,
+                })?);
+                options.daily = true;
+                daily_flag = Some("--date");
+            }
+            "--no-color" => options.no_color = true,
+            "-n" | "--name" => options.name = Some(value(&mut args, "--name")?),
+            "--scores-file" => {
+                options.scores_file = Some(value(&mut args, "--scores-file")?.into())
+            }
+            "--seed" => options.seed = Some(number(&mut args, "--seed")?),
+            _ => return Err(CliError::UnknownArgument(arg)),
+        }
+    }
+    // The daily word does not depend on the seed.
+    if let (Some(flag), Some(_)) = (daily_flag, options.seed) {
+        return Err(CliError::Conflicting(flag, "--seed"));
+    }
+    Ok(Command::Words(options))
+}
+
+fn parse_tui<I: Iterator<Item = String>>(mut args: I) -> Result<Command, CliError> {
+    let mut options = TuiOptions::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-n" | "--name" => options.name = Some(value(&mut args, "--name")?),
+            "--scores-file" => {
+                options.scores_file = Some(value(&mut args, "--scores-file")?.into())
+            }
+            "--seed" => options.seed = Some(number(&mut args, "--seed")?),
+            _ => return Err(CliError::UnknownArgument(arg)),
+        }
+    }
+    Ok(Command::Tui(options))
+}
+
+fn value<I: Iterator<Item = String>>(args: &mut I, flag: &'static str) -> Result<String, CliError> {
+    args.next().ok_or(CliError::MissingValue(flag))
+}
+
+fn number<I: Iterator<Item = String>, T: FromStr>(
+    args: &mut I,
+    flag: &'static str,
+) -> Result<T, CliError> {
+    let value = value(args, flag)?;
+    value
+        .parse()
+        .map_err(|_| CliError::InvalidValue { flag, value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, CliError> {
+        parse_args(args.iter().map(|s| s.to_string()))
+    }
+
+    fn play(difficulty: Option<Difficulty>) -> Command {
+        Command::Play(PlayOptions {
+            difficulty,
+            ..PlayOptions::default()
+        })
+    }
+
+    #[test]
+    fn no_arguments_means_menu() {
+        assert_eq!(parse(&[]), Ok(play(None)));
+    }
+
+    #[test]
+    fn named_difficulty() {
+        assert_eq!(parse(&["-d", "easy"]), Ok(play(Some(Difficulty::Easy))));
+        assert_eq!(
+            parse(&["--difficulty", "Hard"]),
+            Ok(play(Some(Difficulty::Hard)))
+        );
+        assert_eq!(
+            parse(&["-d", "extreme"]),
+            Err(CliError::InvalidValue {
+                flag: "--difficulty",
+                value: "extreme".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn custom_difficulty() {
+        assert_eq!(
+            parse(&[
+                "-d",
+                "custom",
+                "--min",
+                "10",
+                "--max",
+                "20",
+                "--attempts",
+                "3"
+            ]),
+            Ok(play(Difficulty::custom(10..=20, 3)))
+        );
+        assert_eq!(
+            parse(&["-d", "custom", "--max", "1000"]),
+            Ok(play(Difficulty::custom(1..=1000, 10)))
+        );
+        assert!(matches!(
+            parse(&["-d", "custom"]),
+            Err(CliError::InvalidCustom(_))
+        ));
+        assert!(matches!(
+            parse(&["-d", "custom", "--min", "9", "--max", "3"]),
+            Err(CliError::InvalidCustom(_))
+        ));
+        assert!(matches!(
+            parse(&["--max", "3"]),
+            Err(CliError::InvalidCustom(_))
+        ));
+    }
+
+    #[test]
+    fn name_and_scores_file() {
+        assert_eq!(
+            parse(&["-n", "ada", "--scores-file", "/tmp/s.json", "-d", "easy"]),
+            Ok(Command::Play(PlayOptions {
+                difficulty: Some(Difficulty::Easy),
+                name: Some("ada".to_string()),
+                scores_file: Some(PathBuf::from("/tmp/s.json")),
+                ..PlayOptions::default()
+            }))
+        );
+    }
+
+    #[test]
+    fn scores_subcommand() {
+        assert_eq!(
+            parse(&["scores"]),
+            Ok(Command::Scores(ScoresOptions::default()))
+        );
+        assert_eq!(
+            parse(&["scores", "-d", "Hard", "--scores-file", "s.json"]),
+            Ok(Command::Scores(ScoresOptions {
+                difficulty: Some("hard".to_string()),
+                scores_file: Some(PathBuf::from("s.json")),
+            }))
+        );
+        assert_eq!(
+            parse(&["scores", "--max", "3"]),
+            Err(CliError::UnknownArgument("--max".to_string()))
+        );
+    }
+
+    #[test]
+    fn seed_and_record() {
+        assert_eq!(
+            parse(&["--seed", "18446744073709551615", "--record", "game.jsonl"]),
+            Ok(Command::Play(PlayOptions {
+                seed: Some(u64::MAX),
+                record: Some(PathBuf::from("game.jsonl")),
+                ..PlayOptions::default()
+            }))
+        );
+        assert_eq!(
+            parse(&["--seed", "-1"]),
+            Err(CliError::InvalidValue {
+                flag: "--seed",
+                value: "-1".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn replay_subcommand() {
+        assert_eq!(
+            parse(&["replay", "game.jsonl"]),
+            Ok(Command::Replay(PathBuf::from("game.jsonl")))
+        );
+        assert_eq!(parse(&["replay"]), Err(CliError::MissingValue("replay")));
+        assert_eq!(
+            parse(&["replay", "a", "b"]),
+            Err(CliError::UnknownArgument("b".to_string()))
+        );
+    }
+
+    #[test]
+    fn solve_subcommand() {
+        assert_eq!(
+            parse(&["solve"]),
+            Ok(Command::Solve(SolveOptions::default()))
+        );
+        assert_eq!(
+            parse(&[
+                "solve", "-s", "Binary", "-g", "50", "--seed", "3", "-d", "custom", "--max", "8"
+            ]),
+            Ok(Command::Solve(SolveOptions {
+                difficulty: Difficulty::custom(1..=8, 4).unwrap(),
+                strategy: Some("binary".to_string()),
+                games: 50,
+                seed: Some(3),
+            }))
+        );
+        assert_eq!(
+            parse(&["solve", "-s", "all", "-d", "hard"]),
+            Ok(Command::Solve(SolveOptions {
+                difficulty: Difficulty::Hard,
+                ..SolveOptions::default()
+            }))
+        );
+        assert_eq!(
+            parse(&["solve", "-s", "psychic"]),
+            Err(CliError::InvalidValue {
+                flag: "--strategy",
+                value: "psychic".to_string()
+            })
+        );
+        assert_eq!(
+            parse(&["solve", "--name", "ada"]),
+            Err(CliError::UnknownArgument("--name".to_string()))
+        );
+    }
+
+    #[test]
+    fn bulls_subcommand() {
+        assert_eq!(
+            parse(&["bulls"]),
+            Ok(Command::Bulls(BullsOptions::default()))
+        );
+        assert_eq!(
+            parse(&["bulls", "--repeats", "-n", "ada", "--seed", "7"]),
+            Ok(Command::Bulls(BullsOptions {
+                allow_repeats: true,
+                name: Some("ada".to_string()),
+                seed: Some(7),
+                ..BullsOptions::default()
+            }))
+        );
+        assert_eq!(
+            parse(&["bulls", "-d", "easy"]),
+            Err(CliError::UnknownArgument("-d".to_string()))
+        );
+    }
+
+    #[test]
+    fn words_subcommand() {
+        assert_eq!(
+            parse(&["words"]),
+            Ok(Command::Words(WordsOptions::default()))
+        );
+        assert_eq!(
+            parse(&["words", "--hard", "--daily", "--no-color"]),
+            Ok(Command::Words(WordsOptions {
+                hard: true,
+                daily: true,
+                no_color: true,
+                ..WordsOptions::default()
+            }))
+        );
+        assert_eq!(
+            parse(&["words", "--date", "2024-02-29"]),
+            Ok(Command::Words(WordsOptions {
+                daily: true,
+                date: Some(19_782),
+                ..WordsOptions::default()
+            }))
+        );
+        assert_eq!(
+            parse(&["words", "--date", "2024-13-01"]),
+            Err(CliError::InvalidValue {
+                flag: "--date",
+                value: "2024-13-01".to_string()
+            })
+        );
+        assert_eq!(
+            parse(&["words", "--seed", "3", "--daily"]),
+            Err(CliError::Conflicting("--daily", "--seed"))
+        );
+        assert_eq!(
+            parse(&["words", "--date", "2024-02-29", "--seed", "3"]),
+            Err(CliError::Conflicting("--date", "--seed"))
+        );
+    }
+
+    #[test]
+    fn tui_subcommand() {
+        assert_eq!(
+            parse(&["tui", "--seed", "4", "-n", "ada"]),
+            Ok(Command::Tui(TuiOptions {
+                name: Some("ada".to_string()),
+                seed: Some(4),
+                ..TuiOptions::default()
+            }))
+        );
+        assert_eq!(
+            parse(&["tui", "-d", "easy"]),
+            Err(CliError::UnknownArgument("-d".to_string()))
+        );
+    }
+
+    #[test]
+    fn bad_arguments() {
+        assert_eq!(
+            parse(&["--fast"]),
+            Err(CliError::UnknownArgument("--fast".to_string()))
+        );
+        assert_eq!(parse(&["-d"]), Err(CliError::MissingValue("--difficulty")));
+        assert_eq!(
+            parse(&["-d", "custom", "--max", "ten"]),
+            Err(CliError::InvalidValue {
+                flag: "--max",
+                value: "ten".to_string()
+            })
+        );
+        assert_eq!(parse(&["--help", "--fast"]), Ok(Command::Help));
+    }
+}
@@ -0,0 +1,273 @@
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::guess::{read_guess, GuessError};
+
+/// How hard a game is: the range the secret is drawn from and how many
+/// guesses the player gets before losing.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", try_from = "RawDifficulty")]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+    Custom {
+        range: RangeInclusive<u32>,
+        max_attempts: u32,
+    },
+}
+
+/// What a difficulty looks like on the wire, before a custom one has been
+/// through the checks in [`Difficulty::custom`].
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RawDifficulty {
+    Easy,
+    Medium,
+    Hard,
+    Custom {
+        range: RangeInclusive<u32>,
+        max_attempts: u32,
+    },
+}
+
+impl TryFrom<RawDifficulty> for Difficulty {
+    type Error = String;
+
+    fn try_from(raw: RawDifficulty) -> Result<Self, Self::Error> {
+        match raw {
+            RawDifficulty::Easy => Ok(Difficulty::Easy),
+            RawDifficulty::Medium => Ok(Difficulty::Medium),
+            RawDifficulty::Hard => Ok(Difficulty::Hard),
+            RawDifficulty::Custom {
+                range,
+                max_attempts,
+            } => Difficulty::custom(range, max_attempts).ok_or_else(|| {
+                "a custom difficulty needs a non-empty range and at least one attempt".to_string()
+            }),
+        }
+    }
+}
+
+impl Difficulty {
+    /// Builds a custom difficulty, or `None` if the range is empty or no
+    /// attempts are allowed.
+    pub fn custom(range: RangeInclusive<u32>, max_attempts: u32) -> Option<Difficulty> {
+        if range.is_empty() || max_attempts == 0 {
+            return None;
+        }
+        Some(Difficulty::Custom {
+            range,
+            max_attempts,
+        })
+    }
+
+    pub fn range(&self) -> RangeInclusive<u32> {
+        match self {
+            Difficulty::Easy => 1..=10,
+            Difficulty::Medium => 1..=100,
+            Difficulty::Hard => 1..=10_000,
+            Difficulty::Custom { range, .. } => range.clone(),
+        }
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        match self {
+            Difficulty::Easy => 6,
+            Difficulty::Medium => 10,
+            // Exactly what a perfect binary search needs, no room for mistakes.
+            Difficulty::Hard => 14,
+            Difficulty::Custom { max_attempts, .. } => *max_attempts,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Custom { .. } => "custom",
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let range = self.range();
+        write!(
+            f,
+            "{} ({}-{}, {} attempts)",
+            self.name(),
+            range.start(),
+            range.end(),
+            self.max_attempts()
+        )
+    }
+}
+
+/// Parses the named levels. `custom` is not accepted here because it needs a
+/// range and attempt count to go with it.
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            other => Err(format!("unknown difficulty '{}'", other)),
+        }
+    }
+}
+
+/// The worst case number of guesses a binary search needs over `range`.
+pub fn optimal_attempts(range: &RangeInclusive<u32>) -> u32 {
+    if range.is_empty() {
+        return 0;
+    }
+    let size = u64::from(*range.end()) - u64::from(*range.start()) + 1;
+    64 - size.leading_zeros()
+}
+
+/// Shows the difficulty menu and reads the player's choice. Returns `None` if
+/// the input ends before a choice is made.
+pub fn prompt_difficulty<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+) -> io::Result<Option<Difficulty>> {
+    writeln!(output, "Choose a difficulty:")?;
+    for (i, level) in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard]
+        .iter()
+        .enumerate()
+    {
+        writeln!(output, "  {}) {}", i + 1, level)?;
+    }
+    writeln!(output, "  4) custom")?;
+
+    let choice = match prompt_number(input, output, "Enter 1-4:", &(1..=4))? {
+        Some(choice) => choice,
+        None => return Ok(None),
+    };
+
+    let difficulty = match choice {
+        1 => Difficulty::Easy,
+        2 => Difficulty::Medium,
+        3 => Difficulty::Hard,
+        _ => return prompt_custom(input, output),
+    };
+    Ok(Some(difficulty))
+}
+
+fn prompt_custom<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+) -> io::Result<Option<Difficulty>> {
+    let Some(min) = prompt_number(input, output, "Lowest number:", &(0..=u32::MAX))? else {
+        return Ok(None);
+    };
+    let Some(max) = prompt_number(input, output, "Highest number:", &(min..=u32::MAX))? else {
+        return Ok(None);
+    };
+    let range = min..=max;
+    let suggested = optimal_attempts(&range);
+    let prompt = format!("Number of attempts (a perfect player needs {}):", suggested);
+    let Some(attempts) = prompt_number(input, output, &prompt, &(1..=u32::MAX))? else {
+        return Ok(None);
+    };
+    Ok(Difficulty::custom(range, attempts))
+}
+
+/// Asks for a number in `range` until one is given or the input ends.
+fn prompt_number<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    prompt: &str,
+    range: &RangeInclusive<u32>,
+) -> io::Result<Option<u32>> {
+    loop {
+        writeln!(output, "{}", prompt)?;
+        output.flush()?;
+        match read_guess(input, range) {
+            Ok(num) => return Ok(Some(num)),
+            Err(GuessError::Eof) => return Ok(None),
+            Err(e) => writeln!(output, "{}", e)?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_levels_parse_case_insensitively() {
+        assert_eq!("Easy".parse(), Ok(Difficulty::Easy));
+        assert_eq!("HARD".parse(), Ok(Difficulty::Hard));
+        assert!("custom".parse::<Difficulty>().is_err());
+    }
+
+    #[test]
+    fn custom_rejects_empty_range_and_zero_attempts() {
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty = 5..=4;
+        assert_eq!(Difficulty::custom(empty, 3), None);
+        assert_eq!(Difficulty::custom(1..=5, 0), None);
+        assert_eq!(Difficulty::custom(7..=7, 1).unwrap().range(), 7..=7);
+    }
+
+    #[test]
+    fn deserializing_checks_custom_difficulties() {
+        let custom = Difficulty::custom(3..=9, 2).unwrap();
+        let json = serde_json::to_string(&custom).unwrap();
+        assert_eq!(serde_json::from_str::<Difficulty>(&json).unwrap(), custom);
+        let hard: Difficulty = serde_json::from_str(r#""hard""#).unwrap();
+        assert_eq!(hard, Difficulty::Hard);
+
+        for bad in [
+            r#"{"custom":{"range":{"start":9,"end":1},"max_attempts":3}}"#,
+            r#"{"custom":{"range":{"start":1,"end":9},"max_attempts":0}}"#,
+        ] {
+            let error = serde_json::from_str::<Difficulty>(bad).unwrap_err();
+            assert!(error.to_string().contains("non-empty range"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn optimal_attempts_matches_binary_search() {
+        assert_eq!(optimal_attempts(&(1..=1)), 1);
+        assert_eq!(optimal_attempts(&(1..=10)), 4);
+        assert_eq!(optimal_attempts(&(1..=100)), 7);
+        assert_eq!(optimal_attempts(&(1..=10_000)), 14);
+        assert_eq!(optimal_attempts(&(0..=u32::MAX)), 33);
+    }
+
+    #[test]
+    fn menu_reprompts_then_accepts_choice() {
+        let mut input: &[u8] = b"9\n3\n";
+        let mut output = Vec::new();
+        let choice = prompt_difficulty(&mut input, &mut output).unwrap();
+        assert_eq!(choice, Some(Difficulty::Hard));
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("9 is out of range"));
+    }
+
+    #[test]
+    fn menu_builds_custom_difficulty() {
+        let mut input: &[u8] = b"4\n5\n2\n50\n8\n";
+        let mut output = Vec::new();
+        let choice = prompt_difficulty(&mut input, &mut output).unwrap();
+        assert_eq!(choice, Difficulty::custom(5..=50, 8));
+    }
+
+    #[test]
+    fn menu_gives_up_on_eof() {
+        let mut input: &[u8] = b"4\n5\n";
+        let mut output = Vec::new();
+        assert_eq!(prompt_difficulty(&mut input, &mut output).unwrap(), None);
+    }
+}
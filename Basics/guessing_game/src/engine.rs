@@ -0,0 +1,243 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::difficulty::Difficulty;
+use crate::game::Outcome;
+
+/// What a valid guess tells the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Hint {
+    TooSmall,
+    TooBig,
+    Correct,
+}
+
+/// Why a guess was refused. A refused guess does not cost an attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoundError {
+    OutOfRange {
+        guess: u32,
+        range: RangeInclusive<u32>,
+    },
+    /// The round is already won, lost or abandoned.
+    GameOver(Outcome),
+}
+
+impl fmt::Display for RoundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RoundError::OutOfRange { guess, range } => write!(
+                f,
+                "{} is out of range, pick a number between {} and {}.",
+                guess,
+                range.start(),
+                range.end()
+            ),
+            RoundError::GameOver(_) => write!(f, "The game is already over."),
+        }
+    }
+}
+
+impl std::error::Error for RoundError {}
+
+/// The rules of one game, with no I/O: a secret, a range and a limited
+/// number of attempts. Both the terminal game and the solver bots play
+/// through this.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Round {
+    secret: u32,
+    range: RangeInclusive<u32>,
+    /// What is left of `range` after every hint so far.
+    bounds: RangeInclusive<u32>,
+    max_attempts: u32,
+    attempts: u32,
+    outcome: Option<Outcome>,
+}
+
+impl Round {
+    /// Starts a round with a secret drawn from `rng`.
+    pub fn new<G: RngCore + ?Sized>(difficulty: &Difficulty, rng: &mut G) -> Round {
+        let secret = rng.gen_range(difficulty.range());
+        Round::with_secret(difficulty, secret)
+    }
+
+    /// Starts a round with a known secret. Panics if it is outside the
+    /// difficulty's range.
+    pub fn with_secret(difficulty: &Difficulty, secret: u32) -> Round {
+        let range = difficulty.range();
+        assert!(
+            range.contains(&secret),
+            "secret {} outside {:?}",
+            secret,
+            range
+        );
+        Round {
+            secret,
+            bounds: range.clone(),
+            range,
+            max_attempts: difficulty.max_attempts(),
+            attempts: 0,
+            outcome: None,
+        }
+    }
+
+    /// Checks a guess against the secret, counting it as an attempt.
+    pub fn guess(&mut self, guess: u32) -> Result<Hint, RoundError> {
+        if let Some(outcome) = self.outcome {
+            return Err(RoundError::GameOver(outcome));
+        }
+        if !self.range.contains(&guess) {
+            return Err(RoundError::OutOfRange {
+                guess,
+                range: self.range.clone(),
+            });
+        }
+
+        self.attempts += 1;
+        let (secret, attempts) = (self.secret, self.attempts);
+        let hint = match guess.cmp(&secret) {
+            Ordering::Less => Hint::TooSmall,
+            Ordering::Greater => Hint::TooBig,
+            Ordering::Equal => Hint::Correct,
+        };
+
+        let (low, high) = (*self.bounds.start(), *self.bounds.end());
+        self.bounds = match hint {
+            Hint::TooSmall => low.max(guess + 1)..=high,
+            Hint::TooBig => low..=high.min(guess - 1),
+            Hint::Correct => guess..=guess,
+        };
+
+        if hint == Hint::Correct {
+            self.outcome = Some(Outcome::Won { secret, attempts });
+        } else if attempts >= self.max_attempts {
+            self.outcome = Some(Outcome::Lost { secret, attempts });
+        }
+        Ok(hint)
+    }
+
+    /// Abandons the round. Does nothing if it is already over.
+    pub fn quit(&mut self) -> Outcome {
+        *self.outcome.get_or_insert(Outcome::Quit {
+            secret: self.secret,
+            attempts: self.attempts,
+        })
+    }
+
+    /// How the round ended, or `None` while it is still being played.
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    pub fn secret(&self) -> u32 {
+        self.secret
+    }
+
+    pub fn range(&self) -> RangeInclusive<u32> {
+        self.range.clone()
+    }
+
+    /// The numbers that are still consistent with every hint so far. Guesses
+    /// outside them are allowed, just wasted.
+    pub fn bounds(&self) -> RangeInclusive<u32> {
+        self.bounds.clone()
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    pub fn attempts_left(&self) -> u32 {
+        self.max_attempts.saturating_sub(self.attempts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hints_and_win() {
+        let mut round = Round::with_secret(&Difficulty::Medium, 40);
+        assert_eq!(round.guess(10), Ok(Hint::TooSmall));
+        assert_eq!(round.guess(90), Ok(Hint::TooBig));
+        assert_eq!(round.bounds(), 11..=89);
+        assert_eq!(round.guess(5), Ok(Hint::TooSmall));
+        assert_eq!(round.bounds(), 11..=89);
+        assert_eq!(round.outcome(), None);
+        assert_eq!(round.attempts_left(), 7);
+        assert_eq!(round.guess(40), Ok(Hint::Correct));
+        assert_eq!(round.bounds(), 40..=40);
+        assert_eq!(
+            round.outcome(),
+            Some(Outcome::Won {
+                secret: 40,
+                attempts: 4
+            })
+        );
+    }
+
+    #[test]
+    fn refuses_out_of_range_without_charging_an_attempt() {
+        let mut round = Round::with_secret(&Difficulty::Easy, 4);
+        assert_eq!(
+            round.guess(11),
+            Err(RoundError::OutOfRange {
+                guess: 11,
+                range: 1..=10
+            })
+        );
+        assert_eq!(round.attempts(), 0);
+    }
+
+    #[test]
+    fn loses_on_last_attempt_and_refuses_further_guesses() {
+        let mut round = Round::with_secret(&Difficulty::custom(1..=10, 2).unwrap(), 7);
+        round.guess(1).unwrap();
+        round.guess(2).unwrap();
+        let lost = Outcome::Lost {
+            secret: 7,
+            attempts: 2,
+        };
+        assert_eq!(round.outcome(), Some(lost));
+        assert_eq!(round.guess(7), Err(RoundError::GameOver(lost)));
+        assert_eq!(round.quit(), lost);
+    }
+
+    #[test]
+    fn a_round_without_attempts_ends_on_the_first_guess() {
+        let hopeless = Difficulty::Custom {
+            range: 1..=10,
+            max_attempts: 0,
+        };
+        let mut round = Round::with_secret(&hopeless, 7);
+        assert_eq!(round.attempts_left(), 0);
+        assert_eq!(round.guess(1), Ok(Hint::TooSmall));
+        assert_eq!(round.attempts_left(), 0);
+        assert!(matches!(round.outcome(), Some(Outcome::Lost { .. })));
+    }
+
+    #[test]
+    fn quit_ends_the_round() {
+        let mut round = Round::with_secret(&Difficulty::Easy, 3);
+        round.guess(5).unwrap();
+        let quit = round.quit();
+        assert_eq!(
+            quit,
+            Outcome::Quit {
+                secret: 3,
+                attempts: 1
+            }
+        );
+        assert_eq!(round.guess(3), Err(RoundError::GameOver(quit)));
+    }
+}
@@ -0,0 +1,182 @@
+use std::io::{self, BufRead, Write};
+
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::difficulty::Difficulty;
+use crate::engine::{Hint, Round};
+use crate::guess::{parse_guess, read_input, GuessError};
+
+/// How a game ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum Outcome {
+    /// The player found the secret.
+    Won { secret: u32, attempts: u32 },
+    /// The player used up every attempt without finding the secret.
+    Lost { secret: u32, attempts: u32 },
+    /// Input ran out before the secret was found.
+    Quit { secret: u32, attempts: u32 },
+}
+
+/// What the game said back to one line of input.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Response {
+    /// The line was not a usable guess; it did not cost an attempt.
+    Invalid(String),
+    TooSmall,
+    TooBig,
+    Correct,
+}
+
+impl From<Hint> for Response {
+    fn from(hint: Hint) -> Self {
+        match hint {
+            Hint::TooSmall => Response::TooSmall,
+            Hint::TooBig => Response::TooBig,
+            Hint::Correct => Response::Correct,
+        }
+    }
+}
+
+/// One line of input and the game's response to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Turn {
+    pub input: String,
+    pub response: Response,
+    /// The line could not be read at all, e.g. because it was not valid
+    /// UTF-8, so `input` is empty.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unreadable: bool,
+}
+
+/// One round of the guessing game, reading guesses from `input` and writing
+/// prompts and hints to `output`. The secret is drawn from `rng`.
+pub struct Game<R, W, G> {
+    input: R,
+    output: W,
+    rng: G,
+    difficulty: Difficulty,
+    turns: Vec<Turn>,
+}
+
+impl<R: BufRead, W: Write, G: RngCore> Game<R, W, G> {
+    pub fn new(input: R, output: W, rng: G) -> Self {
+        Game::with_difficulty(input, output, rng, Difficulty::default())
+    }
+
+    pub fn with_difficulty(input: R, output: W, rng: G, difficulty: Difficulty) -> Self {
+        Game {
+            input,
+            output,
+            rng,
+            difficulty,
+            turns: Vec::new(),
+        }
+    }
+
+    /// Plays until the player wins, runs out of attempts or the input ends.
+    pub fn play(&mut self) -> io::Result<Outcome> {
+        let range = self.difficulty.range();
+
+        writeln!(self.output, "Guess the number!")?;
+        writeln!(
+            self.output,
+            "The secret is between {} and {}, you have {} attempts.",
+            range.start(),
+            range.end(),
+            self.difficulty.max_attempts()
+        )?;
+
+        let mut round = Round::new(&self.difficulty, &mut self.rng);
+
+        loop {
+            writeln!(self.output, "Please input your guess.")?;
+            self.output.flush()?;
+
+            let (line, guess) = match read_input(&mut self.input) {
+                Ok(line) => {
+                    let guess = parse_guess(&line, &range);
+                    (line, guess)
+                }
+                Err(e) => (String::new(), Err(e)),
+            };
+            let unreadable = matches!(guess, Err(GuessError::Io(_)));
+            let guess = match guess {
+                Ok(num) => num,
+                Err(GuessError::Eof) => {
+                    writeln!(
+                        self.output,
+                        "No more input. The secret number was {}.",
+                        round.secret()
+                    )?;
+                    return Ok(round.quit());
+                }
+                Err(e) => {
+                    writeln!(self.output, "{}", e)?;
+                    self.turns.push(Turn {
+                        input: line,
+                        response: Response::Invalid(e.to_string()),
+                        unreadable,
+                    });
+                    continue;
+                }
+            };
+
+            let hint = match round.guess(guess) {
+                Ok(hint) => hint,
+                Err(e) => {
+                    writeln!(self.output, "{}", e)?;
+                    self.record(line, Response::Invalid(e.to_string()));
+                    continue;
+                }
+            };
+            writeln!(self.output, "You guessed: {}", guess)?;
+            self.record(line, hint.into());
+
+            match hint {
+                Hint::TooSmall => writeln!(self.output, "Too small!")?,
+                Hint::TooBig => writeln!(self.output, "Too big!")?,
+                Hint::Correct => {
+                    writeln!(
+                        self.output,
+                        "You win! It took you {} attempts.",
+                        round.attempts()
+                    )?;
+                }
+            }
+
+            match round.outcome() {
+                Some(outcome @ Outcome::Lost { secret, .. }) => {
+                    writeln!(self.output, "You lose! The secret number was {}.", secret)?;
+                    return Ok(outcome);
+                }
+                Some(outcome) => return Ok(outcome),
+                None => writeln!(self.output, "{} attempts left.", round.attempts_left())?,
+            }
+        }
+    }
+
+    fn record(&mut self, input: String, response: Response) {
+        self.turns.push(Turn {
+            input,
+            response,
+            unreadable: false,
+        });
+    }
+
+    pub fn difficulty(&self) -> &Difficulty {
+        &self.difficulty
+    }
+
+    /// Every line the player entered so far, with the game's response.
+    pub fn turns(&self) -> &[Turn] {
+        &self.turns
+    }
+
+    /// Gives back the output, e.g. to inspect what was written to a `Vec<u8>`.
+    pub fn into_output(self) -> W {
+        self.output
+    }
+}
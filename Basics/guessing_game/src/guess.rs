@@ -0,0 +1,167 @@
+use std::fmt;
+use std::io::{self, BufRead};
+use std::num::IntErrorKind;
+use std::ops::RangeInclusive;
+
+/// Everything that can go wrong between reading a line and getting a usable guess.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuessError {
+    /// The line was blank (or only whitespace).
+    Empty,
+    /// The line contained something other than a whole number.
+    NotANumber(String),
+    /// The number parsed, but falls outside the game's range.
+    OutOfRange { value: i64, range: RangeInclusive<u32> },
+    /// The number is too large to fit in a guess at all.
+    Overflow,
+    /// Input ended (Ctrl-D / Ctrl-Z) before a guess was entered.
+    Eof,
+    /// Reading from the input failed, e.g. because it was not valid UTF-8.
+    Io(io::ErrorKind),
+}
+
+impl fmt::Display for GuessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GuessError::Empty => write!(f, "Please type a number before pressing Enter."),
+            GuessError::NotANumber(input) => write!(f, "'{}' is not a whole number.", input),
+            GuessError::OutOfRange { value, range } => write!(
+                f,
+                "{} is out of range, pick a number between {} and {}.",
+                value,
+                range.start(),
+                range.end()
+            ),
+            GuessError::Overflow => write!(f, "That number is far too big."),
+            GuessError::Eof => write!(f, "No more input."),
+            GuessError::Io(kind) => write!(f, "Could not read your guess ({}).", kind),
+        }
+    }
+}
+
+impl std::error::Error for GuessError {}
+
+/// Parses one line of user input into a guess within `range`.
+pub fn parse_guess(input: &str, range: &RangeInclusive<u32>) -> Result<u32, GuessError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(GuessError::Empty);
+    }
+
+    let value: u32 = match input.parse() {
+        Ok(num) => num,
+        Err(e) => {
+            return Err(match e.kind() {
+                IntErrorKind::PosOverflow => GuessError::Overflow,
+                // `u32` refuses any sign, so give negative numbers a more useful message.
+                _ => match input.parse::<i64>() {
+                    Ok(value) => GuessError::OutOfRange { value, range: range.clone() },
+                    Err(e) if *e.kind() == IntErrorKind::NegOverflow => GuessError::Overflow,
+                    Err(_) => GuessError::NotANumber(input.to_string()),
+                },
+            })
+        }
+    };
+
+    if range.contains(&value) {
+        Ok(value)
+    } else {
+        Err(GuessError::OutOfRange { value: value.into(), range: range.clone() })
+    }
+}
+
+/// Reads a single line from `input` and parses it with [`parse_guess`].
+pub fn read_guess<R: BufRead>(input: &mut R, range: &RangeInclusive<u32>) -> Result<u32, GuessError> {
+    parse_guess(&read_input(input)?, range)
+}
+
+/// Reads a single line from `input`, without its line ending.
+pub fn read_input<R: BufRead>(input: &mut R) -> Result<String, GuessError> {
+    let mut line = String::new();
+    match input.read_line(&mut line) {
+        Ok(0) => Err(GuessError::Eof),
+        Ok(_) => {
+            let len = line.trim_end_matches(['\r', '\n']).len();
+            line.truncate(len);
+            Ok(line)
+        }
+        Err(e) => Err(GuessError::Io(e.kind())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RANGE: RangeInclusive<u32> = 1..=100;
+
+    #[test]
+    fn parses_valid_guess_with_whitespace() {
+        assert_eq!(parse_guess("  42 \r\n", &RANGE), Ok(42));
+    }
+
+    #[test]
+    fn rejects_empty_input() {
+        assert_eq!(parse_guess("   \n", &RANGE), Err(GuessError::Empty));
+    }
+
+    #[test]
+    fn rejects_non_numeric_input() {
+        assert_eq!(
+            parse_guess("forty", &RANGE),
+            Err(GuessError::NotANumber("forty".to_string()))
+        );
+        assert_eq!(
+            parse_guess("4 2", &RANGE),
+            Err(GuessError::NotANumber("4 2".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_range_input() {
+        assert_eq!(
+            parse_guess("101", &RANGE),
+            Err(GuessError::OutOfRange { value: 101, range: RANGE })
+        );
+        assert_eq!(
+            parse_guess("0", &RANGE),
+            Err(GuessError::OutOfRange { value: 0, range: RANGE })
+        );
+        assert_eq!(
+            parse_guess("-7", &RANGE),
+            Err(GuessError::OutOfRange { value: -7, range: RANGE })
+        );
+    }
+
+    #[test]
+    fn rejects_overflowing_input() {
+        assert_eq!(parse_guess("99999999999", &RANGE), Err(GuessError::Overflow));
+        assert_eq!(
+            parse_guess("-99999999999999999999", &RANGE),
+            Err(GuessError::Overflow)
+        );
+    }
+
+    #[test]
+    fn reports_eof() {
+        let mut input: &[u8] = b"";
+        assert_eq!(read_guess(&mut input, &RANGE), Err(GuessError::Eof));
+    }
+
+    #[test]
+    fn reports_invalid_utf8_as_io_error() {
+        let mut input: &[u8] = b"\xff\xfe\n";
+        assert_eq!(
+            read_guess(&mut input, &RANGE),
+            Err(GuessError::Io(io::ErrorKind::InvalidData))
+        );
+    }
+
+    #[test]
+    fn reads_successive_lines() {
+        let mut input: &[u8] = b"abc\n12\n";
+        assert!(matches!(read_guess(&mut input, &RANGE), Err(GuessError::NotANumber(_))));
+        assert_eq!(read_guess(&mut input, &RANGE), Ok(12));
+        assert_eq!(read_guess(&mut input, &RANGE), Err(GuessError::Eof));
+    }
+}
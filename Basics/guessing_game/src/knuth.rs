@@ -0,0 +1,243 @@
+//! Knuth's minimax solver for Bulls and Cows and Mastermind.
+//!
+//! After each answer the solver keeps the codes that would have given the
+//! same answers, then picks the guess whose worst answer leaves the fewest
+//! of them, preferring guesses that could still be the secret. Knuth showed
+//! this never needs more than five guesses for classic Mastermind; for four
+//! different digits out of ten the best any strategy can promise is seven.
+
+use std::collections::BTreeMap;
+
+use crate::bulls::{BullsOutcome, BullsRound, Code, Rules, Score, MAX_LENGTH};
+
+/// The proven best worst case, where one is known.
+pub fn best_possible(rules: &Rules) -> Option<u32> {
+    match (rules.length, rules.symbols, rules.allow_repeats) {
+        (4, 10, false) => Some(7),
+        (4, 6, true) => Some(5),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Knuth {
+    rules: Rules,
+    all: Vec<Code>,
+    /// Codes consistent with every answer so far.
+    candidates: Vec<Code>,
+}
+
+impl Knuth {
+    pub fn new(rules: Rules) -> Knuth {
+        let all = rules.all_codes();
+        Knuth {
+            rules,
+            candidates: all.clone(),
+            all,
+        }
+    }
+
+    /// Starts over for a new secret.
+    pub fn reset(&mut self) {
+        self.candidates = self.all.clone();
+    }
+
+    pub fn candidates(&self) -> &[Code] {
+        &self.candidates
+    }
+
+    pub fn next_guess(&self) -> Code {
+        if self.candidates.len() == self.all.len() {
+            opening(&self.rules)
+        } else {
+            minimax(&self.rules, &self.all, &self.candidates)
+        }
+    }
+
+    /// Drops the codes that would not have answered `guess` with `score`.
+    pub fn observe(&mut self, guess: &Code, score: Score) {
+        self.candidates
+            .retain(|code| Score::of(code, guess) == score);
+    }
+
+    /// Plays `round` to the end.
+    pub fn solve(&mut self, round: &mut BullsRound) -> BullsOutcome {
+        self.reset();
+        loop {
+            let guess = self.next_guess();
+            match round.guess(&guess) {
+                Ok(score) => self.observe(&guess, score),
+                Err(e) => panic!("the solver guessed {}: {}", guess, e),
+            }
+            if let Some(outcome) = round.outcome() {
+                return outcome;
+            }
+        }
+    }
+}
+
+/// The first guess, which does not depend on the secret. Working it out
+/// with [`minimax`] means scoring every code against every other, so use
+/// the shape Knuth found best instead: `0011` with repeats, `0123`
+/// without.
+fn opening(rules: &Rules) -> Code {
+    let text: String = (0..rules.length)
+        .map(|i| {
+            let digit = if rules.allow_repeats { i / 2 } else { i };
+            char::from(b'0' + (digit % usize::from(rules.symbols)) as u8)
+        })
+        .collect();
+    rules.parse(&text).expect("the opening follows the rules")
+}
+
+/// The guess whose largest group of remaining candidates is smallest.
+fn minimax(rules: &Rules, all: &[Code], candidates: &[Code]) -> Code {
+    if candidates.len() <= 2 {
+        return candidates[0];
+    }
+    let mut best: Option<(usize, bool, Code)> = None;
+    let mut counts = [0usize; (MAX_LENGTH + 1) * (MAX_LENGTH + 1)];
+    'guesses: for guess in all {
+        let limit = best.map_or(usize::MAX, |(worst, _, _)| worst);
+        counts.fill(0);
+        let mut worst = 0;
+        for code in candidates {
+            let count = &mut counts[Score::of(code, guess).index(rules.length)];
+            *count += 1;
+            worst = worst.max(*count);
+            // Already worse than the best guess so far.
+            if worst > limit {
+                continue 'guesses;
+            }
+        }
+        let possible = candidates.binary_search(guess).is_ok();
+        // Fewer left in the worst case wins; on a tie, a guess that might
+        // be right; then the lowest code.
+        let better = match best {
+            None => true,
+            Some((best_worst, best_possible, _)) => {
+                worst < best_worst || (worst == best_worst && possible && !best_possible)
+            }
+        };
+        if better {
+            best = Some((worst, possible, *guess));
+        }
+    }
+    best.unwrap().2
+}
+
+/// How many guesses the solver needs for each secret.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Games won in that many guesses.
+    pub histogram: BTreeMap<u32, u32>,
+}
+
+impl Report {
+    pub fn games(&self) -> u32 {
+        self.histogram.values().sum()
+    }
+
+    pub fn worst(&self) -> u32 {
+        self.histogram.keys().next_back().copied().unwrap_or(0)
+    }
+
+    pub fn average(&self) -> f64 {
+        let total: u64 = self
+            .histogram
+            .iter()
+            .map(|(&guesses, &games)| u64::from(guesses) * u64::from(games))
+            .sum();
+        total as f64 / f64::from(self.games().max(1))
+    }
+}
+
+/// Runs the solver against every possible secret.
+///
+/// The solver is deterministic, so every secret that has received the same
+/// answers so far gets the same next guess. Walking that decision tree
+/// once is much faster than playing each secret separately, and gives the
+/// same numbers.
+pub fn solve_all(rules: &Rules) -> Report {
+    let all = rules.all_codes();
+    let mut report = Report::default();
+    walk(rules, &all, all.clone(), 1, &mut report);
+    report
+}
+
+fn walk(rules: &Rules, all: &[Code], candidates: Vec<Code>, depth: u32, report: &mut Report) {
+    let guess = if depth == 1 {
+        opening(rules)
+    } else {
+        minimax(rules, all, &candidates)
+    };
+    let mut groups: BTreeMap<usize, Vec<Code>> = BTreeMap::new();
+    for code in candidates {
+        if code == guess {
+            *report.histogram.entry(depth).or_default() += 1;
+        } else {
+            let index = Score::of(&code, &guess).index(rules.length);
+            groups.entry(index).or_default().push(code);
+        }
+    }
+    for group in groups.into_values() {
+        walk(rules, all, group, depth + 1, report);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solves_a_game_through_the_round() {
+        let rules = Rules::bulls_and_cows();
+        let secret = rules.parse("9876").unwrap();
+        let mut round = BullsRound::with_secret(rules.clone(), secret);
+        let outcome = Knuth::new(rules).solve(&mut round);
+        assert!(
+            matches!(outcome, BullsOutcome::Won { attempts, .. } if attempts <= 7),
+            "{:?}",
+            outcome
+        );
+    }
+
+    #[test]
+    fn matches_knuths_bound_for_mastermind() {
+        let rules = Rules::mastermind();
+        let report = solve_all(&rules);
+        assert_eq!(report.games(), 1296);
+        assert_eq!(report.worst(), 5);
+        assert_eq!(best_possible(&rules), Some(5));
+    }
+
+    #[test]
+    fn never_needs_more_than_seven_for_bulls_and_cows() {
+        let rules = Rules::bulls_and_cows();
+        let report = solve_all(&rules);
+        assert_eq!(report.games(), 5040);
+        assert_eq!(Some(report.worst()), best_possible(&rules));
+        assert!(report.average() < 5.5, "{}", report.average());
+    }
+
+    #[test]
+    fn the_tree_agrees_with_playing_each_secret() {
+        let rules = Rules {
+            length: 3,
+            symbols: 5,
+            ..Rules::bulls_and_cows()
+        };
+        let mut knuth = Knuth::new(rules.clone());
+        let mut played = Report::default();
+        for secret in rules.all_codes() {
+            let mut round = BullsRound::with_secret(rules.clone(), secret);
+            match knuth.solve(&mut round) {
+                BullsOutcome::Won { attempts, .. } => {
+                    *played.histogram.entry(attempts).or_default() += 1
+                }
+                other => panic!("{:?}", other),
+            }
+        }
+        assert_eq!(played, solve_all(&rules));
+    }
+}
@@ -0,0 +1,22 @@
+//! The guessing game from chapter 2 of the Rust book, split out as a library
+//! so it can be driven by something other than a terminal.
+
+pub mod bulls;
+pub mod cli;
+pub mod difficulty;
+pub mod engine;
+pub mod game;
+pub mod guess;
+pub mod knuth;
+pub mod replay;
+pub mod scores;
+pub mod solver;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod vault;
+pub mod words;
+
+pub use difficulty::Difficulty;
+pub use engine::{Hint, Round, RoundError};
+pub use game::{Game, Outcome, Response, Turn};
+pub use guess::{parse_guess, read_guess, read_input, GuessError};
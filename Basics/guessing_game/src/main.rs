@@ -1,15 +1,377 @@
-use std::io;
-use rand::Rng;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, Instant};
 
-fn main() {
-    println!("Guess the number!");
-    println!("Please input your guess.");
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
-    let mut guess = String::new();
+use guessing_game::bulls::{self, BullsOutcome, Rules};
+use guessing_game::cli::{
+    self, BullsOptions, Command, PlayOptions, ScoresOptions, SolveOptions, TuiOptions, WordsOptions,
+};
+use guessing_game::difficulty::prompt_difficulty;
+use guessing_game::replay::{self, Session};
+use guessing_game::scores::{self, LoadStatus, ScoreBoard, ScoreEntry};
+use guessing_game::words::{self, Dictionary, WordOutcome, WordRound};
+use guessing_game::{knuth, solver};
+use guessing_game::{Game, Outcome};
 
-    io::stdin()
-        .read_line(&mut guess)
-        .expect("Failed to read line");
+fn main() -> io::Result<()> {
+    match cli::parse_args(env::args().skip(1)) {
+        Ok(Command::Play(options)) => play(options),
+        Ok(Command::Scores(options)) => show_scores(options),
+        Ok(Command::Replay(file)) => run_replay(&file),
+        Ok(Command::Solve(options)) => solve(options),
+        Ok(Command::Bulls(options)) => play_bulls(options),
+        Ok(Command::Words(options)) => play_words(options),
+        Ok(Command::Tui(options)) => play_tui(options),
+        Ok(Command::Help) => {
+            println!("{}", cli::USAGE);
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, cli::USAGE);
+            process::exit(2);
+        }
+    }
+}
+
+fn play(options: PlayOptions) -> io::Result<()> {
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout();
+
+    let difficulty = match options.difficulty {
+        Some(difficulty) => difficulty,
+        None => match prompt_difficulty(&mut stdin, &mut stdout)? {
+            Some(difficulty) => difficulty,
+            None => return Ok(()),
+        },
+    };
+
+    // Always play from a known seed so every session can be replayed.
+    let seed = options.seed.unwrap_or_else(rand::random);
+    let rng = StdRng::seed_from_u64(seed);
+
+    let started = Instant::now();
+    let mut game = Game::with_difficulty(stdin, stdout, rng, difficulty.clone());
+    let outcome = game.play()?;
+
+    let session = Session {
+        seed,
+        difficulty: difficulty.clone(),
+        turns: game.turns().to_vec(),
+        outcome,
+    };
+    save_replay(&session, options.record);
+
+    let (won, attempts) = match outcome {
+        Outcome::Won { attempts, .. } => (true, attempts),
+        Outcome::Lost { attempts, .. } => (false, attempts),
+        Outcome::Quit { .. } => return Ok(()),
+    };
+
+    let Some(mut board) = open_board(options.scores_file) else {
+        return Ok(());
+    };
+    let player = options.name.unwrap_or_else(default_player);
+    record_score(
+        &mut board,
+        player,
+        difficulty.to_string(),
+        (won, attempts),
+        started.elapsed(),
+    );
+    Ok(())
+}
+
+/// Only reads the score file: a file that cannot be used is reported, and
+/// left for the next game to set aside.
+fn show_scores(options: ScoresOptions) -> io::Result<()> {
+    let Some(path) = options.scores_file.or_else(scores::default_path) else {
+        eprintln!("error: no data directory found, there are no scores");
+        process::exit(1);
+    };
+    match ScoreBoard::load(&path) {
+        Ok((_, LoadStatus::Unusable { reason })) => {
+            eprintln!("error: cannot show {} because {}", path.display(), reason);
+            process::exit(1);
+        }
+        Ok((board, _)) => {
+            board.print_leaderboards(&mut io::stdout(), options.difficulty.as_deref())
+        }
+        Err(e) => {
+            eprintln!("error: could not read {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    }
+}
+
+fn save_replay(session: &Session, path: Option<PathBuf>) {
+    let path = match path {
+        Some(path) => path,
+        None => match replay::default_dir() {
+            Some(dir) => dir.join(format!("{}-{}.jsonl", scores::now(), session.seed)),
+            None => return,
+        },
+    };
+    let write = || -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        session.write(&mut BufWriter::new(File::create(&path)?))
+    };
+    match write() {
+        Ok(()) => println!("Replay saved to {}", path.display()),
+        Err(e) => eprintln!("warning: could not save replay {}: {}", path.display(), e),
+    }
+}
+
+fn run_replay(file: &Path) -> io::Result<()> {
+    let result = File::open(file)
+        .map_err(Into::into)
+        .and_then(|f| Session::read(BufReader::new(f)))
+        .and_then(|session| session.verify().map(|()| session));
+    match result {
+        Ok(session) => {
+            println!(
+                "{}: {} turns on {} with seed {} replayed identically, {:?}",
+                file.display(),
+                session.turns.len(),
+                session.difficulty,
+                session.seed,
+                session.outcome
+            );
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("error: {}: {}", file.display(), e);
+            process::exit(1);
+        }
+    }
+}
+
+fn solve(options: SolveOptions) -> io::Result<()> {
+    let strategies = match &options.strategy {
+        Some(name) => solver::strategy(name).into_iter().collect(),
+        None => solver::strategies(),
+    };
+    let seed = options.seed.unwrap_or_else(rand::random);
+    let mut out = io::stdout().lock();
+
+    writeln!(
+        out,
+        "{} games per strategy on {}, seed {}",
+        options.games, options.difficulty, seed
+    )?;
+    for mut strategy in strategies {
+        // Reseed per strategy so each one faces the same secrets.
+        let mut rng = StdRng::seed_from_u64(seed);
+        let stats = solver::simulate(
+            strategy.as_mut(),
+            &options.difficulty,
+            options.games,
+            &mut rng,
+        );
+        writeln!(out)?;
+        stats.print(&mut out)?;
+    }
+    Ok(())
+}
+
+fn play_bulls(options: BullsOptions) -> io::Result<()> {
+    let rules = Rules::bulls_and_cows().with_repeats(options.allow_repeats);
+    if options.solve {
+        return solve_bulls(&rules);
+    }
+
+    let mut rng = match options.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let started = Instant::now();
+    let outcome = bulls::play(
+        &mut io::stdin().lock(),
+        &mut io::stdout(),
+        &mut rng,
+        rules.clone(),
+    )?;
+    let (won, attempts) = match outcome {
+        BullsOutcome::Won { attempts, .. } => (true, attempts),
+        BullsOutcome::Lost { attempts, .. } => (false, attempts),
+        BullsOutcome::Quit { .. } => return Ok(()),
+    };
+
+    let Some(mut board) = open_board(options.scores_file) else {
+        return Ok(());
+    };
+    let player = options.name.unwrap_or_else(default_player);
+    record_score(
+        &mut board,
+        player,
+        rules.to_string(),
+        (won, attempts),
+        started.elapsed(),
+    );
+    Ok(())
+}
+
+fn solve_bulls(rules: &Rules) -> io::Result<()> {
+    let report = knuth::solve_all(rules);
+    let mut out = io::stdout().lock();
+    writeln!(
+        out,
+        "Knuth's minimax solver against all {} codes of {}",
+        report.games(),
+        rules
+    )?;
+    for (guesses, games) in &report.histogram {
+        writeln!(out, "{:>3} guesses  {:>5} codes", guesses, games)?;
+    }
+    write!(
+        out,
+        "worst case {} guesses, average {:.3}",
+        report.worst(),
+        report.average()
+    )?;
+    match knuth::best_possible(rules) {
+        Some(best) => writeln!(out, " (no strategy can promise fewer than {})", best),
+        None => writeln!(out),
+    }
+}
+
+fn play_words(options: WordsOptions) -> io::Result<()> {
+    let dictionary = Dictionary::bundled();
+    let secret = if options.daily {
+        dictionary.daily_word(options.date.unwrap_or_else(words::today))
+    } else {
+        match options.seed {
+            Some(seed) => dictionary.random_word(&mut StdRng::seed_from_u64(seed)),
+            None => dictionary.random_word(&mut rand::thread_rng()),
+        }
+    };
+    // https://no-color.org: any non-empty NO_COLOR turns colours off.
+    let colour = !options.no_color
+        && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+        && io::stdout().is_terminal();
+
+    let started = Instant::now();
+    let outcome = words::play(
+        &mut io::stdin().lock(),
+        &mut io::stdout(),
+        WordRound::new(dictionary, secret, options.hard),
+        colour,
+    )?;
+    let (won, attempts) = match outcome {
+        WordOutcome::Won { attempts, .. } => (true, attempts),
+        WordOutcome::Lost { attempts, .. } => (false, attempts),
+        WordOutcome::Quit { .. } => return Ok(()),
+    };
+
+    let Some(mut board) = open_board(options.scores_file) else {
+        return Ok(());
+    };
+    let player = options.name.unwrap_or_else(default_player);
+    let difficulty = if options.hard {
+        "words (hard)"
+    } else {
+        "words"
+    };
+    record_score(
+        &mut board,
+        player,
+        difficulty.to_string(),
+        (won, attempts),
+        started.elapsed(),
+    );
+    Ok(())
+}
+
+#[cfg(feature = "tui")]
+fn play_tui(options: TuiOptions) -> io::Result<()> {
+    let rng = match options.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let finished = guessing_game::tui::App::new(rng).run()?;
+    let games: Vec<_> = finished
+        .into_iter()
+        .filter_map(|game| match game.outcome {
+            Outcome::Won { attempts, .. } => Some((game, (true, attempts))),
+            Outcome::Lost { attempts, .. } => Some((game, (false, attempts))),
+            Outcome::Quit { .. } => None,
+        })
+        .collect();
+    if games.is_empty() {
+        return Ok(());
+    }
+    let Some(mut board) = open_board(options.scores_file) else {
+        return Ok(());
+    };
+    let player = options.name.unwrap_or_else(default_player);
+    for (game, result) in games {
+        let difficulty = game.difficulty.to_string();
+        record_score(&mut board, player.clone(), difficulty, result, game.elapsed);
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "tui"))]
+fn play_tui(_: TuiOptions) -> io::Result<()> {
+    eprintln!("error: this build has no full-screen mode; rebuild with `--features tui`");
+    process::exit(2);
+}
+
+/// Opens the score file, warning (rather than failing) when it is unusable.
+fn open_board(path: Option<PathBuf>) -> Option<ScoreBoard> {
+    let Some(path) = path.or_else(scores::default_path) else {
+        eprintln!("warning: no data directory found, scores are not kept");
+        return None;
+    };
+    match ScoreBoard::open(&path) {
+        Ok((board, LoadStatus::Recovered { backup, reason })) => {
+            eprintln!(
+                "warning: ignoring {} because {}; it was moved to {}",
+                path.display(),
+                reason,
+                backup.display()
+            );
+            Some(board)
+        }
+        Ok((board, _)) => Some(board),
+        Err(e) => {
+            eprintln!("warning: could not read {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Records a finished game, `(won, attempts)`, on `board` and saves it,
+/// warning (rather than failing) when the file cannot be written.
+fn record_score(
+    board: &mut ScoreBoard,
+    player: String,
+    difficulty: String,
+    (won, attempts): (bool, u32),
+    elapsed: Duration,
+) {
+    board.record(ScoreEntry {
+        player,
+        difficulty,
+        won,
+        attempts,
+        elapsed_ms: elapsed.as_millis() as u64,
+        played_at: scores::now(),
+    });
+    if let Err(e) = board.save() {
+        eprintln!("warning: could not save {}: {}", board.path().display(), e);
+    }
+}
 
-    println!("You guessed: {}", guess);
+fn default_player() -> String {
+    env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_else(|_| "player".to_string())
 }
@@ -0,0 +1,321 @@
+//! Session recordings, one JSON object per line: a `start` record with the
+//! seed and difficulty, a `turn` record per line the player typed, and an
+//! `end` record with the outcome. Replaying feeds the same lines to a game
+//! seeded the same way and checks that every response matches.
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+use crate::difficulty::Difficulty;
+use crate::game::{Game, Outcome, Turn};
+
+/// Bumped whenever the layout of a replay file changes.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Record {
+    Start {
+        version: u32,
+        seed: u64,
+        difficulty: Difficulty,
+    },
+    Turn(Turn),
+    End {
+        outcome: Outcome,
+    },
+}
+
+/// A whole recorded session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub turns: Vec<Turn>,
+    pub outcome: Outcome,
+}
+
+impl Session {
+    /// Writes the session as JSON lines.
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let start = Record::Start {
+            version: FORMAT_VERSION,
+            seed: self.seed,
+            difficulty: self.difficulty.clone(),
+        };
+        let records = std::iter::once(start)
+            .chain(self.turns.iter().cloned().map(Record::Turn))
+            .chain(std::iter::once(Record::End {
+                outcome: self.outcome,
+            }));
+        for record in records {
+            serde_json::to_writer(&mut *out, &record)?;
+            out.write_all(b"\n")?;
+        }
+        out.flush()
+    }
+
+    /// Reads a session written by [`Session::write`].
+    pub fn read<R: BufRead>(input: R) -> Result<Session, ReplayError> {
+        let mut start = None;
+        let mut turns = Vec::new();
+        let mut outcome = None;
+
+        for (i, line) in input.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if outcome.is_some() {
+                return Err(ReplayError::Malformed {
+                    line: line_no,
+                    reason: "records after the end of the session".to_string(),
+                });
+            }
+            let record = serde_json::from_str(&line).map_err(|e| ReplayError::Malformed {
+                line: line_no,
+                reason: e.to_string(),
+            })?;
+            match (record, &start) {
+                (Record::Start { version, .. }, None) if version != FORMAT_VERSION => {
+                    return Err(ReplayError::UnsupportedVersion(version));
+                }
+                (
+                    Record::Start {
+                        seed, difficulty, ..
+                    },
+                    None,
+                ) => start = Some((seed, difficulty)),
+                (_, None) => {
+                    return Err(ReplayError::Malformed {
+                        line: line_no,
+                        reason: "the first record must be the start".to_string(),
+                    })
+                }
+                (Record::Start { .. }, Some(_)) => {
+                    return Err(ReplayError::Malformed {
+                        line: line_no,
+                        reason: "a second start record".to_string(),
+                    })
+                }
+                (Record::Turn(turn), Some(_)) => turns.push(turn),
+                (Record::End { outcome: end }, Some(_)) => outcome = Some(end),
+            }
+        }
+
+        let (seed, difficulty) = start.ok_or(ReplayError::Incomplete("start"))?;
+        let outcome = outcome.ok_or(ReplayError::Incomplete("end"))?;
+        Ok(Session {
+            seed,
+            difficulty,
+            turns,
+            outcome,
+        })
+    }
+
+    /// Plays the recorded input again and checks it gives the same result.
+    pub fn verify(&self) -> Result<(), ReplayError> {
+        // An unreadable line is fed back as a byte that is not valid UTF-8,
+        // which fails to read the same way.
+        let mut input = Vec::new();
+        for turn in &self.turns {
+            if turn.unreadable {
+                input.push(0xff);
+            } else {
+                input.extend_from_slice(turn.input.as_bytes());
+            }
+            input.push(b'\n');
+        }
+        let replayed = play_input(self.seed, self.difficulty.clone(), &input)?;
+
+        for (i, (expected, actual)) in self.turns.iter().zip(&replayed.turns).enumerate() {
+            if expected != actual {
+                return Err(ReplayError::Mismatch(format!(
+                    "turn {}: recorded {:?}, replay gave {:?}",
+                    i + 1,
+                    expected,
+                    actual
+                )));
+            }
+        }
+        if self.turns.len() != replayed.turns.len() {
+            return Err(ReplayError::Mismatch(format!(
+                "recorded {} turns, replay played {}",
+                self.turns.len(),
+                replayed.turns.len()
+            )));
+        }
+        if self.outcome != replayed.outcome {
+            return Err(ReplayError::Mismatch(format!(
+                "recorded {:?}, replay gave {:?}",
+                self.outcome, replayed.outcome
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Plays a game seeded with `seed` on the given lines of input, discarding
+/// what the game prints.
+pub fn play_seeded<'a>(
+    seed: u64,
+    difficulty: Difficulty,
+    lines: impl IntoIterator<Item = &'a str>,
+) -> io::Result<Session> {
+    let mut input = String::new();
+    for line in lines {
+        input.push_str(line);
+        input.push('\n');
+    }
+    play_input(seed, difficulty, input.as_bytes())
+}
+
+fn play_input(seed: u64, difficulty: Difficulty, input: &[u8]) -> io::Result<Session> {
+    let rng = StdRng::seed_from_u64(seed);
+    let mut game = Game::with_difficulty(input, io::sink(), rng, difficulty.clone());
+    let outcome = game.play()?;
+    Ok(Session {
+        seed,
+        difficulty,
+        turns: game.turns().to_vec(),
+        outcome,
+    })
+}
+
+/// `replays/` under the same data directory as the score file.
+pub fn default_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("guessing_game").join("replays"))
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Malformed {
+        line: usize,
+        reason: String,
+    },
+    UnsupportedVersion(u32),
+    /// The file ended without the named record.
+    Incomplete(&'static str),
+    /// Replaying did not reproduce the recording.
+    Mismatch(String),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "could not read the replay: {}", e),
+            ReplayError::Malformed { line, reason } => write!(f, "line {}: {}", line, reason),
+            ReplayError::UnsupportedVersion(version) => write!(
+                f,
+                "replay format version {} is not supported (expected {})",
+                version, FORMAT_VERSION
+            ),
+            ReplayError::Incomplete(record) => write!(f, "the replay has no {} record", record),
+            ReplayError::Mismatch(detail) => write!(f, "replay diverged: {}", detail),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(e: io::Error) -> Self {
+        ReplayError::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Response;
+
+    fn session() -> Session {
+        play_seeded(42, Difficulty::Easy, ["1", "ten", "10", "5", "3"]).unwrap()
+    }
+
+    #[test]
+    fn round_trips_and_verifies() {
+        let session = session();
+        let mut file = Vec::new();
+        session.write(&mut file).unwrap();
+
+        let text = String::from_utf8(file.clone()).unwrap();
+        assert!(text.starts_with(r#"{"type":"start","version":1,"seed":42,"difficulty":"easy"}"#));
+        assert!(text.contains(
+            r#"{"type":"turn","input":"ten","response":{"invalid":"'ten' is not a whole number."}}"#
+        ));
+
+        let read = Session::read(file.as_slice()).unwrap();
+        assert_eq!(read, session);
+        read.verify().unwrap();
+    }
+
+    #[test]
+    fn unreadable_lines_replay_the_same_way() {
+        let input = b"\xfe\xff\n50\n";
+        let rng = StdRng::seed_from_u64(7);
+        let mut game = Game::with_difficulty(&input[..], io::sink(), rng, Difficulty::Easy);
+        let outcome = game.play().unwrap();
+        let session = Session {
+            seed: 7,
+            difficulty: Difficulty::Easy,
+            turns: game.turns().to_vec(),
+            outcome,
+        };
+        assert!(session.turns[0].unreadable);
+
+        let mut file = Vec::new();
+        session.write(&mut file).unwrap();
+        let read = Session::read(file.as_slice()).unwrap();
+        assert_eq!(read, session);
+        read.verify().unwrap();
+    }
+
+    #[test]
+    fn detects_tampered_responses() {
+        let mut session = session();
+        let turn = session
+            .turns
+            .iter_mut()
+            .find(|t| t.response == Response::TooSmall || t.response == Response::TooBig)
+            .unwrap();
+        turn.response = Response::Correct;
+        assert!(matches!(session.verify(), Err(ReplayError::Mismatch(_))));
+    }
+
+    #[test]
+    fn detects_a_different_seed() {
+        let mut session = play_seeded(1, Difficulty::Hard, ["5000"]).unwrap();
+        session.seed = 2;
+        assert!(matches!(session.verify(), Err(ReplayError::Mismatch(_))));
+    }
+
+    #[test]
+    fn rejects_bad_files() {
+        let read = |text: &str| Session::read(text.as_bytes());
+
+        assert!(matches!(
+            read("not json\n"),
+            Err(ReplayError::Malformed { line: 1, .. })
+        ));
+        assert!(matches!(
+            read(r#"{"type":"end","outcome":{"result":"quit","secret":1,"attempts":0}}"#),
+            Err(ReplayError::Malformed { line: 1, .. })
+        ));
+        assert!(matches!(
+            read(r#"{"type":"start","version":9,"seed":1,"difficulty":"easy"}"#),
+            Err(ReplayError::UnsupportedVersion(9))
+        ));
+        assert!(matches!(
+            read(r#"{"type":"start","version":1,"seed":1,"difficulty":"easy"}"#),
+            Err(ReplayError::Incomplete("end"))
+        ));
+    }
+}
@@ -0,0 +1,464 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Bumped whenever the layout of the score file changes.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// One finished game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoreEntry {
+    pub player: String,
+    /// The difficulty as shown to the player, e.g. `medium (1-100, 10 attempts)`.
+    pub difficulty: String,
+    pub won: bool,
+    pub attempts: u32,
+    pub elapsed_ms: u64,
+    /// Seconds since the Unix epoch.
+    pub played_at: u64,
+}
+
+impl ScoreEntry {
+    /// The date the game was played on, as `YYYY-MM-DD` in UTC.
+    pub fn date(&self) -> String {
+        let (year, month, day) = civil_from_days((self.played_at / 86_400) as i64);
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ScoreFile {
+    version: u32,
+    scores: Vec<ScoreEntry>,
+}
+
+/// What [`ScoreBoard::open`] or [`ScoreBoard::load`] found on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadStatus {
+    /// There was no score file yet.
+    New,
+    Loaded,
+    /// The file could not be used. It was moved to `backup` and the board
+    /// starts empty.
+    Recovered {
+        backup: PathBuf,
+        reason: String,
+    },
+    /// [`ScoreBoard::load`] only: the file could not be used and was left
+    /// where it is. The board is empty.
+    Unusable {
+        reason: String,
+    },
+}
+
+/// The score file as read from disk, before anything is done about it.
+enum Contents {
+    Missing,
+    Scores(Vec<ScoreEntry>),
+    /// `newer` is set for files from a later format version, which must not
+    /// be moved out of the way by an older game.
+    Unusable {
+        reason: String,
+        newer: bool,
+    },
+}
+
+/// The high-score table, backed by a JSON file.
+#[derive(Debug, Clone)]
+pub struct ScoreBoard {
+    path: PathBuf,
+    entries: Vec<ScoreEntry>,
+}
+
+impl ScoreBoard {
+    /// Loads the board from `path` for a caller that is going to save it.
+    /// Corrupted files and files from an older or unknown version are set
+    /// aside rather than overwritten. A file from a newer version is an
+    /// error, and is left alone.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<(ScoreBoard, LoadStatus)> {
+        let path = path.into();
+        match read(&path)? {
+            Contents::Unusable {
+                reason,
+                newer: true,
+            } => Err(io::Error::new(io::ErrorKind::InvalidData, reason)),
+            Contents::Unusable {
+                reason,
+                newer: false,
+            } => {
+                let backup = backup_path(&path);
+                fs::rename(&path, &backup)?;
+                Ok((
+                    ScoreBoard::empty(path),
+                    LoadStatus::Recovered { backup, reason },
+                ))
+            }
+            Contents::Missing => Ok((ScoreBoard::empty(path), LoadStatus::New)),
+            Contents::Scores(entries) => Ok((ScoreBoard { path, entries }, LoadStatus::Loaded)),
+        }
+    }
+
+    /// Loads the board from `path` without touching the file, e.g. just to
+    /// show it. A file that cannot be used is reported as
+    /// [`LoadStatus::Unusable`].
+    pub fn load(path: impl Into<PathBuf>) -> io::Result<(ScoreBoard, LoadStatus)> {
+        let path = path.into();
+        match read(&path)? {
+            Contents::Unusable { reason, .. } => {
+                Ok((ScoreBoard::empty(path), LoadStatus::Unusable { reason }))
+            }
+            Contents::Missing => Ok((ScoreBoard::empty(path), LoadStatus::New)),
+            Contents::Scores(entries) => Ok((ScoreBoard { path, entries }, LoadStatus::Loaded)),
+        }
+    }
+
+    fn empty(path: PathBuf) -> ScoreBoard {
+        ScoreBoard {
+            path,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn entries(&self) -> &[ScoreEntry] {
+        &self.entries
+    }
+
+    pub fn record(&mut self, entry: ScoreEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Writes the board back to disk, replacing the old file in one step so a
+    /// crash never leaves a half-written file behind.
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = ScoreFile {
+            version: FORMAT_VERSION,
+            scores: self.entries.clone(),
+        };
+        let tmp = self.path.with_extension("json.tmp");
+        let mut out = fs::File::create(&tmp)?;
+        serde_json::to_writer_pretty(&mut out, &file)?;
+        out.write_all(b"\n")?;
+        out.sync_all()?;
+        fs::rename(&tmp, &self.path)
+    }
+
+    /// Every difficulty that has at least one win, in the order first seen.
+    pub fn difficulties(&self) -> Vec<&str> {
+        let mut seen: Vec<&str> = Vec::new();
+        for entry in self.entries.iter().filter(|e| e.won) {
+            if !seen.contains(&entry.difficulty.as_str()) {
+                seen.push(&entry.difficulty);
+            }
+        }
+        seen
+    }
+
+    /// Wins at `difficulty`, best first: fewest attempts, then fastest, then
+    /// earliest.
+    pub fn leaderboard(&self, difficulty: &str) -> Vec<&ScoreEntry> {
+        let mut wins: Vec<&ScoreEntry> = self
+            .entries
+            .iter()
+            .filter(|e| e.won && e.difficulty == difficulty)
+            .collect();
+        wins.sort_by_key(|e| (e.attempts, e.elapsed_ms, e.played_at));
+        wins
+    }
+
+    /// Prints a ranked table per difficulty. `filter` limits the output to
+    /// difficulties whose name starts with it.
+    pub fn print_leaderboards<W: Write>(
+        &self,
+        out: &mut W,
+        filter: Option<&str>,
+    ) -> io::Result<()> {
+        let difficulties: Vec<&str> = self
+            .difficulties()
+            .into_iter()
+            .filter(|d| filter.is_none_or(|f| d.starts_with(f)))
+            .collect();
+
+        if difficulties.is_empty() {
+            return writeln!(out, "No games won yet.");
+        }
+
+        for (i, difficulty) in difficulties.into_iter().enumerate() {
+            if i > 0 {
+                writeln!(out)?;
+            }
+            writeln!(out, "== {} ==", difficulty)?;
+            writeln!(out, "rank  player           attempts      time  date")?;
+            for (rank, entry) in self.leaderboard(difficulty).into_iter().enumerate() {
+                writeln!(
+                    out,
+                    "{:>4}  {:<16} {:>8} {:>8.1}s  {}",
+                    rank + 1,
+                    entry.player,
+                    entry.attempts,
+                    entry.elapsed_ms as f64 / 1000.0,
+                    entry.date()
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn read(path: &Path) -> io::Result<Contents> {
+    let unusable = |reason| Contents::Unusable {
+        reason,
+        newer: false,
+    };
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Contents::Missing),
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+            return Ok(unusable("the file is not valid UTF-8".to_string()));
+        }
+        Err(e) => return Err(e),
+    };
+
+    let value = match serde_json::from_str::<serde_json::Value>(&contents) {
+        Ok(value) => value,
+        Err(e) => return Ok(unusable(format!("the file is not valid JSON ({})", e))),
+    };
+    Ok(match value.get("version").and_then(|v| v.as_u64()) {
+        Some(version) if version == u64::from(FORMAT_VERSION) => {
+            match serde_json::from_value::<ScoreFile>(value) {
+                Ok(file) => Contents::Scores(file.scores),
+                Err(e) => unusable(format!("the scores are malformed ({})", e)),
+            }
+        }
+        Some(version) => Contents::Unusable {
+            reason: format!(
+                "it uses format version {}, this game understands version {}",
+                version, FORMAT_VERSION
+            ),
+            newer: version > u64::from(FORMAT_VERSION),
+        },
+        None => unusable("it has no format version".to_string()),
+    })
+}
+
+/// `scores.json` under the platform's data directory, e.g.
+/// `~/.local/share/guessing_game/scores.json` on Linux.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("guessing_game").join("scores.json"))
+}
+
+/// Seconds since the Unix epoch, for [`ScoreEntry::played_at`].
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Picks a `<file>.bak`, `<file>.bak.1`, ... name that is not taken yet.
+fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".bak");
+    let mut candidate = PathBuf::from(&name);
+    let mut n = 1;
+    while candidate.exists() {
+        let mut numbered = name.clone();
+        numbered.push(format!(".{}", n));
+        candidate = PathBuf::from(numbered);
+        n += 1;
+    }
+    candidate
+}
+
+/// Converts days since 1970-01-01 into a (year, month, day) date.
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// The inverse of [`civil_from_days`]. Out-of-range days or months give
+/// a date that rolls over, e.g. February 30th becomes March 1st or 2nd.
+pub(crate) fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = i64::from((month + 9) % 12);
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        player: &str,
+        difficulty: &str,
+        won: bool,
+        attempts: u32,
+        elapsed_ms: u64,
+    ) -> ScoreEntry {
+        ScoreEntry {
+            player: player.to_string(),
+            difficulty: difficulty.to_string(),
+            won,
+            attempts,
+            elapsed_ms,
+            played_at: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(days_from_civil(2024, 2, 29), 19_782);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        assert_eq!(entry("a", "easy", true, 1, 1).date(), "2023-11-14");
+    }
+
+    #[test]
+    fn round_trips_through_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("scores.json");
+
+        let (mut board, status) = ScoreBoard::open(&path).unwrap();
+        assert_eq!(status, LoadStatus::New);
+        board.record(entry("ada", "easy", true, 3, 1200));
+        board.save().unwrap();
+
+        let (board, status) = ScoreBoard::open(&path).unwrap();
+        assert_eq!(status, LoadStatus::Loaded);
+        assert_eq!(board.entries(), &[entry("ada", "easy", true, 3, 1200)]);
+    }
+
+    #[test]
+    fn ranks_wins_by_attempts_then_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut board, _) = ScoreBoard::open(dir.path().join("scores.json")).unwrap();
+        board.record(entry("slow", "easy", true, 3, 9000));
+        board.record(entry("loser", "easy", false, 6, 100));
+        board.record(entry("fast", "easy", true, 3, 1000));
+        board.record(entry("lucky", "easy", true, 1, 5000));
+        board.record(entry("other", "hard", true, 1, 1));
+
+        let players: Vec<&str> = board
+            .leaderboard("easy")
+            .iter()
+            .map(|e| e.player.as_str())
+            .collect();
+        assert_eq!(players, ["lucky", "fast", "slow"]);
+        assert_eq!(board.difficulties(), ["easy", "hard"]);
+
+        let mut out = Vec::new();
+        board.print_leaderboards(&mut out, Some("hard")).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("== hard =="));
+        assert!(!out.contains("easy"));
+    }
+
+    #[test]
+    fn corrupted_file_is_backed_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scores.json");
+        fs::write(&path, "{ not json").unwrap();
+
+        let (board, status) = ScoreBoard::open(&path).unwrap();
+        assert!(board.entries().is_empty());
+        match status {
+            LoadStatus::Recovered { backup, reason } => {
+                assert_eq!(backup, dir.path().join("scores.json.bak"));
+                assert_eq!(fs::read_to_string(backup).unwrap(), "{ not json");
+                assert!(reason.contains("not valid JSON"));
+            }
+            other => panic!("expected recovery, got {:?}", other),
+        }
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn other_format_versions_are_backed_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scores.json");
+        fs::write(dir.path().join("scores.json.bak"), "older backup").unwrap();
+
+        fs::write(&path, r#"[{"name": "ada", "tries": 3}]"#).unwrap();
+        let (_, status) = ScoreBoard::open(&path).unwrap();
+        assert!(matches!(
+            status,
+            LoadStatus::Recovered { ref reason, .. } if reason.contains("no format version")
+        ));
+
+        fs::write(&path, r#"{"version": 0, "scores": []}"#).unwrap();
+        let (_, status) = ScoreBoard::open(&path).unwrap();
+        match status {
+            LoadStatus::Recovered { backup, reason } => {
+                assert_eq!(backup, dir.path().join("scores.json.bak.2"));
+                assert!(reason.contains("format version 0"));
+            }
+            other => panic!("expected recovery, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn newer_format_versions_are_left_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scores.json");
+        let newer = r#"{"version": 99, "scores": []}"#;
+        fs::write(&path, newer).unwrap();
+
+        let e = ScoreBoard::open(&path).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert!(e.to_string().contains("format version 99"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), newer);
+        assert!(!dir.path().join("scores.json.bak").exists());
+    }
+
+    #[test]
+    fn loading_never_moves_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scores.json");
+        fs::write(&path, "{ not json").unwrap();
+
+        let (board, status) = ScoreBoard::load(&path).unwrap();
+        assert!(board.entries().is_empty());
+        assert!(matches!(
+            status,
+            LoadStatus::Unusable { ref reason } if reason.contains("not valid JSON")
+        ));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{ not json");
+        assert!(!dir.path().join("scores.json.bak").exists());
+    }
+
+    #[test]
+    fn malformed_entries_are_backed_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scores.json");
+        fs::write(&path, r#"{"version": 1, "scores": [{"player": 3}]}"#).unwrap();
+
+        let (_, status) = ScoreBoard::open(&path).unwrap();
+        assert!(matches!(
+            status,
+            LoadStatus::Recovered { ref reason, .. } if reason.contains("malformed")
+        ));
+    }
+}
@@ -0,0 +1,411 @@
+//! Computer players for the guessing game. Every strategy plays through the
+//! same [`Round`] engine as a human, so it gets the same hints and is held to
+//! the same range and attempt limits.
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+
+use rand::{Rng, RngCore};
+
+use crate::difficulty::Difficulty;
+use crate::engine::{Hint, Round, RoundError};
+use crate::game::Outcome;
+
+/// Give up on a game after this many refused guesses, so a strategy that
+/// never produces a valid guess cannot loop forever.
+pub const MAX_REFUSED_PER_GAME: u32 = 100;
+
+/// A way of picking guesses. The simulator calls `reset` at the start of each
+/// game, then alternates `next_guess` and `observe` until the game is over.
+pub trait Strategy {
+    fn name(&self) -> &'static str;
+    fn reset(&mut self, range: RangeInclusive<u32>);
+    fn next_guess(&mut self, rng: &mut dyn RngCore) -> u32;
+    fn observe(&mut self, guess: u32, hint: Hint);
+}
+
+/// The bounds that are still consistent with every hint so far.
+#[derive(Debug, Clone, Copy, Default)]
+struct Bounds {
+    low: u32,
+    high: u32,
+}
+
+impl Bounds {
+    fn reset(&mut self, range: &RangeInclusive<u32>) {
+        self.low = *range.start();
+        self.high = *range.end();
+    }
+
+    fn narrow(&mut self, guess: u32, hint: Hint) {
+        match hint {
+            Hint::TooSmall => self.low = self.low.max(guess.saturating_add(1)),
+            Hint::TooBig => self.high = self.high.min(guess.saturating_sub(1)),
+            Hint::Correct => {}
+        }
+    }
+}
+
+/// Always guesses the middle of what is left. Never needs more than
+/// [`crate::difficulty::optimal_attempts`] guesses.
+#[derive(Debug, Default)]
+pub struct BinarySearch {
+    bounds: Bounds,
+}
+
+impl Strategy for BinarySearch {
+    fn name(&self) -> &'static str {
+        "binary"
+    }
+
+    fn reset(&mut self, range: RangeInclusive<u32>) {
+        self.bounds.reset(&range);
+    }
+
+    fn next_guess(&mut self, _rng: &mut dyn RngCore) -> u32 {
+        let Bounds { low, high } = self.bounds;
+        low + (high - low) / 2
+    }
+
+    fn observe(&mut self, guess: u32, hint: Hint) {
+        self.bounds.narrow(guess, hint);
+    }
+}
+
+/// Follows the hints, but picks anywhere in what is left rather than the
+/// middle.
+#[derive(Debug, Default)]
+pub struct RandomGuess {
+    bounds: Bounds,
+}
+
+impl Strategy for RandomGuess {
+    fn name(&self) -> &'static str {
+        "random"
+    }
+
+    fn reset(&mut self, range: RangeInclusive<u32>) {
+        self.bounds.reset(&range);
+    }
+
+    fn next_guess(&mut self, rng: &mut dyn RngCore) -> u32 {
+        rng.gen_range(self.bounds.low..=self.bounds.high)
+    }
+
+    fn observe(&mut self, guess: u32, hint: Hint) {
+        self.bounds.narrow(guess, hint);
+    }
+}
+
+/// Ignores the hints and counts up from the bottom of the range.
+#[derive(Debug, Default)]
+pub struct Linear {
+    next: u32,
+}
+
+impl Strategy for Linear {
+    fn name(&self) -> &'static str {
+        "linear"
+    }
+
+    fn reset(&mut self, range: RangeInclusive<u32>) {
+        self.next = *range.start();
+    }
+
+    fn next_guess(&mut self, _rng: &mut dyn RngCore) -> u32 {
+        self.next
+    }
+
+    fn observe(&mut self, guess: u32, _hint: Hint) {
+        self.next = guess.saturating_add(1);
+    }
+}
+
+/// Plays roughly like a person: follows the hints, but splits the range
+/// unevenly, likes round numbers and now and then misremembers a bound.
+#[derive(Debug, Default)]
+pub struct HumanLike {
+    bounds: Bounds,
+    /// The whole range, which slips never leave.
+    full: Bounds,
+}
+
+impl HumanLike {
+    /// Chance of a guess ignoring what the hints have ruled out.
+    const SLIP_CHANCE: f64 = 0.1;
+}
+
+impl Strategy for HumanLike {
+    fn name(&self) -> &'static str {
+        "human"
+    }
+
+    fn reset(&mut self, range: RangeInclusive<u32>) {
+        self.bounds.reset(&range);
+        self.full.reset(&range);
+    }
+
+    fn next_guess(&mut self, rng: &mut dyn RngCore) -> u32 {
+        let Bounds { low, high } = self.bounds;
+        let span = f64::from(high - low);
+        let mut guess = low + (span * rng.gen_range(0.25..=0.75)).round() as u32;
+
+        if high - low > 20 {
+            guess = (guess.saturating_add(2) / 5 * 5).clamp(low, high);
+        }
+        if rng.gen_bool(Self::SLIP_CHANCE) {
+            let slip = rng.gen_range(1..=3);
+            guess = if rng.gen_bool(0.5) {
+                low.saturating_sub(slip).max(self.full.low)
+            } else {
+                high.saturating_add(slip).min(self.full.high)
+            };
+        }
+        guess
+    }
+
+    fn observe(&mut self, guess: u32, hint: Hint) {
+        self.bounds.narrow(guess, hint);
+    }
+}
+
+/// Every built-in strategy, in the order they are reported.
+pub fn strategies() -> Vec<Box<dyn Strategy>> {
+    vec![
+        Box::new(BinarySearch::default()),
+        Box::new(RandomGuess::default()),
+        Box::new(Linear::default()),
+        Box::new(HumanLike::default()),
+    ]
+}
+
+/// Looks up a built-in strategy by [`Strategy::name`].
+pub fn strategy(name: &str) -> Option<Box<dyn Strategy>> {
+    strategies().into_iter().find(|s| s.name() == name)
+}
+
+/// Plays one game of `strategy` against `round`.
+pub fn play_round<S: Strategy + ?Sized>(
+    strategy: &mut S,
+    round: &mut Round,
+    rng: &mut dyn RngCore,
+) -> (Outcome, u32) {
+    strategy.reset(round.range());
+    let mut refused = 0;
+    loop {
+        if let Some(outcome) = round.outcome() {
+            return (outcome, refused);
+        }
+        let guess = strategy.next_guess(rng);
+        match round.guess(guess) {
+            Ok(hint) => strategy.observe(guess, hint),
+            Err(RoundError::OutOfRange { .. }) => {
+                refused += 1;
+                if refused == MAX_REFUSED_PER_GAME {
+                    return (round.quit(), refused);
+                }
+            }
+            Err(RoundError::GameOver(outcome)) => return (outcome, refused),
+        }
+    }
+}
+
+/// Results of [`simulate`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stats {
+    pub strategy: &'static str,
+    pub games: u32,
+    pub wins: u32,
+    pub losses: u32,
+    /// Games abandoned after [`MAX_REFUSED_PER_GAME`] refused guesses.
+    pub abandoned: u32,
+    /// Guesses the engine refused, over all games.
+    pub refused: u32,
+    /// Number of won games by attempts taken.
+    pub histogram: BTreeMap<u32, u32>,
+}
+
+impl Stats {
+    /// Mean attempts over the games that were won.
+    pub fn mean_attempts(&self) -> Option<f64> {
+        if self.wins == 0 {
+            return None;
+        }
+        let total: u64 = self
+            .histogram
+            .iter()
+            .map(|(&attempts, &count)| u64::from(attempts) * u64::from(count))
+            .sum();
+        Some(total as f64 / f64::from(self.wins))
+    }
+
+    /// Most attempts any won game took.
+    pub fn worst_case(&self) -> Option<u32> {
+        self.histogram.keys().next_back().copied()
+    }
+
+    pub fn print<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        write!(
+            out,
+            "{}: won {}/{}, lost {}",
+            self.strategy, self.wins, self.games, self.losses
+        )?;
+        if self.abandoned > 0 {
+            write!(out, ", abandoned {}", self.abandoned)?;
+        }
+        if self.refused > 0 {
+            write!(out, ", {} guesses refused", self.refused)?;
+        }
+        writeln!(out)?;
+
+        if let (Some(mean), Some(worst)) = (self.mean_attempts(), self.worst_case()) {
+            writeln!(out, "  mean {:.2} attempts, worst case {}", mean, worst)?;
+        }
+        let widest = self.histogram.values().copied().max().unwrap_or(0);
+        for (attempts, &count) in &self.histogram {
+            let bar = (u64::from(count) * 40).div_ceil(u64::from(widest.max(1)));
+            writeln!(
+                out,
+                "  {:>3} | {:<40} {}",
+                attempts,
+                "#".repeat(bar as usize),
+                count
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Plays `games` games of `strategy` at `difficulty`.
+pub fn simulate<S: Strategy + ?Sized>(
+    strategy: &mut S,
+    difficulty: &Difficulty,
+    games: u32,
+    rng: &mut dyn RngCore,
+) -> Stats {
+    let mut stats = Stats {
+        strategy: strategy.name(),
+        games,
+        ..Stats::default()
+    };
+    for _ in 0..games {
+        let mut round = Round::new(difficulty, rng);
+        let (outcome, refused) = play_round(strategy, &mut round, rng);
+        stats.refused += refused;
+        match outcome {
+            Outcome::Won { attempts, .. } => {
+                stats.wins += 1;
+                *stats.histogram.entry(attempts).or_insert(0) += 1;
+            }
+            Outcome::Lost { .. } => stats.losses += 1,
+            Outcome::Quit { .. } => stats.abandoned += 1,
+        }
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::optimal_attempts;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn run(name: &str, difficulty: &Difficulty, games: u32) -> Stats {
+        let mut strategy = strategy(name).unwrap();
+        let mut rng = StdRng::seed_from_u64(2024);
+        simulate(strategy.as_mut(), difficulty, games, &mut rng)
+    }
+
+    #[test]
+    fn binary_search_always_wins_within_the_optimum() {
+        for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+            let stats = run("binary", &difficulty, 500);
+            assert_eq!(stats.wins, 500);
+            assert_eq!(
+                stats.worst_case(),
+                Some(optimal_attempts(&difficulty.range()))
+            );
+        }
+    }
+
+    #[test]
+    fn binary_search_finds_every_secret() {
+        let difficulty = Difficulty::Medium;
+        for secret in difficulty.range() {
+            let mut round = Round::with_secret(&difficulty, secret);
+            let mut rng = StdRng::seed_from_u64(0);
+            let (outcome, _) = play_round(&mut BinarySearch::default(), &mut round, &mut rng);
+            assert!(matches!(outcome, Outcome::Won { attempts, .. } if attempts <= 7));
+        }
+    }
+
+    #[test]
+    fn engine_stops_strategies_at_the_attempt_limit() {
+        let stats = run("linear", &Difficulty::Medium, 300);
+        assert!(stats.losses > 0);
+        assert_eq!(stats.wins + stats.losses, 300);
+        assert!(stats.worst_case().unwrap() <= Difficulty::Medium.max_attempts());
+    }
+
+    #[test]
+    fn weaker_strategies_do_worse_than_binary_search() {
+        let binary = run("binary", &Difficulty::Hard, 300);
+        for name in ["random", "human"] {
+            let stats = run(name, &Difficulty::Hard, 300);
+            assert_eq!(stats.games, 300);
+            assert!(stats.wins < binary.wins || stats.mean_attempts() > binary.mean_attempts());
+        }
+    }
+
+    #[test]
+    fn strategies_play_at_the_top_of_the_range() {
+        let difficulty = Difficulty::custom(u32::MAX - 1_000..=u32::MAX, 20).unwrap();
+        for mut strategy in strategies() {
+            let mut rng = StdRng::seed_from_u64(5);
+            let stats = simulate(strategy.as_mut(), &difficulty, 200, &mut rng);
+            assert_eq!(stats.wins + stats.losses, 200, "{}", stats.strategy);
+        }
+        let mut round = Round::with_secret(&difficulty, u32::MAX);
+        let mut rng = StdRng::seed_from_u64(5);
+        let (outcome, _) = play_round(&mut HumanLike::default(), &mut round, &mut rng);
+        assert!(matches!(
+            outcome,
+            Outcome::Won { .. } | Outcome::Lost { .. }
+        ));
+    }
+
+    struct OffTheBoard;
+
+    impl Strategy for OffTheBoard {
+        fn name(&self) -> &'static str {
+            "off-the-board"
+        }
+        fn reset(&mut self, _range: RangeInclusive<u32>) {}
+        fn next_guess(&mut self, _rng: &mut dyn RngCore) -> u32 {
+            0
+        }
+        fn observe(&mut self, _guess: u32, _hint: Hint) {}
+    }
+
+    #[test]
+    fn engine_refuses_out_of_range_guesses() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let stats = simulate(&mut OffTheBoard, &Difficulty::Easy, 3, &mut rng);
+        assert_eq!(stats.abandoned, 3);
+        assert_eq!(stats.refused, 3 * MAX_REFUSED_PER_GAME);
+        assert_eq!(stats.mean_attempts(), None);
+    }
+
+    #[test]
+    fn report_has_histogram() {
+        let stats = run("binary", &Difficulty::Easy, 100);
+        let mut out = Vec::new();
+        stats.print(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("binary: won 100/100, lost 0\n  mean "));
+        assert!(out.contains("worst case 4"));
+        assert!(out.contains("    4 | #"));
+    }
+}
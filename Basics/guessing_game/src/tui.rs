@@ -0,0 +1,550 @@
+//! A full-screen front end for the number game, built with `--features tui`.
+//!
+//! It plays through the same [`Round`] engine as the line-based [`Game`],
+//! so the rules, hints and outcomes are identical; only the screen differs.
+//! The state lives in [`App`], which turns key presses into changes and
+//! draws itself, so it can be driven and inspected without a terminal.
+//!
+//! [`Game`]: crate::Game
+
+use std::io;
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
+
+use rand::RngCore;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, Gauge, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::difficulty::Difficulty;
+use crate::engine::{Hint, Round};
+use crate::game::Outcome;
+use crate::guess::parse_guess;
+
+/// How often the screen is redrawn while waiting for a key, so the timer
+/// keeps ticking.
+const TICK: Duration = Duration::from_millis(250);
+
+const MAIN_MENU: [&str; 2] = ["Play", "Quit"];
+const LEVELS: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+/// A round the player finished or gave up on, for the score table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finished {
+    pub difficulty: Difficulty,
+    pub outcome: Outcome,
+    pub elapsed: Duration,
+}
+
+/// Which screen is showing.
+#[derive(Debug)]
+enum Screen {
+    Menu { selected: usize },
+    Levels { selected: usize },
+    Playing(Play),
+}
+
+/// One round on screen.
+#[derive(Debug)]
+struct Play {
+    difficulty: Difficulty,
+    round: Round,
+    /// What the player has typed since the last guess.
+    input: String,
+    /// Every accepted guess, oldest first.
+    history: Vec<(u32, Hint)>,
+    /// Why the last line was refused, if it was.
+    error: Option<String>,
+    started: Instant,
+    /// How long the round took, once it is over.
+    took: Option<Duration>,
+}
+
+impl Play {
+    fn elapsed(&self) -> Duration {
+        self.took.unwrap_or_else(|| self.started.elapsed())
+    }
+}
+
+/// The whole front end: the current screen, the games played so far and
+/// where new secrets come from.
+pub struct App<G> {
+    screen: Screen,
+    rng: G,
+    finished: Vec<Finished>,
+    quit: bool,
+}
+
+impl<G: RngCore> App<G> {
+    pub fn new(rng: G) -> Self {
+        App {
+            screen: Screen::Menu { selected: 0 },
+            rng,
+            finished: Vec::new(),
+            quit: false,
+        }
+    }
+
+    /// Takes over the terminal until the player quits, then gives back
+    /// every round they finished.
+    pub fn run(mut self) -> io::Result<Vec<Finished>> {
+        ratatui::run(|terminal| self.event_loop(terminal))?;
+        Ok(self.finished)
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            if event::poll(TICK)? {
+                if let Event::Key(key) = event::read()? {
+                    // Windows also reports releases; only act on presses.
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the player has asked to leave.
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    /// Every round finished so far, oldest first.
+    pub fn finished(&self) -> &[Finished] {
+        &self.finished
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.leave_round();
+            self.quit = true;
+            return;
+        }
+        match &mut self.screen {
+            Screen::Menu { selected } => match key.code {
+                KeyCode::Up | KeyCode::Char('k') => {
+                    *selected = previous(*selected, MAIN_MENU.len())
+                }
+                KeyCode::Down | KeyCode::Char('j') => *selected = next(*selected, MAIN_MENU.len()),
+                KeyCode::Enter if *selected == 0 => self.screen = Screen::Levels { selected: 1 },
+                KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q') => self.quit = true,
+                _ => {}
+            },
+            Screen::Levels { selected } => match key.code {
+                KeyCode::Up | KeyCode::Char('k') => *selected = previous(*selected, LEVELS.len()),
+                KeyCode::Down | KeyCode::Char('j') => *selected = next(*selected, LEVELS.len()),
+                KeyCode::Char(c @ '1'..='3') => {
+                    let level = usize::from(c as u8 - b'1');
+                    self.start(LEVELS[level].clone());
+                }
+                KeyCode::Enter => {
+                    let level = *selected;
+                    self.start(LEVELS[level].clone());
+                }
+                KeyCode::Esc | KeyCode::Char('q') => self.screen = Screen::Menu { selected: 0 },
+                _ => {}
+            },
+            Screen::Playing(play) if play.took.is_some() => match key.code {
+                KeyCode::Enter | KeyCode::Char('r') => {
+                    let difficulty = play.difficulty.clone();
+                    self.start(difficulty);
+                }
+                KeyCode::Esc | KeyCode::Char('m') => {
+                    let selected = LEVELS.iter().position(|l| *l == play.difficulty);
+                    self.screen = Screen::Levels {
+                        selected: selected.unwrap_or(1),
+                    };
+                }
+                KeyCode::Char('q') => self.quit = true,
+                _ => {}
+            },
+            Screen::Playing(play) => match key.code {
+                KeyCode::Char(c) if c.is_ascii_digit() && play.input.len() < 10 => {
+                    play.input.push(c)
+                }
+                KeyCode::Backspace => {
+                    play.input.pop();
+                }
+                KeyCode::Enter => self.submit(),
+                KeyCode::Esc => self.leave_round(),
+                _ => {}
+            },
+        }
+    }
+
+    fn start(&mut self, difficulty: Difficulty) {
+        let round = Round::new(&difficulty, &mut self.rng);
+        self.screen = Screen::Playing(Play {
+            difficulty,
+            round,
+            input: String::new(),
+            history: Vec::new(),
+            error: None,
+            started: Instant::now(),
+            took: None,
+        });
+    }
+
+    /// Sends the typed number to the round.
+    fn submit(&mut self) {
+        let Screen::Playing(play) = &mut self.screen else {
+            return;
+        };
+        let line = std::mem::take(&mut play.input);
+        let result = parse_guess(&line, &play.round.range())
+            .map_err(|e| e.to_string())
+            .and_then(|guess| {
+                play.round
+                    .guess(guess)
+                    .map(|hint| (guess, hint))
+                    .map_err(|e| e.to_string())
+            });
+        match result {
+            Ok((guess, hint)) => {
+                play.error = None;
+                play.history.push((guess, hint));
+            }
+            Err(e) => play.error = Some(e),
+        }
+        if let Some(outcome) = play.round.outcome() {
+            self.end_round(outcome);
+        }
+    }
+
+    /// Gives up on a round in progress and shows its result.
+    fn leave_round(&mut self) {
+        if let Screen::Playing(play) = &mut self.screen {
+            if play.took.is_none() {
+                let outcome = play.round.quit();
+                self.end_round(outcome);
+            }
+        }
+    }
+
+    fn end_round(&mut self, outcome: Outcome) {
+        let Screen::Playing(play) = &mut self.screen else {
+            return;
+        };
+        let elapsed = play.started.elapsed();
+        play.took = Some(elapsed);
+        self.finished.push(Finished {
+            difficulty: play.difficulty.clone(),
+            outcome,
+            elapsed,
+        });
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        match &self.screen {
+            Screen::Menu { selected } => {
+                draw_menu(frame, "Guess the number!", &MAIN_MENU, *selected)
+            }
+            Screen::Levels { selected } => {
+                let levels: Vec<String> = LEVELS
+                    .iter()
+                    .enumerate()
+                    .map(|(i, level)| format!("{}) {}", i + 1, level))
+                    .collect();
+                draw_menu(frame, "Choose a difficulty", &levels, *selected)
+            }
+            Screen::Playing(play) => draw_play(frame, play),
+        }
+    }
+}
+
+fn previous(selected: usize, len: usize) -> usize {
+    (selected + len - 1) % len
+}
+
+fn next(selected: usize, len: usize) -> usize {
+    (selected + 1) % len
+}
+
+fn draw_menu<S: AsRef<str>>(frame: &mut Frame, title: &str, items: &[S], selected: usize) {
+    let width = items
+        .iter()
+        .map(|item| item.as_ref().len())
+        .chain([title.len()])
+        .max()
+        .unwrap_or(0) as u16
+        + 8;
+    let area = frame.area().centered(
+        Constraint::Length(width),
+        Constraint::Length(items.len() as u16 + 4),
+    );
+    let [list, help] = Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+
+    let items: Vec<ListItem> = items
+        .iter()
+        .map(|item| ListItem::new(item.as_ref()))
+        .collect();
+    let list_widget = List::new(items)
+        .block(Block::bordered().title(title.bold()))
+        .highlight_style(Style::new().reversed())
+        .highlight_symbol("> ");
+    let mut state = ListState::default().with_selected(Some(selected));
+    frame.render_stateful_widget(list_widget, list, &mut state);
+    frame.render_widget(
+        Paragraph::new("↑↓ move  Enter choose  Esc back").dim(),
+        help,
+    );
+}
+
+fn draw_play(frame: &mut Frame, play: &Play) {
+    let [title, bar, status, middle, input, help] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(3),
+        Constraint::Length(3),
+        Constraint::Fill(1),
+        Constraint::Length(3),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    frame.render_widget(
+        Paragraph::new(format!("Guess the number! {}", play.difficulty)).bold(),
+        title,
+    );
+
+    let bounds = play.round.bounds();
+    let bar_block = Block::bordered().title(format!(
+        "Still possible: {}-{}",
+        bounds.start(),
+        bounds.end()
+    ));
+    let width = bar_block.inner(bar).width;
+    frame.render_widget(
+        Paragraph::new(range_bar(&play.round.range(), &bounds, width).green()).block(bar_block),
+        bar,
+    );
+
+    let [attempts, timer] =
+        Layout::horizontal([Constraint::Fill(1), Constraint::Length(16)]).areas(status);
+    let left = play.round.attempts_left();
+    let max = play.round.max_attempts();
+    frame.render_widget(
+        Gauge::default()
+            .block(Block::bordered().title("Attempts left"))
+            .gauge_style(if left * 4 <= max {
+                Color::Red
+            } else {
+                Color::Cyan
+            })
+            .ratio(f64::from(left) / f64::from(max))
+            .label(format!("{} of {}", left, max)),
+        attempts,
+    );
+    let secs = play.elapsed().as_secs();
+    frame.render_widget(
+        Paragraph::new(format!("{:02}:{:02}", secs / 60, secs % 60))
+            .block(Block::bordered().title("Time")),
+        timer,
+    );
+
+    let history: Vec<ListItem> = play
+        .history
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, &(guess, hint))| {
+            let (text, colour) = match hint {
+                Hint::TooSmall => ("too small", Color::Yellow),
+                Hint::TooBig => ("too big", Color::Magenta),
+                Hint::Correct => ("correct!", Color::Green),
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{:>3}. {:>10}  ", i + 1, guess)),
+                Span::styled(text, Style::new().fg(colour)),
+            ]))
+        })
+        .collect();
+    frame.render_widget(
+        List::new(history).block(Block::bordered().title("Guesses")),
+        middle,
+    );
+
+    let prompt = match &play.error {
+        Some(error) => Line::from(vec![
+            Span::raw(format!("> {}", play.input)),
+            Span::styled(format!("   {}", error), Style::new().fg(Color::Red)),
+        ]),
+        None => Line::from(format!("> {}", play.input)),
+    };
+    frame.render_widget(
+        Paragraph::new(prompt).block(Block::bordered().title("Your guess")),
+        input,
+    );
+    frame.render_widget(
+        Paragraph::new("0-9 type  Enter guess  Backspace erase  Esc give up").dim(),
+        help,
+    );
+
+    if play.took.is_some() {
+        draw_result(frame, middle, play);
+    }
+}
+
+/// The box over the history once the round is over.
+fn draw_result(frame: &mut Frame, over: Rect, play: &Play) {
+    let headline = match play.round.outcome() {
+        Some(Outcome::Won { attempts, .. }) => {
+            Line::from(format!("You win! It took you {} attempts.", attempts)).green()
+        }
+        Some(Outcome::Lost { secret, .. }) => {
+            Line::from(format!("You lose! The secret number was {}.", secret)).red()
+        }
+        _ => Line::from(format!(
+            "You gave up. The secret number was {}.",
+            play.round.secret()
+        )),
+    };
+    let text = vec![
+        headline.bold(),
+        Line::from(""),
+        Line::from("Enter play again  Esc change difficulty  q quit"),
+    ];
+    let area = over.centered(Constraint::Length(52), Constraint::Length(5));
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(text)
+            .centered()
+            .block(Block::bordered().title("Game over")),
+        area,
+    );
+}
+
+/// Draws `full` as `width` cells, filled where the cell holds a number
+/// inside `bounds` and shaded elsewhere.
+pub fn range_bar(full: &RangeInclusive<u32>, bounds: &RangeInclusive<u32>, width: u16) -> String {
+    let (start, width) = (u64::from(*full.start()), u64::from(width));
+    let size = u64::from(*full.end()) - start + 1;
+    (0..width)
+        .map(|cell| {
+            // The numbers this cell stands for, at least one each.
+            let first = start + cell * size / width;
+            let last = (start + ((cell + 1) * size).div_ceil(width) - 1).max(first);
+            let open = first <= u64::from(*bounds.end()) && last >= u64::from(*bounds.start());
+            if open {
+                '█'
+            } else {
+                '░'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn press(app: &mut App<StdRng>, keys: &str) {
+        for c in keys.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                '\x1b' => KeyCode::Esc,
+                '↓' => KeyCode::Down,
+                c => KeyCode::Char(c),
+            };
+            app.handle_key(KeyEvent::from(code));
+        }
+    }
+
+    fn screen(app: &App<StdRng>) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(70, 24)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let mut text = String::new();
+        for y in 0..buffer.area.height {
+            for x in 0..buffer.area.width {
+                text.push_str(buffer[(x, y)].symbol());
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    #[test]
+    fn range_bar_shades_ruled_out_numbers() {
+        assert_eq!(range_bar(&(1..=10), &(1..=10), 10), "██████████");
+        assert_eq!(range_bar(&(1..=10), &(4..=6), 10), "░░░███░░░░");
+        assert_eq!(range_bar(&(1..=100), &(51..=100), 4), "░░██");
+        // Wider than the range: every number still gets a cell.
+        assert_eq!(range_bar(&(1..=3), &(2..=2), 6), "░░██░░");
+        assert_eq!(range_bar(&(0..=u32::MAX), &(0..=0), 4), "█░░░");
+    }
+
+    #[test]
+    fn menus_lead_to_a_round() {
+        let mut app = App::new(StdRng::seed_from_u64(1));
+        assert!(screen(&app).contains("> Play"));
+        press(&mut app, "↓");
+        assert!(screen(&app).contains("> Quit"));
+        press(&mut app, "↓\n");
+        let levels = screen(&app);
+        assert!(levels.contains("Choose a difficulty"), "{}", levels);
+        assert!(
+            levels.contains("> 2) medium (1-100, 10 attempts)"),
+            "{}",
+            levels
+        );
+        press(&mut app, "\x1b");
+        assert!(screen(&app).contains("> Play"));
+        press(&mut app, "\n1");
+        let playing = screen(&app);
+        assert!(playing.contains("easy (1-10, 6 attempts)"), "{}", playing);
+        assert!(playing.contains("6 of 6"), "{}", playing);
+        press(&mut app, "\x1b\x1bqq");
+        assert!(app.should_quit());
+    }
+
+    #[test]
+    fn plays_a_round_with_the_shared_engine() {
+        let secret = StdRng::seed_from_u64(3).gen_range(Difficulty::Medium.range());
+        assert!(secret > 1 && secret < 100);
+        let mut app = App::new(StdRng::seed_from_u64(3));
+        press(&mut app, "\n\n");
+
+        press(&mut app, "1\n");
+        let after_low = screen(&app);
+        assert!(after_low.contains("Still possible: 2-100"), "{}", after_low);
+        assert!(after_low.contains("too small"), "{}", after_low);
+        assert!(after_low.contains("9 of 10"), "{}", after_low);
+
+        press(&mut app, "500\n");
+        let refused = screen(&app);
+        assert!(refused.contains("500 is out of range"), "{}", refused);
+        assert!(refused.contains("9 of 10"), "{}", refused);
+
+        press(&mut app, &format!("{}\n", secret));
+        let won = screen(&app);
+        assert!(won.contains("You win! It took you 2 attempts."), "{}", won);
+        assert_eq!(app.finished().len(), 1);
+        assert_eq!(
+            app.finished()[0].outcome,
+            Outcome::Won {
+                secret,
+                attempts: 2
+            }
+        );
+
+        // Play again, then give up.
+        press(&mut app, "\n\x1b");
+        assert!(screen(&app).contains("You gave up."));
+        assert!(matches!(
+            app.finished()[1].outcome,
+            Outcome::Quit { attempts: 0, .. }
+        ));
+        press(&mut app, "q");
+        assert!(app.should_quit());
+    }
+}
@@ -0,0 +1,489 @@
+//! Encrypted score files, for players who would rather not leave their
+//! names and results lying around in plain JSON.
+//!
+//! The `Network.md` snippet uses AES-CBC with a hard-coded key, which
+//! neither protects the key nor notices when the ciphertext is changed.
+//! Here the key is derived from a passphrase with Argon2id, and every
+//! record is sealed with ChaCha20-Poly1305, so a record that was edited,
+//! swapped with another or dropped fails to open instead of decrypting to
+//! garbage.
+//!
+//! ```text
+//! {
+//!   "version": 1,
+//!   "kdf": { "salt": "…", "m_cost": 19456, "t_cost": 2, "p_cost": 1 },
+//!   "key_check": { "nonce": "…", "ciphertext": "…" },
+//!   "records": [ { "nonce": "…", "ciphertext": "…" }, … ]
+//! }
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::scores::ScoreEntry;
+
+/// Bumped whenever the layout of an encrypted file changes.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// What the key check decrypts to. It tells a wrong passphrase apart from
+/// a damaged file.
+const KEY_CHECK: &[u8] = b"guessing_game vault";
+
+/// The most costly key derivation a file may ask for. The settings are
+/// read from the file before anything in it is authenticated, so without
+/// a bound a doctored file could make opening it take any amount of memory
+/// and time.
+const MAX_M_COST: u32 = 256 * 1024; // KiB, so 256 MiB
+const MAX_T_COST: u32 = 16;
+const MAX_P_COST: u32 = 16;
+
+#[derive(Debug)]
+pub enum VaultError {
+    Io(io::Error),
+    /// The passphrase does not open this file, or records were added to or
+    /// removed from it; the key check cannot tell which.
+    WrongPassphrase,
+    /// The record at this position was modified, moved or cut short.
+    Tampered {
+        record: usize,
+    },
+    /// The file is not an encrypted score file this game understands.
+    Malformed(String),
+}
+
+impl fmt::Display for VaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VaultError::Io(e) => write!(f, "could not access the encrypted scores: {}", e),
+            VaultError::WrongPassphrase => {
+                write!(f, "wrong passphrase, or records were added or removed")
+            }
+            VaultError::Tampered { record } => write!(
+                f,
+                "record {} failed its integrity check; the file was modified",
+                record + 1
+            ),
+            VaultError::Malformed(reason) => {
+                write!(f, "not a valid encrypted score file: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for VaultError {}
+
+impl From<io::Error> for VaultError {
+    fn from(e: io::Error) -> Self {
+        VaultError::Io(e)
+    }
+}
+
+/// How the key is derived from the passphrase. Stored with the file, so
+/// the cost can be raised later without breaking older files.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    #[serde(with = "hex_bytes")]
+    pub salt: Vec<u8>,
+    /// Memory in KiB.
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+impl KdfParams {
+    /// Argon2's recommended costs with a fresh random salt.
+    pub fn generate() -> KdfParams {
+        let mut salt = vec![0; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        KdfParams {
+            salt,
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+/// One encrypted value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sealed {
+    #[serde(with = "hex_bytes")]
+    pub nonce: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    pub ciphertext: Vec<u8>,
+}
+
+/// A key derived from a passphrase.
+pub struct Vault {
+    cipher: ChaCha20Poly1305,
+    kdf: KdfParams,
+}
+
+impl fmt::Debug for Vault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Vault").field("kdf", &self.kdf).finish()
+    }
+}
+
+impl Vault {
+    pub fn derive(passphrase: &str, kdf: KdfParams) -> Result<Vault, VaultError> {
+        if kdf.m_cost > MAX_M_COST || kdf.t_cost > MAX_T_COST || kdf.p_cost > MAX_P_COST {
+            return Err(VaultError::Malformed(format!(
+                "key derivation settings are too costly (at most {} KiB, {} passes and {} lanes)",
+                MAX_M_COST, MAX_T_COST, MAX_P_COST
+            )));
+        }
+        let params = Params::new(kdf.m_cost, kdf.t_cost, kdf.p_cost, Some(32))
+            .map_err(|e| VaultError::Malformed(format!("bad key derivation settings ({})", e)))?;
+        let mut key = Key::default();
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &kdf.salt, &mut key)
+            .map_err(|e| VaultError::Malformed(format!("bad key derivation settings ({})", e)))?;
+        Ok(Vault {
+            cipher: ChaCha20Poly1305::new(&key),
+            kdf,
+        })
+    }
+
+    pub fn kdf(&self) -> &KdfParams {
+        &self.kdf
+    }
+
+    /// Encrypts `plaintext` under a fresh nonce. `context` is authenticated
+    /// but not stored; opening needs the same context.
+    pub fn seal(&self, context: &[u8], plaintext: &[u8]) -> Sealed {
+        let mut nonce = vec![0; 12];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext,
+                    aad: context,
+                },
+            )
+            .expect("the plaintext fits in one message");
+        Sealed { nonce, ciphertext }
+    }
+
+    /// Decrypts `sealed`, or `None` if the key, the context or any byte of
+    /// it does not match.
+    pub fn open(&self, context: &[u8], sealed: &Sealed) -> Option<Vec<u8>> {
+        if sealed.nonce.len() != 12 {
+            return None;
+        }
+        self.cipher
+            .decrypt(
+                Nonce::from_slice(&sealed.nonce),
+                Payload {
+                    msg: &sealed.ciphertext,
+                    aad: context,
+                },
+            )
+            .ok()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    kdf: KdfParams,
+    key_check: Sealed,
+    records: Vec<Sealed>,
+}
+
+/// Binds a record to its place in the file, so records cannot be
+/// reordered, copied between slots or dropped without notice.
+fn record_context(index: usize, count: usize) -> Vec<u8> {
+    format!("score {} of {}", index, count).into_bytes()
+}
+
+/// Binds the key check to the number of records. Each record only vouches
+/// for its own slot, so without this, cutting the list down to nothing
+/// would open as an empty board.
+fn key_check_context(count: usize) -> Vec<u8> {
+    format!("key check, {} scores", count).into_bytes()
+}
+
+/// Score entries kept in a file that only opens with the passphrase.
+#[derive(Debug)]
+pub struct EncryptedScores {
+    path: PathBuf,
+    vault: Vault,
+    entries: Vec<ScoreEntry>,
+}
+
+impl EncryptedScores {
+    /// Opens the file at `path`, or starts an empty one if there is none
+    /// yet. Nothing is written until [`save`](Self::save).
+    pub fn open(path: impl Into<PathBuf>, passphrase: &str) -> Result<EncryptedScores, VaultError> {
+        let path = path.into();
+        let contents = match fs::read(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(EncryptedScores {
+                    path,
+                    vault: Vault::derive(passphrase, KdfParams::generate())?,
+                    entries: Vec::new(),
+                });
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let file: VaultFile =
+            serde_json::from_slice(&contents).map_err(|e| VaultError::Malformed(e.to_string()))?;
+        if file.version != FORMAT_VERSION {
+            return Err(VaultError::Malformed(format!(
+                "it uses format version {}, this game understands version {}",
+                file.version, FORMAT_VERSION
+            )));
+        }
+        let vault = Vault::derive(passphrase, file.kdf)?;
+        let count = file.records.len();
+        if vault
+            .open(&key_check_context(count), &file.key_check)
+            .as_deref()
+            != Some(KEY_CHECK)
+        {
+            return Err(VaultError::WrongPassphrase);
+        }
+
+        let entries = file
+            .records
+            .iter()
+            .enumerate()
+            .map(|(i, sealed)| {
+                let plaintext = vault
+                    .open(&record_context(i, count), sealed)
+                    .ok_or(VaultError::Tampered { record: i })?;
+                serde_json::from_slice(&plaintext)
+                    .map_err(|e| VaultError::Malformed(format!("record {}: {}", i + 1, e)))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(EncryptedScores {
+            path,
+            vault,
+            entries,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn entries(&self) -> &[ScoreEntry] {
+        &self.entries
+    }
+
+    pub fn record(&mut self, entry: ScoreEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Encrypts every entry afresh and replaces the file in one step.
+    pub fn save(&self) -> Result<(), VaultError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let count = self.entries.len();
+        let records = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let plaintext = serde_json::to_vec(entry).expect("score entries serialize");
+                self.vault.seal(&record_context(i, count), &plaintext)
+            })
+            .collect();
+        let file = VaultFile {
+            version: FORMAT_VERSION,
+            kdf: self.vault.kdf.clone(),
+            key_check: self.vault.seal(&key_check_context(count), KEY_CHECK),
+            records,
+        };
+
+        let tmp = self.path.with_extension("tmp");
+        let mut out = fs::File::create(&tmp)?;
+        serde_json::to_writer_pretty(&mut out, &file).map_err(io::Error::from)?;
+        out.write_all(b"\n")?;
+        out.sync_all()?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// Switches to a key derived from `new_passphrase` with a fresh salt
+    /// and re-encrypts every record with it. Once this returns, the old
+    /// passphrase no longer opens the file.
+    pub fn rotate(&mut self, new_passphrase: &str) -> Result<(), VaultError> {
+        let new = Vault::derive(new_passphrase, KdfParams::generate())?;
+        let old = std::mem::replace(&mut self.vault, new);
+        if let Err(e) = self.save() {
+            self.vault = old;
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+/// Byte strings as lowercase hex in JSON.
+mod hex_bytes {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(d)?;
+        hex::decode(text).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(player: &str, attempts: u32) -> ScoreEntry {
+        ScoreEntry {
+            player: player.to_string(),
+            difficulty: "easy (1-10, 6 attempts)".to_string(),
+            won: true,
+            attempts,
+            elapsed_ms: 1500,
+            played_at: 1_700_000_000,
+        }
+    }
+
+    fn saved(dir: &TempDir, name: &str, entries: &[ScoreEntry]) -> PathBuf {
+        let path = dir.path().join(name);
+        let mut scores = EncryptedScores::open(&path, "correct horse").unwrap();
+        for entry in entries {
+            scores.record(entry.clone());
+        }
+        scores.save().unwrap();
+        path
+    }
+
+    fn edit(path: &Path, change: impl FnOnce(&mut VaultFile)) {
+        let mut file: VaultFile = serde_json::from_slice(&fs::read(path).unwrap()).unwrap();
+        change(&mut file);
+        fs::write(path, serde_json::to_vec(&file).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn round_trips_without_leaking_names() {
+        let dir = TempDir::new().unwrap();
+        let entries = [entry("ann", 3), entry("bob", 5)];
+        let path = saved(&dir, "scores.vault", &entries);
+
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(!text.contains("ann") && !text.contains("easy"), "{}", text);
+
+        let scores = EncryptedScores::open(&path, "correct horse").unwrap();
+        assert_eq!(scores.entries(), entries);
+    }
+
+    #[test]
+    fn refuses_the_wrong_passphrase() {
+        let dir = TempDir::new().unwrap();
+        let path = saved(&dir, "scores.vault", &[entry("ann", 3)]);
+        let err = EncryptedScores::open(&path, "Correct horse").unwrap_err();
+        assert!(matches!(err, VaultError::WrongPassphrase), "{}", err);
+    }
+
+    #[test]
+    fn detects_tampered_records() {
+        let dir = TempDir::new().unwrap();
+        let entries = [entry("ann", 3), entry("bob", 5), entry("cat", 4)];
+
+        let path = saved(&dir, "edited.vault", &entries);
+        edit(&path, |file| file.records[1].ciphertext[0] ^= 1);
+        let err = EncryptedScores::open(&path, "correct horse").unwrap_err();
+        assert!(matches!(err, VaultError::Tampered { record: 1 }));
+        assert_eq!(
+            err.to_string(),
+            "record 2 failed its integrity check; the file was modified"
+        );
+
+        // Swapping two intact records is caught too.
+        let path = saved(&dir, "swapped.vault", &entries);
+        edit(&path, |file| file.records.swap(0, 2));
+        assert!(matches!(
+            EncryptedScores::open(&path, "correct horse"),
+            Err(VaultError::Tampered { record: 0 })
+        ));
+
+        // So is dropping the last one, or all of them.
+        let path = saved(&dir, "truncated.vault", &entries);
+        edit(&path, |file| {
+            file.records.pop();
+        });
+        assert!(EncryptedScores::open(&path, "correct horse").is_err());
+        let path = saved(&dir, "emptied.vault", &entries);
+        edit(&path, |file| file.records.clear());
+        assert!(matches!(
+            EncryptedScores::open(&path, "correct horse"),
+            Err(VaultError::WrongPassphrase)
+        ));
+
+        fs::write(&path, "{\"version\": 1}").unwrap();
+        assert!(matches!(
+            EncryptedScores::open(&path, "correct horse"),
+            Err(VaultError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn refuses_costly_key_derivation_settings() {
+        let dir = TempDir::new().unwrap();
+        let path = saved(&dir, "scores.vault", &[entry("ann", 3)]);
+        edit(&path, |file| file.kdf.m_cost = u32::MAX);
+        let err = EncryptedScores::open(&path, "correct horse").unwrap_err();
+        assert!(matches!(err, VaultError::Malformed(_)), "{}", err);
+        assert!(err.to_string().contains("too costly"), "{}", err);
+
+        for (t_cost, p_cost) in [(MAX_T_COST + 1, 1), (2, MAX_P_COST + 1)] {
+            let kdf = KdfParams {
+                t_cost,
+                p_cost,
+                ..KdfParams::generate()
+            };
+            assert!(matches!(
+                Vault::derive("correct horse", kdf),
+                Err(VaultError::Malformed(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn rotation_re_encrypts_every_record() {
+        let dir = TempDir::new().unwrap();
+        let entries = [entry("ann", 3), entry("bob", 5)];
+        let path = saved(&dir, "scores.vault", &entries);
+        let before: VaultFile = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+
+        let mut scores = EncryptedScores::open(&path, "correct horse").unwrap();
+        scores.rotate("battery staple").unwrap();
+
+        let after: VaultFile = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_ne!(after.kdf.salt, before.kdf.salt);
+        for (old, new) in before.records.iter().zip(&after.records) {
+            assert_ne!(old.ciphertext, new.ciphertext);
+        }
+        assert!(matches!(
+            EncryptedScores::open(&path, "correct horse"),
+            Err(VaultError::WrongPassphrase)
+        ));
+        let reopened = EncryptedScores::open(&path, "battery staple").unwrap();
+        assert_eq!(reopened.entries(), entries);
+    }
+}
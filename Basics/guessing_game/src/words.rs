@@ -0,0 +1,661 @@
+//! The word game: the secret is a five-letter word and every guess is
+//! answered letter by letter. Green is the right letter in the right place,
+//! yellow a letter the word has elsewhere, grey a letter it does not have
+//! (or not that many times).
+//!
+//! In hard mode every hint revealed so far must be used: green letters stay
+//! where they are and yellow letters appear somewhere in the next guess.
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{RngCore, SeedableRng};
+
+use crate::guess::{read_input, GuessError};
+use crate::scores;
+
+pub const WORD_LENGTH: usize = 5;
+pub const MAX_ATTEMPTS: u32 = 6;
+
+/// The bundled dictionary, `data/words.txt`.
+const BUNDLED: &str = include_str!("../data/words.txt");
+
+/// Five lowercase ASCII letters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Word([u8; WORD_LENGTH]);
+
+impl Word {
+    pub fn letters(&self) -> &[u8; WORD_LENGTH] {
+        &self.0
+    }
+
+    /// How many times `letter` appears.
+    fn count(&self, letter: u8) -> usize {
+        self.0.iter().filter(|&&l| l == letter).count()
+    }
+}
+
+impl FromStr for Word {
+    type Err = WordError;
+
+    /// Reads a word in any case, ignoring surrounding whitespace.
+    fn from_str(text: &str) -> Result<Word, WordError> {
+        let text = text.trim();
+        if let Some(c) = text.chars().find(|c| !c.is_ascii_alphabetic()) {
+            return Err(WordError::NotALetter(c));
+        }
+        let letters: [u8; WORD_LENGTH] = text
+            .to_ascii_lowercase()
+            .into_bytes()
+            .try_into()
+            .map_err(|_| WordError::WrongLength(text.len()))?;
+        Ok(Word(letters))
+    }
+}
+
+impl fmt::Display for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &letter in &self.0 {
+            write!(f, "{}", char::from(letter.to_ascii_uppercase()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Why a line is not a word at all.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WordError {
+    WrongLength(usize),
+    NotALetter(char),
+}
+
+impl fmt::Display for WordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WordError::WrongLength(got) => {
+                write!(f, "Enter {} letters, not {}.", WORD_LENGTH, got)
+            }
+            WordError::NotALetter(c) => write!(f, "'{}' is not a letter.", c),
+        }
+    }
+}
+
+impl std::error::Error for WordError {}
+
+/// The words the game picks secrets from and accepts as guesses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dictionary {
+    /// Sorted, without duplicates.
+    words: Vec<Word>,
+}
+
+impl Dictionary {
+    pub fn new<I: IntoIterator<Item = Word>>(words: I) -> Dictionary {
+        let mut words: Vec<Word> = words.into_iter().collect();
+        words.sort_unstable();
+        words.dedup();
+        Dictionary { words }
+    }
+
+    /// Reads one word per line. Blank lines and lines starting with `#` are
+    /// skipped; the error gives the line number of the first bad word.
+    pub fn parse(text: &str) -> Result<Dictionary, (usize, WordError)> {
+        let mut words = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            words.push(line.parse().map_err(|e| (i + 1, e))?);
+        }
+        Ok(Dictionary::new(words))
+    }
+
+    /// The word list that ships with the game.
+    pub fn bundled() -> &'static Dictionary {
+        static BUNDLED_DICTIONARY: OnceLock<Dictionary> = OnceLock::new();
+        BUNDLED_DICTIONARY.get_or_init(|| {
+            Dictionary::parse(BUNDLED)
+                .unwrap_or_else(|(line, e)| panic!("data/words.txt line {}: {}", line, e))
+        })
+    }
+
+    pub fn contains(&self, word: &Word) -> bool {
+        self.words.binary_search(word).is_ok()
+    }
+
+    pub fn words(&self) -> &[Word] {
+        &self.words
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Draws a secret. Panics if the dictionary is empty.
+    pub fn random_word<G: RngCore + ?Sized>(&self, rng: &mut G) -> Word {
+        *self.words.choose(rng).expect("the dictionary is empty")
+    }
+
+    /// The secret everyone gets on `day`, counted in days since 1970-01-01.
+    /// It only changes if the dictionary does.
+    pub fn daily_word(&self, day: i64) -> Word {
+        self.random_word(&mut StdRng::seed_from_u64(daily_seed(day)))
+    }
+}
+
+/// The seed for the daily word. Neighbouring days get unrelated seeds, so
+/// one day's word says nothing about the next.
+pub fn daily_seed(day: i64) -> u64 {
+    // SplitMix64's finalizer.
+    let mut z = (day as u64).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Today in days since 1970-01-01. Days change at midnight UTC, so every
+/// player gets the same daily word at the same moment.
+pub fn today() -> i64 {
+    (scores::now() / 86_400) as i64
+}
+
+/// Reads a `YYYY-MM-DD` date as days since 1970-01-01.
+pub fn parse_date(text: &str) -> Option<i64> {
+    let mut parts = text.trim().splitn(3, '-');
+    let year = i64::from(parts.next()?.parse::<u16>().ok()?);
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    let days = scores::days_from_civil(year, month, day);
+    // Reject dates such as 2023-02-30 that would roll over into March.
+    (scores::civil_from_days(days) == (year, month, day)).then_some(days)
+}
+
+/// The colour of one letter's tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mark {
+    /// Right letter, right place.
+    Green,
+    /// The word has this letter somewhere else.
+    Yellow,
+    /// The word has no (more) of this letter.
+    Grey,
+}
+
+/// The answer to one guess, a mark per letter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Feedback(pub [Mark; WORD_LENGTH]);
+
+impl Feedback {
+    /// Marks `guess` against `secret`. A letter guessed more often than the
+    /// secret has it is only marked as many times as the secret has it,
+    /// greens first and then yellows from left to right.
+    pub fn of(secret: &Word, guess: &Word) -> Feedback {
+        let mut marks = [Mark::Grey; WORD_LENGTH];
+        let mut unmatched = [0u8; 26];
+        for i in 0..WORD_LENGTH {
+            if guess.0[i] == secret.0[i] {
+                marks[i] = Mark::Green;
+            } else {
+                unmatched[usize::from(secret.0[i] - b'a')] += 1;
+            }
+        }
+        for i in 0..WORD_LENGTH {
+            let left = &mut unmatched[usize::from(guess.0[i] - b'a')];
+            if marks[i] != Mark::Green && *left > 0 {
+                marks[i] = Mark::Yellow;
+                *left -= 1;
+            }
+        }
+        Feedback(marks)
+    }
+
+    pub fn is_win(&self) -> bool {
+        self.0.iter().all(|&mark| mark == Mark::Green)
+    }
+}
+
+impl fmt::Display for Feedback {
+    /// The squares people paste to share a game.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for mark in &self.0 {
+            f.write_str(match mark {
+                Mark::Green => "🟩",
+                Mark::Yellow => "🟨",
+                Mark::Grey => "⬜",
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Shows a marked guess. With `colour` each letter gets an ANSI-coloured
+/// tile; without, the word is followed by its squares.
+pub fn paint(guess: &Word, feedback: &Feedback, colour: bool) -> String {
+    if !colour {
+        return format!("{}  {}", guess, feedback);
+    }
+    let mut painted = String::new();
+    for (&letter, mark) in guess.0.iter().zip(&feedback.0) {
+        let style = match mark {
+            Mark::Green => "1;97;42",
+            Mark::Yellow => "1;30;43",
+            Mark::Grey => "1;97;100",
+        };
+        painted.push_str(&format!(
+            "\x1b[{}m {} \x1b[0m",
+            style,
+            char::from(letter.to_ascii_uppercase())
+        ));
+    }
+    painted
+}
+
+/// A guess that breaks hard mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HardModeError {
+    /// A green letter was not kept in place. `position` counts from 1.
+    Moved { letter: u8, position: usize },
+    /// Fewer of `letter` than the hints have revealed.
+    Missing { letter: u8, count: usize },
+}
+
+impl fmt::Display for HardModeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            HardModeError::Moved { letter, position } => write!(
+                f,
+                "Hard mode: letter {} must be {}.",
+                position,
+                char::from(letter.to_ascii_uppercase())
+            ),
+            HardModeError::Missing { letter, count: 1 } => write!(
+                f,
+                "Hard mode: the guess must contain {}.",
+                char::from(letter.to_ascii_uppercase())
+            ),
+            HardModeError::Missing { letter, count } => write!(
+                f,
+                "Hard mode: the guess must contain {} {}s.",
+                count,
+                char::from(letter.to_ascii_uppercase())
+            ),
+        }
+    }
+}
+
+/// How a game of the word game ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordOutcome {
+    Won { secret: Word, attempts: u32 },
+    Lost { secret: Word, attempts: u32 },
+    Quit { secret: Word, attempts: u32 },
+}
+
+/// Why a guess was refused. A refused guess does not cost an attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WordRoundError {
+    Invalid(WordError),
+    NotInDictionary(Word),
+    HardMode(HardModeError),
+    /// The round is already won, lost or abandoned.
+    GameOver(WordOutcome),
+}
+
+impl fmt::Display for WordRoundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WordRoundError::Invalid(e) => e.fmt(f),
+            WordRoundError::NotInDictionary(word) => write!(f, "{} is not in the word list.", word),
+            WordRoundError::HardMode(e) => e.fmt(f),
+            WordRoundError::GameOver(_) => write!(f, "The game is already over."),
+        }
+    }
+}
+
+impl std::error::Error for WordRoundError {}
+
+/// One game with no I/O, the counterpart of [`crate::Round`].
+#[derive(Debug, Clone)]
+pub struct WordRound<'d> {
+    dictionary: &'d Dictionary,
+    secret: Word,
+    hard: bool,
+    history: Vec<(Word, Feedback)>,
+    outcome: Option<WordOutcome>,
+}
+
+impl<'d> WordRound<'d> {
+    /// Starts a round. Panics if the secret is not in the dictionary, since
+    /// the player could then never type it.
+    pub fn new(dictionary: &'d Dictionary, secret: Word, hard: bool) -> WordRound<'d> {
+        assert!(
+            dictionary.contains(&secret),
+            "secret {} is not in the dictionary",
+            secret
+        );
+        WordRound {
+            dictionary,
+            secret,
+            hard,
+            history: Vec::new(),
+            outcome: None,
+        }
+    }
+
+    /// Marks a guess, counting it as an attempt.
+    pub fn guess(&mut self, guess: &Word) -> Result<Feedback, WordRoundError> {
+        if let Some(outcome) = self.outcome {
+            return Err(WordRoundError::GameOver(outcome));
+        }
+        if !self.dictionary.contains(guess) {
+            return Err(WordRoundError::NotInDictionary(*guess));
+        }
+        if self.hard {
+            self.check_hard_mode(guess)
+                .map_err(WordRoundError::HardMode)?;
+        }
+
+        let feedback = Feedback::of(&self.secret, guess);
+        self.history.push((*guess, feedback));
+        let (secret, attempts) = (self.secret, self.attempts());
+        if feedback.is_win() {
+            self.outcome = Some(WordOutcome::Won { secret, attempts });
+        } else if attempts == MAX_ATTEMPTS {
+            self.outcome = Some(WordOutcome::Lost { secret, attempts });
+        }
+        Ok(feedback)
+    }
+
+    /// Checks that `guess` uses every hint given so far.
+    fn check_hard_mode(&self, guess: &Word) -> Result<(), HardModeError> {
+        for (previous, feedback) in &self.history {
+            for (i, mark) in feedback.0.iter().enumerate() {
+                if *mark == Mark::Green && guess.0[i] != previous.0[i] {
+                    return Err(HardModeError::Moved {
+                        letter: previous.0[i],
+                        position: i + 1,
+                    });
+                }
+            }
+            for (i, &letter) in previous.0.iter().enumerate() {
+                let revealed = previous
+                    .0
+                    .iter()
+                    .zip(&feedback.0)
+                    .filter(|&(&l, &mark)| l == letter && mark != Mark::Grey)
+                    .count();
+                // Only report each letter once, at its first appearance.
+                if previous.0[..i].contains(&letter) {
+                    continue;
+                }
+                if guess.count(letter) < revealed {
+                    return Err(HardModeError::Missing {
+                        letter,
+                        count: revealed,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Abandons the round. Does nothing if it is already over.
+    pub fn quit(&mut self) -> WordOutcome {
+        *self.outcome.get_or_insert(WordOutcome::Quit {
+            secret: self.secret,
+            attempts: self.history.len() as u32,
+        })
+    }
+
+    pub fn outcome(&self) -> Option<WordOutcome> {
+        self.outcome
+    }
+
+    pub fn secret(&self) -> Word {
+        self.secret
+    }
+
+    pub fn is_hard(&self) -> bool {
+        self.hard
+    }
+
+    /// Every accepted guess so far with its marks.
+    pub fn history(&self) -> &[(Word, Feedback)] {
+        &self.history
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.history.len() as u32
+    }
+
+    pub fn attempts_left(&self) -> u32 {
+        MAX_ATTEMPTS - self.attempts()
+    }
+}
+
+/// Plays one game on a terminal, like [`crate::Game::play`] does for the
+/// number game. `colour` turns on ANSI colours for the tiles.
+pub fn play<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    mut round: WordRound<'_>,
+    colour: bool,
+) -> io::Result<WordOutcome> {
+    writeln!(output, "Guess the word!")?;
+    writeln!(
+        output,
+        "Find the {}-letter word, you have {} attempts.",
+        WORD_LENGTH, MAX_ATTEMPTS
+    )?;
+    writeln!(
+        output,
+        "Green is the right letter in the right place, yellow a letter in the wrong place."
+    )?;
+    if round.is_hard() {
+        writeln!(output, "Hard mode: every hint you are given must be used.")?;
+    }
+
+    loop {
+        writeln!(output, "Please input your guess.")?;
+        output.flush()?;
+        let line = match read_input(input) {
+            Ok(line) => line,
+            Err(GuessError::Eof) => {
+                writeln!(output, "No more input. The word was {}.", round.secret())?;
+                return Ok(round.quit());
+            }
+            Err(e) => {
+                writeln!(output, "{}", e)?;
+                continue;
+            }
+        };
+        let feedback = match line.parse().map_err(WordRoundError::Invalid) {
+            Ok(word) => round.guess(&word).map(|feedback| (word, feedback)),
+            Err(e) => Err(e),
+        };
+        match feedback {
+            Ok((word, feedback)) => writeln!(output, "{}", paint(&word, &feedback, colour))?,
+            Err(e) => {
+                writeln!(output, "{}", e)?;
+                continue;
+            }
+        }
+
+        match round.outcome() {
+            Some(outcome @ WordOutcome::Won { attempts, .. }) => {
+                writeln!(output, "You win! It took you {} attempts.", attempts)?;
+                return Ok(outcome);
+            }
+            Some(outcome @ WordOutcome::Lost { secret, .. }) => {
+                writeln!(output, "You lose! The word was {}.", secret)?;
+                return Ok(outcome);
+            }
+            Some(outcome) => return Ok(outcome),
+            None => writeln!(output, "{} attempts left.", round.attempts_left())?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Mark::{Green as G, Grey as X, Yellow as Y};
+
+    fn word(text: &str) -> Word {
+        text.parse().unwrap()
+    }
+
+    fn marks(secret: &str, guess: &str) -> [Mark; WORD_LENGTH] {
+        Feedback::of(&word(secret), &word(guess)).0
+    }
+
+    #[test]
+    fn marks_letters() {
+        assert_eq!(marks("crane", "crane"), [G, G, G, G, G]);
+        assert_eq!(marks("crane", "nacre"), [Y, Y, Y, Y, G]);
+        assert_eq!(marks("crane", "pilot"), [X, X, X, X, X]);
+        // A doubled guess letter is yellow once, grey the second time.
+        assert_eq!(marks("abide", "speed"), [X, X, Y, X, Y]);
+        // The green is marked first even when it comes after the yellow.
+        assert_eq!(marks("abbey", "kebab"), [X, Y, G, Y, Y]);
+        assert_eq!(marks("those", "geese"), [X, X, X, G, G]);
+        // Both copies count when the secret has two.
+        assert_eq!(marks("eerie", "geese"), [X, G, Y, X, G]);
+    }
+
+    #[test]
+    fn parses_words() {
+        assert_eq!(word(" Crane\n").to_string(), "CRANE");
+        assert_eq!("cran".parse::<Word>(), Err(WordError::WrongLength(4)));
+        assert_eq!("cranes".parse::<Word>(), Err(WordError::WrongLength(6)));
+        assert_eq!("cr4ne".parse::<Word>(), Err(WordError::NotALetter('4')));
+        assert_eq!(
+            Dictionary::parse("# list\ncrane\n\nCRANE\nslate\nnope\n"),
+            Err((6, WordError::WrongLength(4)))
+        );
+        assert_eq!(Dictionary::parse("crane\nslate\nCRANE").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn bundled_dictionary_and_daily_word() {
+        let dictionary = Dictionary::bundled();
+        assert!(dictionary.len() > 500);
+        assert!(dictionary.contains(&word("crane")));
+
+        let day = parse_date("2024-02-29").unwrap();
+        assert_eq!(day, 19_782);
+        assert_eq!(dictionary.daily_word(day), dictionary.daily_word(day));
+        let week: std::collections::HashSet<Word> =
+            (day..day + 7).map(|d| dictionary.daily_word(d)).collect();
+        assert!(week.len() > 1);
+
+        assert_eq!(parse_date("1970-01-01"), Some(0));
+        assert_eq!(parse_date("2023-02-29"), None);
+        assert_eq!(parse_date("yesterday"), None);
+    }
+
+    #[test]
+    fn hard_mode_keeps_revealed_hints() {
+        let dictionary = Dictionary::parse("crane\nbrine\nspeed\nshine\nsnipe\nspine").unwrap();
+        let mut round = WordRound::new(&dictionary, word("spine"), true);
+        assert_eq!(round.guess(&word("crane")).unwrap().0, [X, X, X, G, G]);
+        assert_eq!(
+            round.guess(&word("speed")),
+            Err(WordRoundError::HardMode(HardModeError::Moved {
+                letter: b'n',
+                position: 4
+            }))
+        );
+        assert_eq!(round.attempts(), 1);
+        assert_eq!(round.guess(&word("brine")).unwrap().0, [X, X, G, G, G]);
+        assert_eq!(round.guess(&word("shine")).unwrap().0, [G, X, G, G, G]);
+        assert_eq!(round.guess(&word("spine")).unwrap().0, [G, G, G, G, G]);
+        assert!(matches!(
+            round.outcome(),
+            Some(WordOutcome::Won { attempts: 4, .. })
+        ));
+
+        // Yellow letters must come back, as many times as they were revealed.
+        let dictionary = Dictionary::parse("geese\neerie\ncrane\nslate").unwrap();
+        let mut round = WordRound::new(&dictionary, word("eerie"), true);
+        round.guess(&word("geese")).unwrap();
+        assert_eq!(
+            round.check_hard_mode(&word("slate")),
+            Err(HardModeError::Moved {
+                letter: b'e',
+                position: 2
+            })
+        );
+        let mut round = WordRound::new(&dictionary, word("crane"), true);
+        round.guess(&word("eerie")).unwrap();
+        assert_eq!(
+            round.guess(&word("slate")),
+            Err(WordRoundError::HardMode(HardModeError::Missing {
+                letter: b'r',
+                count: 1
+            }))
+        );
+        assert_eq!(
+            HardModeError::Missing {
+                letter: b'e',
+                count: 2
+            }
+            .to_string(),
+            "Hard mode: the guess must contain 2 Es."
+        );
+        // Outside hard mode anything in the dictionary goes.
+        let mut round = WordRound::new(&dictionary, word("crane"), false);
+        round.guess(&word("eerie")).unwrap();
+        assert!(round.guess(&word("slate")).is_ok());
+    }
+
+    #[test]
+    fn plays_on_a_terminal() {
+        let dictionary = Dictionary::bundled();
+        let round = WordRound::new(dictionary, word("crane"), false);
+        let input = "cran\nzzzzz\nslate\ncrane\n";
+        let mut output = Vec::new();
+        let outcome = play(&mut input.as_bytes(), &mut output, round, false).unwrap();
+        assert_eq!(
+            outcome,
+            WordOutcome::Won {
+                secret: word("crane"),
+                attempts: 2
+            }
+        );
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Enter 5 letters, not 4."), "{}", output);
+        assert!(
+            output.contains("ZZZZZ is not in the word list."),
+            "{}",
+            output
+        );
+        assert!(output.contains("SLATE  ⬜⬜🟩⬜🟩"), "{}", output);
+        assert!(
+            output.contains("You win! It took you 2 attempts."),
+            "{}",
+            output
+        );
+
+        let painted = paint(
+            &word("slate"),
+            &Feedback::of(&word("crane"), &word("slate")),
+            true,
+        );
+        assert!(
+            painted.starts_with("\x1b[1;97;100m S \x1b[0m"),
+            "{:?}",
+            painted
+        );
+        assert!(painted.contains("\x1b[1;97;42m A \x1b[0m"), "{:?}", painted);
+    }
+}
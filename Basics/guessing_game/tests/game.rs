@@ -0,0 +1,107 @@
+use guessing_game::{Difficulty, Game, Outcome};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// The secret a `Game` seeded with `seed` will pick.
+fn secret_for(seed: u64) -> u32 {
+    StdRng::seed_from_u64(seed).gen_range(1..=100)
+}
+
+fn play(seed: u64, input: &str) -> (Outcome, String) {
+    let mut game = Game::new(input.as_bytes(), Vec::new(), StdRng::seed_from_u64(seed));
+    let outcome = game.play().unwrap();
+    (outcome, String::from_utf8(game.into_output()).unwrap())
+}
+
+#[test]
+fn scripted_session_produces_full_transcript() {
+    let seed = 7;
+    let secret = secret_for(seed);
+    assert!(secret > 1 && secret < 100, "pick a seed whose secret is not on the boundary");
+
+    let (outcome, transcript) = play(seed, &format!("1\n100\nnope\n{}\n", secret));
+
+    assert_eq!(outcome, Outcome::Won { secret, attempts: 3 });
+    assert_eq!(
+        transcript,
+        format!(
+            "Guess the number!\n\
+             The secret is between 1 and 100, you have 10 attempts.\n\
+             Please input your guess.\n\
+             You guessed: 1\n\
+             Too small!\n\
+             9 attempts left.\n\
+             Please input your guess.\n\
+             You guessed: 100\n\
+             Too big!\n\
+             8 attempts left.\n\
+             Please input your guess.\n\
+             'nope' is not a whole number.\n\
+             Please input your guess.\n\
+             You guessed: {}\n\
+             You win! It took you 3 attempts.\n",
+            secret
+        )
+    );
+}
+
+#[test]
+fn same_seed_picks_same_secret() {
+    let (first, _) = play(99, "");
+    let (second, _) = play(99, "");
+    assert_eq!(first, second);
+    assert_eq!(first, Outcome::Quit { secret: secret_for(99), attempts: 0 });
+}
+
+#[test]
+fn running_out_of_input_reveals_secret() {
+    let seed = 3;
+    let (outcome, transcript) = play(seed, "\n");
+    assert_eq!(outcome, Outcome::Quit { secret: secret_for(seed), attempts: 0 });
+    assert!(transcript.ends_with(&format!(
+        "Please type a number before pressing Enter.\nPlease input your guess.\nNo more input. The secret number was {}.\n",
+        secret_for(seed)
+    )));
+}
+
+#[test]
+fn running_out_of_attempts_loses() {
+    let seed = 11;
+    let difficulty = Difficulty::custom(1..=1000, 2).unwrap();
+    let secret = StdRng::seed_from_u64(seed).gen_range(difficulty.range());
+    let wrong = if secret == 1 { 2 } else { 1 };
+    let input = format!("{}\n{}\n{}\n", wrong, wrong, secret);
+
+    let mut game = Game::with_difficulty(
+        input.as_bytes(),
+        Vec::new(),
+        StdRng::seed_from_u64(seed),
+        difficulty,
+    );
+    let outcome = game.play().unwrap();
+    let transcript = String::from_utf8(game.into_output()).unwrap();
+
+    assert_eq!(
+        outcome,
+        Outcome::Lost {
+            secret,
+            attempts: 2
+        }
+    );
+    assert!(transcript.ends_with(&format!("You lose! The secret number was {}.\n", secret)));
+}
+
+#[test]
+fn guesses_outside_the_difficulty_range_are_rejected() {
+    let mut game = Game::with_difficulty(
+        "11\n".as_bytes(),
+        Vec::new(),
+        StdRng::seed_from_u64(1),
+        Difficulty::Easy,
+    );
+    let outcome = game.play().unwrap();
+    let transcript = String::from_utf8(game.into_output()).unwrap();
+
+    assert!(matches!(outcome, Outcome::Quit { attempts: 0, .. }));
+    assert!(transcript.contains("11 is out of range, pick a number between 1 and 10."));
+}
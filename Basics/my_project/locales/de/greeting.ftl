@@ -0,0 +1,9 @@
+hello = Hallo, { $name }!
+
+welcome-back = Willkommen zurück, { $name }!
+
+new-messages = { $count ->
+    [0] Du hast keine neuen Nachrichten.
+    [one] Du hast eine neue Nachricht.
+   *[other] Du hast { $count } neue Nachrichten.
+}
@@ -0,0 +1,12 @@
+# English, the last locale in every fallback chain: every message must be
+# here.
+
+hello = Hello, { $name }!
+
+welcome-back = Welcome back, { $name }!
+
+new-messages = { $count ->
+    [0] You have no new messages.
+    [one] You have one new message.
+   *[other] You have { $count } new messages.
+}
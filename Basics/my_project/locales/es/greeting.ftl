@@ -0,0 +1,13 @@
+hello = ¡Hola, { $name }!
+
+welcome-back = { $gender ->
+    [female] ¡Bienvenida de nuevo, { $name }!
+    [male] ¡Bienvenido de nuevo, { $name }!
+   *[other] ¡Qué bueno verte de nuevo, { $name }!
+}
+
+new-messages = { $count ->
+    [0] No tienes mensajes nuevos.
+    [one] Tienes un mensaje nuevo.
+   *[other] Tienes { $count } mensajes nuevos.
+}
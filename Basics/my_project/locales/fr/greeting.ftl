@@ -0,0 +1,15 @@
+# French counts 0 and 1 as "one", so 0 needs its own variant.
+
+hello = Bonjour, { $name } !
+
+welcome-back = { $gender ->
+    [female] Sois la bienvenue, { $name } !
+    [male] Sois le bienvenu, { $name } !
+   *[other] Bon retour parmi nous, { $name } !
+}
+
+new-messages = { $count ->
+    [0] Tu n’as aucun nouveau message.
+    [one] Tu as { $count } nouveau message.
+   *[other] Tu as { $count } nouveaux messages.
+}
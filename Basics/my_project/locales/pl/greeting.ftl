@@ -0,0 +1,16 @@
+# Polish: 2-4, 22-24, ... are "few"; 5-21, 25-31, ... are "many".
+
+hello = Cześć, { $name }!
+
+welcome-back = { $gender ->
+    [female] Dobrze, że wróciłaś, { $name }!
+    [male] Dobrze, że wróciłeś, { $name }!
+   *[other] Witaj ponownie, { $name }!
+}
+
+new-messages = { $count ->
+    [0] Nie masz nowych wiadomości.
+    [one] Masz jedną nową wiadomość.
+    [few] Masz { $count } nowe wiadomości.
+   *[many] Masz { $count } nowych wiadomości.
+}
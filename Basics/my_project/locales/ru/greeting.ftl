@@ -0,0 +1,17 @@
+# Russian: 1, 21, 31, ... are "one"; 2-4, 22-24, ... are "few"; the rest
+# of the whole numbers are "many".
+
+hello = Привет, { $name }!
+
+welcome-back = { $gender ->
+    [female] Рады, что ты вернулась, { $name }!
+    [male] Рады, что ты вернулся, { $name }!
+   *[other] С возвращением, { $name }!
+}
+
+new-messages = { $count ->
+    [0] У тебя нет новых сообщений.
+    [one] У тебя { $count } новое сообщение.
+    [few] У тебя { $count } новых сообщения.
+   *[many] У тебя { $count } новых сообщений.
+}
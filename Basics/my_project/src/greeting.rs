@@ -0,0 +1,327 @@
+//! Greetings in the user's language. The wording lives in the Fluent files
+//! under `locales/`, which are compiled into the program.
+
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
+use fluent_langneg::{negotiate_languages, NegotiationStrategy};
+use unic_langid::LanguageIdentifier;
+
+/// Every bundled locale with its messages. English comes first because it
+/// ends every fallback chain, so it has to have every message.
+const LOCALES: [(&str, &str); 6] = [
+    ("en", include_str!("../locales/en/greeting.ftl")),
+    ("de", include_str!("../locales/de/greeting.ftl")),
+    ("es", include_str!("../locales/es/greeting.ftl")),
+    ("fr", include_str!("../locales/fr/greeting.ftl")),
+    ("pl", include_str!("../locales/pl/greeting.ftl")),
+    ("ru", include_str!("../locales/ru/greeting.ftl")),
+];
+
+/// How to address someone in languages whose words change with it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Gender {
+    Female,
+    Male,
+    /// Unknown or neither: the neutral wording.
+    #[default]
+    Other,
+}
+
+impl Gender {
+    fn as_str(self) -> &'static str {
+        match self {
+            Gender::Female => "female",
+            Gender::Male => "male",
+            Gender::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for Gender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Gender {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "female" | "f" => Ok(Gender::Female),
+            "male" | "m" => Ok(Gender::Male),
+            "other" | "o" => Ok(Gender::Other),
+            other => Err(format!("unknown gender '{}'", other)),
+        }
+    }
+}
+
+/// Formats greetings for one requested locale. Each message comes from the
+/// closest bundled locale that has it: `es-MX` tries `es`, then English.
+pub struct Greeter {
+    /// Best match first, English last.
+    chain: Vec<FluentBundle<FluentResource>>,
+}
+
+impl Greeter {
+    /// Takes a language tag such as `pt-BR`, or a POSIX locale such as
+    /// `pl_PL.UTF-8`. Anything unknown gets English.
+    pub fn new(locale: &str) -> Greeter {
+        let available: Vec<LanguageIdentifier> = LOCALES
+            .iter()
+            .map(|(tag, _)| tag.parse().expect("bundled locale tags are valid"))
+            .collect();
+        let requested: Vec<LanguageIdentifier> = parse_locale(locale).into_iter().collect();
+        let chain = negotiate_languages(
+            &requested,
+            &available,
+            Some(&available[0]),
+            NegotiationStrategy::Filtering,
+        )
+        .into_iter()
+        .map(|id| {
+            let index = available.iter().position(|a| a == id).unwrap();
+            bundle(id.clone(), LOCALES[index].1)
+        })
+        .collect();
+        Greeter { chain }
+    }
+
+    /// Uses the first of `LC_ALL`, `LC_MESSAGES` and `LANG` that is set, the
+    /// way other command line programs pick their language.
+    pub fn from_env() -> Greeter {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        Greeter::new(&locale)
+    }
+
+    /// "Hello, Ferris!"
+    pub fn hello(&self, name: &str) -> String {
+        let mut args = FluentArgs::new();
+        args.set("name", name);
+        self.format("hello", &args)
+    }
+
+    /// "Welcome back, Ferris! You have 3 new messages."
+    pub fn welcome_back(&self, name: &str, gender: Gender, unread: u32) -> String {
+        let mut args = FluentArgs::new();
+        args.set("name", name);
+        args.set("gender", gender.as_str());
+        args.set("count", unread);
+        format!(
+            "{} {}",
+            self.format("welcome-back", &args),
+            self.format("new-messages", &args)
+        )
+    }
+
+    fn format(&self, id: &str, args: &FluentArgs) -> String {
+        for bundle in &self.chain {
+            let Some(pattern) = bundle.get_message(id).and_then(|m| m.value()) else {
+                continue;
+            };
+            let mut errors = Vec::new();
+            let text = bundle.format_pattern(pattern, Some(args), &mut errors);
+            if errors.is_empty() {
+                return text.into_owned();
+            }
+        }
+        // English has every message, so this only happens if one was
+        // removed from it.
+        id.to_string()
+    }
+}
+
+/// Greets `name` in the language of the environment.
+pub fn greet(name: &str) -> String {
+    Greeter::from_env().hello(name)
+}
+
+/// Reads `pl_PL.UTF-8` as `pl-PL`. `C` and `POSIX` ask for no language in
+/// particular.
+fn parse_locale(locale: &str) -> Option<LanguageIdentifier> {
+    let tag = locale
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .replace('_', "-");
+    match tag.as_str() {
+        "" | "C" | "POSIX" => None,
+        tag => tag.parse().ok(),
+    }
+}
+
+fn bundle(locale: LanguageIdentifier, source: &str) -> FluentBundle<FluentResource> {
+    let resource =
+        FluentResource::try_new(source.to_string()).expect("bundled message files parse");
+    let mut bundle = FluentBundle::new(vec![locale]);
+    // Direction marks around arguments would end up in plain terminal
+    // output.
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .expect("bundled message files have no duplicate ids");
+    bundle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_locale_has_every_message() {
+        for (tag, source) in LOCALES {
+            let bundle = bundle(tag.parse().unwrap(), source);
+            for id in ["hello", "welcome-back", "new-messages"] {
+                assert!(bundle.has_message(id), "{} has no {}", tag, id);
+            }
+        }
+    }
+
+    #[test]
+    fn english() {
+        let en = Greeter::new("en-US");
+        assert_eq!(en.hello("Ferris"), "Hello, Ferris!");
+        assert_eq!(
+            en.welcome_back("Ferris", Gender::Other, 0),
+            "Welcome back, Ferris! You have no new messages."
+        );
+        assert_eq!(
+            en.welcome_back("Ferris", Gender::Female, 1),
+            "Welcome back, Ferris! You have one new message."
+        );
+        assert_eq!(
+            en.welcome_back("Ferris", Gender::Male, 2),
+            "Welcome back, Ferris! You have 2 new messages."
+        );
+    }
+
+    #[test]
+    fn german() {
+        let de = Greeter::new("de");
+        assert_eq!(de.hello("Jonas"), "Hallo, Jonas!");
+        assert_eq!(
+            de.welcome_back("Jonas", Gender::Male, 1),
+            "Willkommen zurück, Jonas! Du hast eine neue Nachricht."
+        );
+        assert_eq!(
+            de.welcome_back("Mia", Gender::Female, 7),
+            "Willkommen zurück, Mia! Du hast 7 neue Nachrichten."
+        );
+    }
+
+    #[test]
+    fn spanish() {
+        let es = Greeter::new("es");
+        assert_eq!(es.hello("Ana"), "¡Hola, Ana!");
+        assert_eq!(
+            es.welcome_back("Ana", Gender::Female, 1),
+            "¡Bienvenida de nuevo, Ana! Tienes un mensaje nuevo."
+        );
+        assert_eq!(
+            es.welcome_back("Luis", Gender::Male, 3),
+            "¡Bienvenido de nuevo, Luis! Tienes 3 mensajes nuevos."
+        );
+        assert_eq!(
+            es.welcome_back("Alex", Gender::Other, 0),
+            "¡Qué bueno verte de nuevo, Alex! No tienes mensajes nuevos."
+        );
+    }
+
+    #[test]
+    fn french() {
+        let fr = Greeter::new("fr");
+        assert_eq!(fr.hello("Chloé"), "Bonjour, Chloé !");
+        assert_eq!(
+            fr.welcome_back("Chloé", Gender::Female, 0),
+            "Sois la bienvenue, Chloé ! Tu n’as aucun nouveau message."
+        );
+        assert_eq!(
+            fr.welcome_back("Hugo", Gender::Male, 1),
+            "Sois le bienvenu, Hugo ! Tu as 1 nouveau message."
+        );
+        assert_eq!(
+            fr.welcome_back("Camille", Gender::Other, 2),
+            "Bon retour parmi nous, Camille ! Tu as 2 nouveaux messages."
+        );
+    }
+
+    #[test]
+    fn polish() {
+        let pl = Greeter::new("pl");
+        assert_eq!(pl.hello("Ola"), "Cześć, Ola!");
+        assert_eq!(
+            pl.welcome_back("Ola", Gender::Female, 1),
+            "Dobrze, że wróciłaś, Ola! Masz jedną nową wiadomość."
+        );
+        assert_eq!(
+            pl.welcome_back("Piotr", Gender::Male, 3),
+            "Dobrze, że wróciłeś, Piotr! Masz 3 nowe wiadomości."
+        );
+        let many = |count| pl.welcome_back("Kim", Gender::Other, count);
+        assert_eq!(many(5), "Witaj ponownie, Kim! Masz 5 nowych wiadomości.");
+        assert_eq!(many(12), "Witaj ponownie, Kim! Masz 12 nowych wiadomości.");
+        assert_eq!(many(22), "Witaj ponownie, Kim! Masz 22 nowe wiadomości.");
+    }
+
+    #[test]
+    fn russian() {
+        let ru = Greeter::new("ru");
+        assert_eq!(ru.hello("Оля"), "Привет, Оля!");
+        assert_eq!(
+            ru.welcome_back("Оля", Gender::Female, 21),
+            "Рады, что ты вернулась, Оля! У тебя 21 новое сообщение."
+        );
+        assert_eq!(
+            ru.welcome_back("Иван", Gender::Male, 3),
+            "Рады, что ты вернулся, Иван! У тебя 3 новых сообщения."
+        );
+        assert_eq!(
+            ru.welcome_back("Саша", Gender::Other, 11),
+            "С возвращением, Саша! У тебя 11 новых сообщений."
+        );
+        assert_eq!(
+            ru.welcome_back("Саша", Gender::Other, 0),
+            "С возвращением, Саша! У тебя нет новых сообщений."
+        );
+    }
+
+    #[test]
+    fn falls_back_through_the_chain() {
+        assert_eq!(Greeter::new("es-MX").hello("Ana"), "¡Hola, Ana!");
+        assert_eq!(Greeter::new("pl_PL.UTF-8").hello("Ola"), "Cześć, Ola!");
+        assert_eq!(Greeter::new("fr_CA@euro").hello("Zoé"), "Bonjour, Zoé !");
+        for unknown in ["ja-JP", "C", "", "not a locale"] {
+            assert_eq!(Greeter::new(unknown).hello("Ferris"), "Hello, Ferris!");
+        }
+
+        // A message missing from the closest locale comes from the next.
+        let greeter = Greeter {
+            chain: vec![
+                bundle("de-CH".parse().unwrap(), "hello = Grüezi, { $name }!"),
+                bundle("de".parse().unwrap(), LOCALES[1].1),
+                bundle("en".parse().unwrap(), LOCALES[0].1),
+            ],
+        };
+        assert_eq!(greeter.hello("Lea"), "Grüezi, Lea!");
+        assert_eq!(
+            greeter.welcome_back("Lea", Gender::Female, 2),
+            "Willkommen zurück, Lea! Du hast 2 neue Nachrichten."
+        );
+        assert_eq!(Greeter { chain: Vec::new() }.hello("Lea"), "hello");
+    }
+
+    #[test]
+    fn parses_genders() {
+        assert_eq!("Female".parse(), Ok(Gender::Female));
+        assert_eq!("m".parse(), Ok(Gender::Male));
+        assert_eq!(Gender::Other.to_string(), "other");
+        assert!("x".parse::<Gender>().is_err());
+    }
+}
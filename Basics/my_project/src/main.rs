@@ -1,3 +1,12 @@
+mod greeting;
+
+use std::env;
+use std::process;
+
+use greeting::{Gender, Greeter};
+
+/// Usage: my_project [NAME [female|male|other]]. The greeting is in the
+/// language of `LC_ALL`, `LC_MESSAGES` or `LANG`.
 fn main() {
     println!("Hello, world!");
     println!("--------------");
@@ -21,9 +30,15 @@ fn main() {
     }
     println!("--------------");
 
-    greet("Rustacean");
+    let mut args = env::args().skip(1);
+    let name = args.next().unwrap_or_else(|| "Rustacean".to_string());
+    let gender = match args.next() {
+        Some(arg) => arg.parse().unwrap_or_else(|e| {
+            eprintln!("error: {}", e);
+            process::exit(2);
+        }),
+        None => Gender::Other,
+    };
+    println!("{}", greeting::greet(&name));
+    println!("{}", Greeter::from_env().welcome_back(&name, gender, 3));
 }
-
-fn greet(name: &str) {
-    println!("Hello, {}!", name);
-}
\ No newline at end of file
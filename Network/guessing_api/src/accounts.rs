@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use serde::{Deserialize, Serialize};
+
+/// Bumped whenever the layout of the accounts file changes.
+pub const FORMAT_VERSION: u32 = 1;
+
+const MIN_PASSWORD_LEN: usize = 8;
+const MAX_USERNAME_LEN: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Account {
+    username: String,
+    /// An Argon2id hash in PHC format, which carries its own salt.
+    password_hash: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AccountsFile {
+    version: u32,
+    accounts: Vec<Account>,
+}
+
+#[derive(Debug)]
+pub enum AccountError {
+    /// Usernames are 1-32 letters, digits, `-` or `_`.
+    InvalidUsername,
+    PasswordTooShort,
+    UsernameTaken,
+    /// Unknown username or wrong password; which one is not revealed.
+    BadCredentials,
+    Io(io::Error),
+}
+
+impl fmt::Display for AccountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountError::InvalidUsername => write!(
+                f,
+                "usernames are 1 to {} letters, digits, '-' or '_'",
+                MAX_USERNAME_LEN
+            ),
+            AccountError::PasswordTooShort => write!(
+                f,
+                "passwords must be at least {} characters",
+                MIN_PASSWORD_LEN
+            ),
+            AccountError::UsernameTaken => write!(f, "that username is taken"),
+            AccountError::BadCredentials => write!(f, "wrong username or password"),
+            AccountError::Io(e) => write!(f, "could not save accounts: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AccountError {}
+
+impl From<io::Error> for AccountError {
+    fn from(e: io::Error) -> Self {
+        AccountError::Io(e)
+    }
+}
+
+/// Player accounts, kept in a JSON file next to the score file.
+pub struct Accounts {
+    path: PathBuf,
+    accounts: Mutex<HashMap<String, Account>>,
+}
+
+impl Accounts {
+    /// Loads accounts from `path`, or starts with none if it does not exist.
+    /// Unlike the score file, a damaged accounts file is an error: setting
+    /// it aside would lock every player out.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Accounts> {
+        let path = path.into();
+        let accounts = match fs::read_to_string(&path) {
+            Ok(contents) => {
+                let file: AccountsFile = serde_json::from_str(&contents)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                if file.version != FORMAT_VERSION {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "accounts file has format version {}, expected {}",
+                            file.version, FORMAT_VERSION
+                        ),
+                    ));
+                }
+                file.accounts
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        let accounts = accounts
+            .into_iter()
+            .map(|account| (account.username.clone(), account))
+            .collect();
+        Ok(Accounts {
+            path,
+            accounts: Mutex::new(accounts),
+        })
+    }
+
+    /// Creates an account and saves the file.
+    pub fn register(&self, username: &str, password: &str) -> Result<(), AccountError> {
+        let valid = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+        if username.is_empty() || username.len() > MAX_USERNAME_LEN || !username.chars().all(valid)
+        {
+            return Err(AccountError::InvalidUsername);
+        }
+        if password.chars().count() < MIN_PASSWORD_LEN {
+            return Err(AccountError::PasswordTooShort);
+        }
+
+        let salt = SaltString::generate(&mut OsRng);
+        let password_hash = Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .expect("default Argon2 parameters are valid")
+            .to_string();
+
+        let mut accounts = self.accounts.lock().unwrap();
+        if accounts.contains_key(username) {
+            return Err(AccountError::UsernameTaken);
+        }
+        accounts.insert(
+            username.to_string(),
+            Account {
+                username: username.to_string(),
+                password_hash,
+            },
+        );
+        if let Err(e) = self.save(&accounts) {
+            accounts.remove(username);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// Checks a username and password. An unknown username costs as much
+    /// time as a wrong password, so timing does not tell which usernames
+    /// exist.
+    pub fn verify(&self, username: &str, password: &str) -> Result<(), AccountError> {
+        let known = self
+            .accounts
+            .lock()
+            .unwrap()
+            .get(username)
+            .map(|account| account.password_hash.clone());
+        let hash = known.as_deref().unwrap_or_else(|| dummy_hash());
+        let hash = PasswordHash::new(hash).map_err(|_| AccountError::BadCredentials)?;
+        let matches = Argon2::default()
+            .verify_password(password.as_bytes(), &hash)
+            .is_ok();
+        if known.is_some() && matches {
+            Ok(())
+        } else {
+            Err(AccountError::BadCredentials)
+        }
+    }
+
+    pub fn exists(&self, username: &str) -> bool {
+        self.accounts.lock().unwrap().contains_key(username)
+    }
+
+    /// Writes every account, replacing the old file in one step.
+    fn save(&self, accounts: &HashMap<String, Account>) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut list: Vec<Account> = accounts.values().cloned().collect();
+        list.sort_by(|a, b| a.username.cmp(&b.username));
+        let file = AccountsFile {
+            version: FORMAT_VERSION,
+            accounts: list,
+        };
+        let tmp = self.path.with_extension("json.tmp");
+        let mut out = fs::File::create(&tmp)?;
+        serde_json::to_writer_pretty(&mut out, &file)?;
+        out.write_all(b"\n")?;
+        out.sync_all()?;
+        fs::rename(&tmp, &self.path)
+    }
+}
+
+/// A hash with the same parameters as real ones, to check passwords against
+/// when the username is unknown.
+fn dummy_hash() -> &'static str {
+    static HASH: OnceLock<String> = OnceLock::new();
+    HASH.get_or_init(|| {
+        Argon2::default()
+            .hash_password(b"not anyone's password", &SaltString::generate(&mut OsRng))
+            .expect("default Argon2 parameters are valid")
+            .to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registers_and_verifies_across_reopens() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("accounts.json");
+        let accounts = Accounts::open(&path).unwrap();
+        accounts.register("ann", "correct horse").unwrap();
+        assert!(matches!(
+            accounts.register("ann", "another one"),
+            Err(AccountError::UsernameTaken)
+        ));
+
+        let accounts = Accounts::open(&path).unwrap();
+        assert!(accounts.exists("ann"));
+        accounts.verify("ann", "correct horse").unwrap();
+        assert!(matches!(
+            accounts.verify("ann", "wrong horse"),
+            Err(AccountError::BadCredentials)
+        ));
+        assert!(matches!(
+            accounts.verify("bob", "correct horse"),
+            Err(AccountError::BadCredentials)
+        ));
+        // Not even the password behind the stand-in hash gets in.
+        assert!(matches!(
+            accounts.verify("bob", "not anyone's password"),
+            Err(AccountError::BadCredentials)
+        ));
+
+        // Only a salted hash is stored.
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("correct horse"));
+        assert!(contents.contains("$argon2id$"));
+    }
+
+    #[test]
+    fn same_password_gets_different_hashes() {
+        let dir = tempfile::tempdir().unwrap();
+        let accounts = Accounts::open(dir.path().join("accounts.json")).unwrap();
+        accounts.register("ann", "password1").unwrap();
+        accounts.register("bob", "password1").unwrap();
+        let map = accounts.accounts.lock().unwrap();
+        assert_ne!(map["ann"].password_hash, map["bob"].password_hash);
+    }
+
+    #[test]
+    fn rejects_bad_usernames_and_short_passwords() {
+        let dir = tempfile::tempdir().unwrap();
+        let accounts = Accounts::open(dir.path().join("accounts.json")).unwrap();
+        for name in ["", "has space", "ünïcode", &"x".repeat(33)] {
+            assert!(matches!(
+                accounts.register(name, "long enough"),
+                Err(AccountError::InvalidUsername)
+            ));
+        }
+        assert!(matches!(
+            accounts.register("ann", "short"),
+            Err(AccountError::PasswordTooShort)
+        ));
+    }
+
+    #[test]
+    fn refuses_a_damaged_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("accounts.json");
+        fs::write(&path, "{ not json").unwrap();
+        assert!(Accounts::open(&path).is_err());
+    }
+}
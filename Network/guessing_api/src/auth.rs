@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::future::{ready, Ready};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use actix_web::dev::Payload;
+use actix_web::http::header;
+use actix_web::{web, FromRequest, HttpRequest};
+use guessing_game::scores;
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::ApiError;
+
+/// Who signs the tokens.
+pub const ISSUER: &str = "guessing_api";
+/// Who the tokens are for. A token minted for another service with the same
+/// key is refused.
+pub const AUDIENCE: &str = "guessing_game";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenKind {
+    /// Sent with every game request; short-lived.
+    Access,
+    /// Only good for getting a new pair from `POST /tokens/refresh`.
+    Refresh,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claims {
+    /// The username.
+    pub sub: String,
+    pub iss: String,
+    pub aud: String,
+    /// Issued at, in seconds since the Unix epoch.
+    pub iat: u64,
+    /// Expires at, in seconds since the Unix epoch.
+    pub exp: u64,
+    pub kind: TokenKind,
+    /// Unique to each token, so a refresh token can be used only once.
+    pub jti: String,
+}
+
+/// What login and refresh return.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub token_type: String,
+    /// Seconds until the access token expires.
+    pub expires_in: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    /// No `Authorization: Bearer` header.
+    Missing,
+    Expired,
+    WrongAudience,
+    /// A refresh token where an access token belongs, or the other way round.
+    WrongKind,
+    /// A refresh token that was already exchanged for a new pair.
+    Revoked,
+    /// Bad signature, malformed token, wrong issuer and the like.
+    Invalid(String),
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::Missing => write!(f, "a bearer token is required"),
+            AuthError::Expired => write!(f, "the token has expired"),
+            AuthError::WrongAudience => write!(f, "the token is not meant for this service"),
+            AuthError::WrongKind => write!(f, "wrong kind of token"),
+            AuthError::Revoked => write!(f, "the token has already been used"),
+            AuthError::Invalid(reason) => write!(f, "invalid token: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+/// Issues and checks HMAC-signed JWTs.
+pub struct Auth {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    access_ttl: Duration,
+    refresh_ttl: Duration,
+    /// The ids of refresh tokens already exchanged, with when they expire.
+    /// Only this process remembers them.
+    redeemed: Mutex<HashMap<String, u64>>,
+}
+
+impl Auth {
+    /// Access tokens last 15 minutes and refresh tokens a week.
+    pub fn new(secret: &[u8]) -> Auth {
+        Auth {
+            encoding: EncodingKey::from_secret(secret),
+            decoding: DecodingKey::from_secret(secret),
+            access_ttl: Duration::from_secs(15 * 60),
+            refresh_ttl: Duration::from_secs(7 * 24 * 60 * 60),
+            redeemed: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_ttls(mut self, access: Duration, refresh: Duration) -> Auth {
+        self.access_ttl = access;
+        self.refresh_ttl = refresh;
+        self
+    }
+
+    pub fn issue(&self, username: &str) -> TokenPair {
+        self.issue_at(username, scores::now())
+    }
+
+    /// Issues a pair as if it were `now`, in seconds since the Unix epoch.
+    pub fn issue_at(&self, username: &str, now: u64) -> TokenPair {
+        TokenPair {
+            access_token: self.sign(&self.claims(username, TokenKind::Access, now)),
+            refresh_token: self.sign(&self.claims(username, TokenKind::Refresh, now)),
+            token_type: "Bearer".to_string(),
+            expires_in: self.access_ttl.as_secs(),
+        }
+    }
+
+    /// The claims a token of `kind` issued at `now` would carry.
+    pub fn claims(&self, username: &str, kind: TokenKind, now: u64) -> Claims {
+        let ttl = match kind {
+            TokenKind::Access => self.access_ttl,
+            TokenKind::Refresh => self.refresh_ttl,
+        };
+        Claims {
+            sub: username.to_string(),
+            iss: ISSUER.to_string(),
+            aud: AUDIENCE.to_string(),
+            iat: now,
+            exp: now + ttl.as_secs(),
+            kind,
+            jti: Uuid::new_v4().to_string(),
+        }
+    }
+
+    pub fn sign(&self, claims: &Claims) -> String {
+        jsonwebtoken::encode(&Header::new(Algorithm::HS256), claims, &self.encoding)
+            .expect("claims always serialize")
+    }
+
+    /// Checks the signature, expiry, issuer, audience and kind of `token`.
+    pub fn verify(&self, token: &str, kind: TokenKind) -> Result<Claims, AuthError> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_audience(&[AUDIENCE]);
+        validation.set_issuer(&[ISSUER]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        validation.leeway = 0;
+
+        let claims = jsonwebtoken::decode::<Claims>(token, &self.decoding, &validation)
+            .map_err(|e| match e.kind() {
+                ErrorKind::ExpiredSignature => AuthError::Expired,
+                ErrorKind::InvalidAudience => AuthError::WrongAudience,
+                _ => AuthError::Invalid(e.to_string()),
+            })?
+            .claims;
+        if claims.kind != kind {
+            return Err(AuthError::WrongKind);
+        }
+        Ok(claims)
+    }
+
+    /// Checks a refresh token and uses it up: each one is good for a single
+    /// new pair, so a stolen one stops working once either side uses it.
+    pub fn redeem(&self, token: &str) -> Result<Claims, AuthError> {
+        let claims = self.verify(token, TokenKind::Refresh)?;
+        let mut redeemed = self.redeemed.lock().unwrap();
+        // Expired tokens are refused anyway, so they need no remembering.
+        let now = scores::now();
+        redeemed.retain(|_, exp| *exp >= now);
+        if redeemed.insert(claims.jti.clone(), claims.exp).is_some() {
+            return Err(AuthError::Revoked);
+        }
+        Ok(claims)
+    }
+}
+
+/// The player a request is made by, taken from a valid access token.
+/// Handlers that take a `Player` answer 401 without one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Player(pub String);
+
+impl FromRequest for Player {
+    type Error = ApiError;
+    type Future = Ready<Result<Player, ApiError>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let auth = req
+            .app_data::<web::Data<Auth>>()
+            .expect("the app must provide web::Data<Auth>");
+        let token = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        let player = match token {
+            Some(token) => auth
+                .verify(token.trim(), TokenKind::Access)
+                .map(|claims| Player(claims.sub))
+                .map_err(ApiError::Unauthorized),
+            None => Err(ApiError::Unauthorized(AuthError::Missing)),
+        };
+        ready(player)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auth() -> Auth {
+        Auth::new(b"test secret")
+    }
+
+    #[test]
+    fn accepts_fresh_tokens_of_the_right_kind() {
+        let auth = auth();
+        let pair = auth.issue("ann");
+        let claims = auth.verify(&pair.access_token, TokenKind::Access).unwrap();
+        assert_eq!(claims.sub, "ann");
+        assert_eq!(claims.exp - claims.iat, 15 * 60);
+        assert_eq!(
+            auth.verify(&pair.refresh_token, TokenKind::Refresh)
+                .unwrap()
+                .sub,
+            "ann"
+        );
+
+        assert_eq!(
+            auth.verify(&pair.refresh_token, TokenKind::Access),
+            Err(AuthError::WrongKind)
+        );
+        assert_eq!(
+            auth.verify(&pair.access_token, TokenKind::Refresh),
+            Err(AuthError::WrongKind)
+        );
+    }
+
+    #[test]
+    fn refresh_tokens_are_good_once() {
+        let auth = auth();
+        let pair = auth.issue("ann");
+        assert_eq!(auth.redeem(&pair.refresh_token).unwrap().sub, "ann");
+        assert_eq!(auth.redeem(&pair.refresh_token), Err(AuthError::Revoked));
+        assert_eq!(auth.redeem(&pair.access_token), Err(AuthError::WrongKind));
+
+        // Every token is told apart, even two issued in the same second.
+        let again = auth.issue("ann");
+        assert_ne!(pair.refresh_token, again.refresh_token);
+        assert!(auth.redeem(&again.refresh_token).is_ok());
+    }
+
+    #[test]
+    fn refuses_expired_tokens() {
+        let auth = auth();
+        let an_hour_ago = scores::now() - 3600;
+        let pair = auth.issue_at("ann", an_hour_ago);
+        assert_eq!(
+            auth.verify(&pair.access_token, TokenKind::Access),
+            Err(AuthError::Expired)
+        );
+        // The refresh token lives longer.
+        assert!(auth.verify(&pair.refresh_token, TokenKind::Refresh).is_ok());
+    }
+
+    #[test]
+    fn refuses_tampered_tokens() {
+        let auth = auth();
+        let token = auth.issue("ann").access_token;
+
+        // Swap the payload for one naming someone else, keeping the
+        // signature.
+        let mut parts: Vec<&str> = token.split('.').collect();
+        let forged = auth.sign(&auth.claims("bob", TokenKind::Access, scores::now()));
+        let forged_payload = forged.split('.').nth(1).unwrap();
+        parts[1] = forged_payload;
+        assert!(matches!(
+            auth.verify(&parts.join("."), TokenKind::Access),
+            Err(AuthError::Invalid(_))
+        ));
+
+        let other_key = Auth::new(b"another secret").issue("ann").access_token;
+        assert!(matches!(
+            auth.verify(&other_key, TokenKind::Access),
+            Err(AuthError::Invalid(_))
+        ));
+        assert!(matches!(
+            auth.verify("not.a.token", TokenKind::Access),
+            Err(AuthError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn refuses_tokens_for_another_audience() {
+        let auth = auth();
+        let mut claims = auth.claims("ann", TokenKind::Access, scores::now());
+        claims.aud = "some_other_service".to_string();
+        assert_eq!(
+            auth.verify(&auth.sign(&claims), TokenKind::Access),
+            Err(AuthError::WrongAudience)
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use std::fmt;
+
+use actix_web::http::{header, StatusCode};
+use actix_web::{HttpResponse, ResponseError};
+use serde_json::json;
+
+use crate::accounts::AccountError;
+use crate::auth::AuthError;
+use crate::store::StoreError;
+
+/// Why a request failed. Rendered as `{"error": "..."}` with a matching
+/// status code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+    /// The body was not the JSON we expected.
+    BadRequest(String),
+    /// A custom difficulty no game can be played at, or an unknown level.
+    BadDifficulty(String),
+    /// No valid access token came with the request.
+    Unauthorized(AuthError),
+    /// Login with an unknown username or the wrong password.
+    BadCredentials,
+    UsernameTaken,
+    /// No game with that id, or it belongs to someone else.
+    NotFound,
+    /// The game is already won or lost.
+    GameOver,
+    /// The guess was a number, but not one the game allows.
+    InvalidGuess(String),
+    Store(StoreError),
+    /// Something on the server went wrong, e.g. the accounts file could not
+    /// be written.
+    Internal(String),
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::BadRequest(reason) => write!(f, "bad request: {}", reason),
+            ApiError::BadDifficulty(reason) => write!(f, "bad difficulty: {}", reason),
+            ApiError::Unauthorized(e) => e.fmt(f),
+            ApiError::BadCredentials => write!(f, "wrong username or password"),
+            ApiError::UsernameTaken => write!(f, "that username is taken"),
+            ApiError::NotFound => write!(f, "no such game"),
+            ApiError::GameOver => write!(f, "the game is over"),
+            ApiError::InvalidGuess(reason) => f.write_str(reason),
+            ApiError::Store(e) => e.fmt(f),
+            ApiError::Internal(reason) => f.write_str(reason),
+        }
+    }
+}
+
+impl From<StoreError> for ApiError {
+    fn from(e: StoreError) -> Self {
+        ApiError::Store(e)
+    }
+}
+
+impl From<AccountError> for ApiError {
+    fn from(e: AccountError) -> Self {
+        match e {
+            AccountError::InvalidUsername | AccountError::PasswordTooShort => {
+                ApiError::BadRequest(e.to_string())
+            }
+            AccountError::UsernameTaken => ApiError::UsernameTaken,
+            AccountError::BadCredentials => ApiError::BadCredentials,
+            AccountError::Io(_) => ApiError::Internal(e.to_string()),
+        }
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) | ApiError::BadDifficulty(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) | ApiError::BadCredentials => StatusCode::UNAUTHORIZED,
+            ApiError::UsernameTaken => StatusCode::CONFLICT,
+            ApiError::NotFound => StatusCode::NOT_FOUND,
+            ApiError::GameOver => StatusCode::CONFLICT,
+            ApiError::InvalidGuess(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::Store(_) | ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut response = HttpResponse::build(self.status_code());
+        if let ApiError::Unauthorized(_) = self {
+            response.insert_header((header::WWW_AUTHENTICATE, "Bearer"));
+        }
+        response.json(json!({ "error": self.to_string() }))
+    }
+}
@@ -0,0 +1,29 @@
+//! The guessing game as a JSON API, built on the actix-web example in
+//! `Network.md`.
+//!
+//! ```text
+//! POST /accounts              {"username": "ann", "password": "..."}   -> 201
+//! POST /tokens                {"username": "ann", "password": "..."}   -> access and refresh tokens
+//! POST /tokens/refresh        {"refresh_token": "..."}                -> a new pair, once per refresh token
+//! POST /games                 {"difficulty": "easy"}   -> 201, the new game
+//! POST /games/{id}/guesses    {"guess": 50}            -> too_low / too_high / correct
+//! GET  /games/{id}                                     -> the game's state
+//! GET  /metrics                                        -> Prometheus metrics
+//! ```
+//!
+//! The `/games` endpoints need an `Authorization: Bearer <access token>`
+//! header, and a player only sees their own games.
+
+pub mod accounts;
+pub mod auth;
+pub mod error;
+pub mod metrics;
+pub mod routes;
+pub mod store;
+
+pub use accounts::{AccountError, Accounts};
+pub use auth::{Auth, AuthError, Player, TokenKind, TokenPair};
+pub use error::ApiError;
+pub use metrics::{Metrics, RequestId};
+pub use routes::routes;
+pub use store::{GameId, GameRecord, GameStore, MemoryStore, StoreError};
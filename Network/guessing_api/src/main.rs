@@ -0,0 +1,70 @@
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use actix_web::{web, App, HttpServer};
+use guessing_api::{routes, Accounts, Auth, GameStore, MemoryStore, Metrics};
+use guessing_game::scores;
+use rand::RngCore;
+use tracing_subscriber::EnvFilter;
+
+/// Where the signing key comes from. Without it a random key is used, and
+/// every token is invalidated when the server restarts.
+const SECRET_VAR: &str = "GUESSING_API_SECRET";
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    // One JSON object per request on stdout; `RUST_LOG` picks the level.
+    tracing_subscriber::fmt()
+        .json()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .init();
+
+    let addr = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:8000".to_string());
+    let accounts_file = env::args()
+        .nth(2)
+        .map(PathBuf::from)
+        .or_else(|| scores::default_path().map(|path| path.with_file_name("accounts.json")))
+        .expect("no data directory; pass the accounts file after the address");
+
+    let secret = match env::var(SECRET_VAR) {
+        Ok(secret) if !secret.is_empty() => secret.into_bytes(),
+        _ => {
+            eprintln!(
+                "warning: {} is not set, tokens will not survive a restart",
+                SECRET_VAR
+            );
+            let mut secret = vec![0; 32];
+            rand::thread_rng().fill_bytes(&mut secret);
+            secret
+        }
+    };
+
+    let store: Arc<dyn GameStore> = Arc::new(MemoryStore::new());
+    let store = web::Data::from(store);
+    let accounts = web::Data::new(Accounts::open(&accounts_file)?);
+    let auth = web::Data::new(Auth::new(&secret));
+    let metrics = Metrics::new();
+
+    println!(
+        "guessing API listening on http://{}, accounts in {}",
+        addr,
+        accounts_file.display()
+    );
+    HttpServer::new(move || {
+        App::new()
+            .wrap(metrics.middleware())
+            .app_data(web::Data::new(metrics.clone()))
+            .app_data(store.clone())
+            .app_data(accounts.clone())
+            .app_data(auth.clone())
+            .configure(routes)
+    })
+    .bind(addr)?
+    .run()
+    .await
+}
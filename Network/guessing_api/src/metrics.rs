@@ -0,0 +1,252 @@
+//! Request logging and Prometheus metrics, grown out of the `Logger`
+//! example in `Network.md`.
+//!
+//! Wrapping an app in [`Metrics::middleware`] gives every request an id,
+//! logs one structured `tracing` event per request and times it per route.
+//! The handlers count games started and won, and `GET /metrics` serves it
+//! all in the Prometheus text format.
+
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::time::Instant;
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::http::Method;
+use actix_web::{get, web, HttpMessage, HttpResponse};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, Opts, Registry, TextEncoder,
+};
+use uuid::Uuid;
+
+/// Carries the request id in and out. A sensible id sent by a proxy is
+/// kept, so one request can be followed through several services.
+pub const REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// The route label for requests that matched no route, so that scanners
+/// probing random paths cannot create a new time series each.
+const UNMATCHED: &str = "unmatched";
+
+/// The method label. Any method outside the standard set becomes `other`,
+/// for the same reason.
+fn method_label(method: &Method) -> &'static str {
+    match *method {
+        Method::GET => "GET",
+        Method::HEAD => "HEAD",
+        Method::POST => "POST",
+        Method::PUT => "PUT",
+        Method::DELETE => "DELETE",
+        Method::CONNECT => "CONNECT",
+        Method::OPTIONS => "OPTIONS",
+        Method::TRACE => "TRACE",
+        Method::PATCH => "PATCH",
+        _ => "other",
+    }
+}
+
+/// The id of the request being handled. Handlers can take it from the
+/// request extensions to mention it in their own logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    fn for_request(req: &ServiceRequest) -> RequestId {
+        let sent = req
+            .headers()
+            .get(&REQUEST_ID)
+            .and_then(|value| value.to_str().ok())
+            .filter(|id| {
+                (1..=64).contains(&id.len())
+                    && id
+                        .bytes()
+                        .all(|b| b.is_ascii_alphanumeric() || b"-_.".contains(&b))
+            });
+        match sent {
+            Some(id) => RequestId(id.to_string()),
+            None => RequestId(Uuid::new_v4().to_string()),
+        }
+    }
+}
+
+/// The app's metrics. Cloning is cheap and every clone updates the same
+/// series.
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    latency: HistogramVec,
+    games_started: IntCounter,
+    games_won: IntCounter,
+}
+
+impl Metrics {
+    pub fn new() -> Metrics {
+        let registry = Registry::new();
+        let requests = IntCounterVec::new(
+            Opts::new("guessing_http_requests_total", "HTTP requests handled"),
+            &["method", "route", "status"],
+        )
+        .unwrap();
+        let latency = HistogramVec::new(
+            HistogramOpts::new(
+                "guessing_http_request_duration_seconds",
+                "Time taken to answer HTTP requests",
+            )
+            // Password hashing makes account requests much slower than
+            // guesses, so the buckets reach further than the defaults.
+            .buckets(vec![
+                0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5,
+            ]),
+            &["method", "route"],
+        )
+        .unwrap();
+        let games_started =
+            IntCounter::new("guessing_games_started_total", "Games created").unwrap();
+        let games_won =
+            IntCounter::new("guessing_games_won_total", "Games that ended in a win").unwrap();
+
+        registry.register(Box::new(requests.clone())).unwrap();
+        registry.register(Box::new(latency.clone())).unwrap();
+        registry.register(Box::new(games_started.clone())).unwrap();
+        registry.register(Box::new(games_won.clone())).unwrap();
+        Metrics {
+            registry,
+            requests,
+            latency,
+            games_started,
+            games_won,
+        }
+    }
+
+    pub fn game_started(&self) {
+        self.games_started.inc();
+    }
+
+    pub fn game_won(&self) {
+        self.games_won.inc();
+    }
+
+    /// Everything collected so far, in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let mut out = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut out)
+            .expect("metrics are always encodable");
+        String::from_utf8(out).expect("the text format is UTF-8")
+    }
+
+    /// The middleware that logs and times every request into these
+    /// metrics.
+    pub fn middleware(&self) -> RequestMetrics {
+        RequestMetrics {
+            metrics: self.clone(),
+        }
+    }
+
+    fn observe(&self, method: &str, route: &str, status: u16, seconds: f64) {
+        self.requests
+            .with_label_values(&[method, route, &status.to_string()])
+            .inc();
+        self.latency
+            .with_label_values(&[method, route])
+            .observe(seconds);
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Metrics::new()
+    }
+}
+
+/// Serves [`Metrics::render`]. Needs a `web::Data<Metrics>`.
+#[get("/metrics")]
+pub async fn metrics_endpoint(metrics: web::Data<Metrics>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(TextEncoder::new().format_type())
+        .body(metrics.render())
+}
+
+/// See [`Metrics::middleware`].
+pub struct RequestMetrics {
+    metrics: Metrics,
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestMetrics
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = RequestMetricsService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestMetricsService {
+            service: Rc::new(service),
+            metrics: self.metrics.clone(),
+        }))
+    }
+}
+
+pub struct RequestMetricsService<S> {
+    service: Rc<S>,
+    metrics: Metrics,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestMetricsService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let started = Instant::now();
+        let id = RequestId::for_request(&req);
+        req.extensions_mut().insert(id.clone());
+        let method = req.method().clone();
+        let path = req.path().to_string();
+        let route = req.match_pattern().unwrap_or_else(|| UNMATCHED.to_string());
+
+        let service = Rc::clone(&self.service);
+        let metrics = self.metrics.clone();
+        Box::pin(async move {
+            let result = service.call(req).await;
+            let elapsed = started.elapsed();
+            let status = match &result {
+                Ok(res) => res.status(),
+                Err(e) => e.as_response_error().status_code(),
+            };
+            metrics.observe(
+                method_label(&method),
+                &route,
+                status.as_u16(),
+                elapsed.as_secs_f64(),
+            );
+            tracing::info!(
+                request_id = %id.0,
+                method = %method,
+                path = %path,
+                route = %route,
+                status = status.as_u16(),
+                latency_ms = elapsed.as_secs_f64() * 1000.0,
+                "request"
+            );
+
+            let mut res = result?;
+            if let Ok(value) = HeaderValue::from_str(&id.0) {
+                res.headers_mut().insert(REQUEST_ID, value);
+            }
+            Ok(res)
+        })
+    }
+}
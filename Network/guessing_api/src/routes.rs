@@ -0,0 +1,286 @@
+use actix_web::http::header;
+use actix_web::{get, post, web, HttpResponse};
+use guessing_game::{Difficulty, Hint, Outcome, Round, RoundError};
+use serde::{Deserialize, Serialize};
+
+use crate::accounts::Accounts;
+use crate::auth::{Auth, Player, TokenPair};
+use crate::error::ApiError;
+use crate::metrics::{metrics_endpoint, Metrics};
+use crate::store::{GameId, GameRecord, GameStore};
+
+/// The body of `POST /accounts` and `POST /tokens`.
+#[derive(Debug, Deserialize)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// The body of `POST /tokens/refresh`.
+#[derive(Debug, Deserialize)]
+pub struct Refresh {
+    pub refresh_token: String,
+}
+
+/// The body of `POST /games`. It may be left out for a medium game.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewGame {
+    #[serde(default)]
+    pub difficulty: Difficulty,
+}
+
+/// The body of `POST /games/{id}/guesses`.
+#[derive(Debug, Deserialize)]
+pub struct NewGuess {
+    pub guess: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Playing,
+    Won,
+    Lost,
+}
+
+impl Status {
+    fn of(round: &Round) -> Status {
+        match round.outcome() {
+            None => Status::Playing,
+            Some(Outcome::Won { .. }) => Status::Won,
+            Some(Outcome::Lost { .. } | Outcome::Quit { .. }) => Status::Lost,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuessResult {
+    TooLow,
+    TooHigh,
+    Correct,
+}
+
+impl From<Hint> for GuessResult {
+    fn from(hint: Hint) -> Self {
+        match hint {
+            Hint::TooSmall => GuessResult::TooLow,
+            Hint::TooBig => GuessResult::TooHigh,
+            Hint::Correct => GuessResult::Correct,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PastGuess {
+    pub guess: u32,
+    pub result: GuessResult,
+}
+
+/// A game as `GET /games/{id}` shows it. The secret is only included once
+/// the game is over.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameView {
+    pub id: GameId,
+    pub player: String,
+    pub difficulty: Difficulty,
+    pub low: u32,
+    pub high: u32,
+    pub max_attempts: u32,
+    pub attempts: u32,
+    pub attempts_left: u32,
+    pub status: Status,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<u32>,
+    pub guesses: Vec<PastGuess>,
+}
+
+impl GameView {
+    pub fn new(id: GameId, game: &GameRecord) -> GameView {
+        let round = &game.round;
+        let range = round.range();
+        GameView {
+            id,
+            player: game.player.clone(),
+            difficulty: game.difficulty.clone(),
+            low: *range.start(),
+            high: *range.end(),
+            max_attempts: round.max_attempts(),
+            attempts: round.attempts(),
+            attempts_left: round.attempts_left(),
+            status: Status::of(round),
+            secret: round.outcome().map(|_| round.secret()),
+            guesses: game
+                .guesses
+                .iter()
+                .map(|&(guess, hint)| PastGuess {
+                    guess,
+                    result: hint.into(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The answer to one guess.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuessReply {
+    pub result: GuessResult,
+    pub attempts: u32,
+    pub attempts_left: u32,
+    pub status: Status,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<u32>,
+}
+
+/// Registers the endpoints. The app must also provide a
+/// `web::Data<dyn GameStore>`, a `web::Data<Accounts>`, a `web::Data<Auth>`
+/// and a `web::Data<Metrics>`.
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.app_data(
+        web::JsonConfig::default()
+            .error_handler(|err, _| ApiError::BadRequest(err.to_string()).into()),
+    )
+    .app_data(web::PathConfig::default().error_handler(|_, _| ApiError::NotFound.into()))
+    .service(register)
+    .service(login)
+    .service(refresh)
+    .service(create_game)
+    .service(make_guess)
+    .service(get_game)
+    .service(metrics_endpoint);
+}
+
+#[post("/accounts")]
+async fn register(
+    accounts: web::Data<Accounts>,
+    body: web::Json<Credentials>,
+) -> Result<HttpResponse, ApiError> {
+    // Hashing is slow on purpose, so keep it off the async workers.
+    let Credentials { username, password } = body.into_inner();
+    let name = username.clone();
+    web::block(move || accounts.register(&name, &password))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))??;
+    Ok(HttpResponse::Created().json(serde_json::json!({ "username": username })))
+}
+
+#[post("/tokens")]
+async fn login(
+    accounts: web::Data<Accounts>,
+    auth: web::Data<Auth>,
+    body: web::Json<Credentials>,
+) -> Result<web::Json<TokenPair>, ApiError> {
+    let Credentials { username, password } = body.into_inner();
+    let name = username.clone();
+    web::block(move || accounts.verify(&name, &password))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))??;
+    Ok(web::Json(auth.issue(&username)))
+}
+
+#[post("/tokens/refresh")]
+async fn refresh(
+    accounts: web::Data<Accounts>,
+    auth: web::Data<Auth>,
+    body: web::Json<Refresh>,
+) -> Result<web::Json<TokenPair>, ApiError> {
+    let claims = auth
+        .redeem(&body.refresh_token)
+        .map_err(ApiError::Unauthorized)?;
+    if !accounts.exists(&claims.sub) {
+        return Err(ApiError::BadCredentials);
+    }
+    Ok(web::Json(auth.issue(&claims.sub)))
+}
+
+#[post("/games")]
+async fn create_game(
+    store: web::Data<dyn GameStore>,
+    metrics: web::Data<Metrics>,
+    player: Player,
+    body: web::Bytes,
+) -> Result<HttpResponse, ApiError> {
+    let options = parse_new_game(&body)?;
+    let round = Round::new(&options.difficulty, &mut rand::thread_rng());
+    let game = GameRecord::new(&player.0, options.difficulty, round);
+    let view_of = game.clone();
+    let id = store.insert(game)?;
+    metrics.game_started();
+
+    Ok(HttpResponse::Created()
+        .insert_header((header::LOCATION, format!("/games/{}", id)))
+        .json(GameView::new(id, &view_of)))
+}
+
+/// An empty body is a medium game. The difficulty is read on its own first,
+/// so one that goes through `Difficulty::custom` and fails, say an empty
+/// range or no attempts, is reported as a bad difficulty and never reaches
+/// `Round::new`.
+fn parse_new_game(body: &[u8]) -> Result<NewGame, ApiError> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(NewGame::default());
+    }
+    let bad_request = |e: serde_json::Error| ApiError::BadRequest(e.to_string());
+    let value: serde_json::Value = serde_json::from_slice(body).map_err(bad_request)?;
+    if let Some(difficulty) = value.get("difficulty") {
+        Difficulty::deserialize(difficulty).map_err(|e| ApiError::BadDifficulty(e.to_string()))?;
+    }
+    serde_json::from_value(value).map_err(bad_request)
+}
+
+#[post("/games/{id}/guesses")]
+async fn make_guess(
+    store: web::Data<dyn GameStore>,
+    metrics: web::Data<Metrics>,
+    player: Player,
+    id: web::Path<GameId>,
+    body: web::Json<NewGuess>,
+) -> Result<web::Json<GuessReply>, ApiError> {
+    let guess = body.guess;
+    let mut reply = Err(ApiError::NotFound);
+    store.update(*id, &mut |game| {
+        if game.player != player.0 {
+            return;
+        }
+        reply = match game.round.guess(guess) {
+            Ok(hint) => {
+                game.guesses.push((guess, hint));
+                let round = &game.round;
+                Ok(GuessReply {
+                    result: hint.into(),
+                    attempts: round.attempts(),
+                    attempts_left: round.attempts_left(),
+                    status: Status::of(round),
+                    secret: round.outcome().map(|_| round.secret()),
+                })
+            }
+            Err(RoundError::GameOver(_)) => Err(ApiError::GameOver),
+            Err(e) => Err(ApiError::InvalidGuess(e.to_string())),
+        };
+    })?;
+    if matches!(
+        reply,
+        Ok(GuessReply {
+            status: Status::Won,
+            ..
+        })
+    ) {
+        metrics.game_won();
+    }
+    reply.map(web::Json)
+}
+
+#[get("/games/{id}")]
+async fn get_game(
+    store: web::Data<dyn GameStore>,
+    player: Player,
+    id: web::Path<GameId>,
+) -> Result<web::Json<GameView>, ApiError> {
+    let game = store
+        .get(*id)?
+        .filter(|game| game.player == player.0)
+        .ok_or(ApiError::NotFound)?;
+    Ok(web::Json(GameView::new(*id, &game)))
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+use guessing_game::{Difficulty, Hint, Round};
+use uuid::Uuid;
+
+pub type GameId = Uuid;
+
+/// Everything kept about one game.
+#[derive(Debug, Clone)]
+pub struct GameRecord {
+    /// The account the game belongs to.
+    pub player: String,
+    pub difficulty: Difficulty,
+    pub round: Round,
+    /// Each accepted guess with its hint, oldest first.
+    pub guesses: Vec<(u32, Hint)>,
+}
+
+impl GameRecord {
+    pub fn new(player: &str, difficulty: Difficulty, round: Round) -> GameRecord {
+        GameRecord {
+            player: player.to_string(),
+            difficulty,
+            round,
+            guesses: Vec::new(),
+        }
+    }
+}
+
+/// A backend failed, e.g. a database connection dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreError(pub String);
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "game store failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+/// Where games live between requests.
+pub trait GameStore: Send + Sync {
+    /// Stores a new game and returns its id.
+    fn insert(&self, game: GameRecord) -> Result<GameId, StoreError>;
+
+    fn get(&self, id: GameId) -> Result<Option<GameRecord>, StoreError>;
+
+    /// Changes a game in place, so that two requests for the same game
+    /// cannot overwrite each other. Returns `false` if there is no such
+    /// game.
+    fn update(
+        &self,
+        id: GameId,
+        change: &mut dyn FnMut(&mut GameRecord),
+    ) -> Result<bool, StoreError>;
+}
+
+/// Keeps games in a map for as long as the process runs.
+#[derive(Default)]
+pub struct MemoryStore {
+    games: Mutex<HashMap<GameId, GameRecord>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+}
+
+impl GameStore for MemoryStore {
+    fn insert(&self, game: GameRecord) -> Result<GameId, StoreError> {
+        let id = Uuid::new_v4();
+        self.games.lock().unwrap().insert(id, game);
+        Ok(id)
+    }
+
+    fn get(&self, id: GameId) -> Result<Option<GameRecord>, StoreError> {
+        Ok(self.games.lock().unwrap().get(&id).cloned())
+    }
+
+    fn update(
+        &self,
+        id: GameId,
+        change: &mut dyn FnMut(&mut GameRecord),
+    ) -> Result<bool, StoreError> {
+        match self.games.lock().unwrap().get_mut(&id) {
+            Some(game) => {
+                change(game);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(secret: u32) -> GameRecord {
+        GameRecord::new(
+            "ann",
+            Difficulty::Easy,
+            Round::with_secret(&Difficulty::Easy, secret),
+        )
+    }
+
+    #[test]
+    fn inserts_and_updates_games() {
+        let store = MemoryStore::new();
+        let a = store.insert(record(3)).unwrap();
+        let b = store.insert(record(8)).unwrap();
+        assert_ne!(a, b);
+
+        let found = store
+            .update(a, &mut |game| {
+                let hint = game.round.guess(5).unwrap();
+                game.guesses.push((5, hint));
+            })
+            .unwrap();
+        assert!(found);
+
+        let a = store.get(a).unwrap().unwrap();
+        assert_eq!(a.guesses, vec![(5, Hint::TooBig)]);
+        assert_eq!(store.get(b).unwrap().unwrap().round.attempts(), 0);
+    }
+
+    #[test]
+    fn unknown_ids() {
+        let store = MemoryStore::new();
+        let id = Uuid::new_v4();
+        assert!(store.get(id).unwrap().is_none());
+        assert!(!store.update(id, &mut |_| panic!("no such game")).unwrap());
+    }
+}
@@ -0,0 +1,239 @@
+mod common;
+
+use actix_web::http::{header, StatusCode};
+use actix_web::test;
+use common::Fixture;
+use guessing_api::routes::{GameView, GuessReply, GuessResult, PastGuess, Status};
+use guessing_api::{GameId, GameRecord, GameStore};
+use guessing_game::{Difficulty, Round};
+use serde_json::{json, Value};
+
+impl Fixture {
+    /// An easy game for "ann" with a known secret.
+    fn game_with_secret(&self, secret: u32) -> GameId {
+        let round = Round::with_secret(&Difficulty::Easy, secret);
+        self.store
+            .insert(GameRecord::new("ann", Difficulty::Easy, round))
+            .unwrap()
+    }
+
+    fn get(&self, uri: &str) -> test::TestRequest {
+        test::TestRequest::get()
+            .uri(uri)
+            .insert_header(self.bearer("ann"))
+    }
+
+    fn post(&self, uri: &str) -> test::TestRequest {
+        test::TestRequest::post()
+            .uri(uri)
+            .insert_header(self.bearer("ann"))
+    }
+
+    fn guess(&self, id: GameId, guess: u32) -> actix_http::Request {
+        self.post(&format!("/games/{}/guesses", id))
+            .set_json(json!({ "guess": guess }))
+            .to_request()
+    }
+}
+
+#[actix_web::test]
+async fn creates_games() {
+    let fx = Fixture::new();
+    let app = fx.app().await;
+
+    let req = fx.post("/games").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let location = resp
+        .headers()
+        .get(header::LOCATION)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    let created: GameView = test::read_body_json(resp).await;
+    assert_eq!(location, format!("/games/{}", created.id));
+    assert_eq!(
+        (created.low, created.high, created.max_attempts),
+        (1, 100, 10)
+    );
+    assert_eq!(created.player, "ann");
+    assert_eq!(created.status, Status::Playing);
+    assert_eq!(created.secret, None);
+
+    let req = fx.get(&location).to_request();
+    let fetched: GameView = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(fetched, created);
+
+    let req = fx
+        .post("/games")
+        .set_json(json!({ "difficulty": "hard" }))
+        .to_request();
+    let hard: GameView = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(hard.difficulty, Difficulty::Hard);
+    assert_eq!(hard.high, 10_000);
+    assert_ne!(hard.id, created.id);
+}
+
+#[actix_web::test]
+async fn plays_a_game_to_the_end() {
+    let fx = Fixture::new();
+    let id = fx.game_with_secret(7);
+    let app = fx.app().await;
+
+    let low: GuessReply = test::call_and_read_body_json(&app, fx.guess(id, 3)).await;
+    assert_eq!(
+        low,
+        GuessReply {
+            result: GuessResult::TooLow,
+            attempts: 1,
+            attempts_left: 5,
+            status: Status::Playing,
+            secret: None,
+        }
+    );
+    let high: GuessReply = test::call_and_read_body_json(&app, fx.guess(id, 9)).await;
+    assert_eq!(high.result, GuessResult::TooHigh);
+    let win: GuessReply = test::call_and_read_body_json(&app, fx.guess(id, 7)).await;
+    assert_eq!(
+        win,
+        GuessReply {
+            result: GuessResult::Correct,
+            attempts: 3,
+            attempts_left: 3,
+            status: Status::Won,
+            secret: Some(7),
+        }
+    );
+
+    let req = fx.get(&format!("/games/{}", id)).to_request();
+    let state: GameView = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(state.status, Status::Won);
+    assert_eq!(state.secret, Some(7));
+    assert_eq!(
+        state.guesses,
+        vec![
+            PastGuess {
+                guess: 3,
+                result: GuessResult::TooLow
+            },
+            PastGuess {
+                guess: 9,
+                result: GuessResult::TooHigh
+            },
+            PastGuess {
+                guess: 7,
+                result: GuessResult::Correct
+            },
+        ]
+    );
+
+    let resp = test::call_service(&app, fx.guess(id, 7)).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+}
+
+#[actix_web::test]
+async fn losing_reveals_the_secret() {
+    let fx = Fixture::new();
+    let id = fx.game_with_secret(10);
+    let app = fx.app().await;
+
+    let mut last = None;
+    for n in 1..=6 {
+        last = Some(test::call_and_read_body_json::<_, _, GuessReply>(&app, fx.guess(id, n)).await);
+    }
+    let last = last.unwrap();
+    assert_eq!(last.status, Status::Lost);
+    assert_eq!(last.attempts_left, 0);
+    assert_eq!(last.secret, Some(10));
+}
+
+#[actix_web::test]
+async fn reports_errors_as_json() {
+    let fx = Fixture::new();
+    let id = fx.game_with_secret(5);
+    let app = fx.app().await;
+
+    let cases = [
+        (fx.guess(id, 11), StatusCode::UNPROCESSABLE_ENTITY),
+        (fx.guess(GameId::new_v4(), 1), StatusCode::NOT_FOUND),
+        (
+            fx.get("/games/not-an-id").to_request(),
+            StatusCode::NOT_FOUND,
+        ),
+        (
+            fx.post(&format!("/games/{}/guesses", id))
+                .set_json(json!({ "guess": "five" }))
+                .to_request(),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            fx.post("/games")
+                .set_json(json!({ "difficulty": "impossible" }))
+                .to_request(),
+            StatusCode::BAD_REQUEST,
+        ),
+    ];
+    for (req, status) in cases {
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), status);
+        let body: Value = test::read_body_json(resp).await;
+        assert!(body["error"].is_string(), "{}", body);
+    }
+
+    // The refused guess did not cost an attempt.
+    let req = fx.get(&format!("/games/{}", id)).to_request();
+    let state: GameView = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(state.attempts, 0);
+}
+
+#[actix_web::test]
+async fn refuses_custom_difficulties_with_no_game_in_them() {
+    let fx = Fixture::new();
+    let app = fx.app().await;
+
+    for difficulty in [
+        json!({ "custom": { "range": { "start": 10, "end": 1 }, "max_attempts": 5 } }),
+        json!({ "custom": { "range": { "start": 1, "end": 10 }, "max_attempts": 0 } }),
+    ] {
+        let req = fx
+            .post("/games")
+            .set_json(json!({ "difficulty": difficulty }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: Value = test::read_body_json(resp).await;
+        let error = body["error"].as_str().unwrap();
+        assert!(error.starts_with("bad difficulty:"), "{}", error);
+    }
+}
+
+#[actix_web::test]
+async fn players_only_see_their_own_games() {
+    let fx = Fixture::new();
+    let id = fx.game_with_secret(5);
+    let app = fx.app().await;
+
+    let uri = format!("/games/{}", id);
+    let req = test::TestRequest::get()
+        .uri(&uri)
+        .insert_header(fx.bearer("bob"))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::NOT_FOUND
+    );
+    let req = test::TestRequest::post()
+        .uri(&format!("{}/guesses", uri))
+        .insert_header(fx.bearer("bob"))
+        .set_json(json!({ "guess": 5 }))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::NOT_FOUND
+    );
+
+    let req = fx.get(&uri).to_request();
+    let state: GameView = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(state.attempts, 0);
+}
@@ -0,0 +1,207 @@
+mod common;
+
+use actix_web::http::{header, StatusCode};
+use actix_web::test;
+use common::Fixture;
+use guessing_api::auth::{Claims, TokenKind};
+use guessing_api::routes::GameView;
+use guessing_api::{Auth, TokenPair};
+use guessing_game::scores;
+use serde_json::{json, Value};
+
+fn credentials(username: &str, password: &str) -> Value {
+    json!({ "username": username, "password": password })
+}
+
+fn new_game(token: &str) -> actix_http::Request {
+    test::TestRequest::post()
+        .uri("/games")
+        .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+        .to_request()
+}
+
+/// Sends `token` to a game endpoint and returns the status and error.
+async fn try_token<S>(app: &S, token: &str) -> (StatusCode, String)
+where
+    S: actix_web::dev::Service<
+        actix_http::Request,
+        Response = actix_web::dev::ServiceResponse,
+        Error = actix_web::Error,
+    >,
+{
+    let resp = test::call_service(app, new_game(token)).await;
+    let status = resp.status();
+    if status == StatusCode::UNAUTHORIZED {
+        assert_eq!(
+            resp.headers().get(header::WWW_AUTHENTICATE).unwrap(),
+            "Bearer"
+        );
+    }
+    let body: Value = test::read_body_json(resp).await;
+    (status, body["error"].as_str().unwrap_or("").to_string())
+}
+
+#[actix_web::test]
+async fn register_login_and_play() {
+    let fx = Fixture::new();
+    let app = fx.app().await;
+
+    let req = test::TestRequest::post()
+        .uri("/accounts")
+        .set_json(credentials("ann", "correct horse"))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::CREATED
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/accounts")
+        .set_json(credentials("ann", "battery staple"))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::CONFLICT
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/tokens")
+        .set_json(credentials("ann", "wrong horse"))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/tokens")
+        .set_json(credentials("ann", "correct horse"))
+        .to_request();
+    let tokens: TokenPair = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(tokens.token_type, "Bearer");
+    assert_eq!(tokens.expires_in, 15 * 60);
+
+    let game: GameView = test::call_and_read_body_json(&app, new_game(&tokens.access_token)).await;
+    assert_eq!(game.player, "ann");
+}
+
+#[actix_web::test]
+async fn refresh_tokens_get_a_new_pair() {
+    let fx = Fixture::new();
+    fx.accounts.register("ann", "correct horse").unwrap();
+    let app = fx.app().await;
+    let tokens = fx.auth.issue("ann");
+
+    let req = test::TestRequest::post()
+        .uri("/tokens/refresh")
+        .set_json(json!({ "refresh_token": tokens.refresh_token }))
+        .to_request();
+    let fresh: TokenPair = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(
+        try_token(&app, &fresh.access_token).await.0,
+        StatusCode::CREATED
+    );
+
+    // The old refresh token was used up; the new one works once.
+    let refresh = |token: &str| {
+        test::TestRequest::post()
+            .uri("/tokens/refresh")
+            .set_json(json!({ "refresh_token": token }))
+            .to_request()
+    };
+    let resp = test::call_service(&app, refresh(&tokens.refresh_token)).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "the token has already been used");
+    let resp = test::call_service(&app, refresh(&fresh.refresh_token)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // An access token is not a refresh token.
+    let req = test::TestRequest::post()
+        .uri("/tokens/refresh")
+        .set_json(json!({ "refresh_token": tokens.access_token }))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+
+    // Nor does a refresh token open the game endpoints.
+    let (status, error) = try_token(&app, &tokens.refresh_token).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(error, "wrong kind of token");
+}
+
+#[actix_web::test]
+async fn game_endpoints_need_a_token() {
+    let fx = Fixture::new();
+    let app = fx.app().await;
+
+    let req = test::TestRequest::post().uri("/games").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "a bearer token is required");
+
+    let req = test::TestRequest::post()
+        .uri("/games")
+        .insert_header(fx.bearer("ann"))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::CREATED
+    );
+}
+
+#[actix_web::test]
+async fn refuses_expired_tokens() {
+    let fx = Fixture::new();
+    let app = fx.app().await;
+
+    let an_hour_ago = scores::now() - 3600;
+    let stale = fx.auth.issue_at("ann", an_hour_ago).access_token;
+    assert_eq!(
+        try_token(&app, &stale).await,
+        (
+            StatusCode::UNAUTHORIZED,
+            "the token has expired".to_string()
+        )
+    );
+}
+
+#[actix_web::test]
+async fn refuses_tampered_tokens() {
+    let fx = Fixture::new();
+    let app = fx.app().await;
+
+    // Flip a character in the signature.
+    let mut token = fx.auth.issue("ann").access_token.into_bytes();
+    let last = token.len() - 2;
+    token[last] = if token[last] == b'A' { b'B' } else { b'A' };
+    let token = String::from_utf8(token).unwrap();
+    let (status, error) = try_token(&app, &token).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(error.starts_with("invalid token"), "{}", error);
+
+    // Signed with someone else's key.
+    let forged = Auth::new(b"not our secret").issue("ann").access_token;
+    assert_eq!(try_token(&app, &forged).await.0, StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn refuses_tokens_for_another_audience() {
+    let fx = Fixture::new();
+    let app = fx.app().await;
+
+    let claims = Claims {
+        aud: "billing".to_string(),
+        ..fx.auth.claims("ann", TokenKind::Access, scores::now())
+    };
+    assert_eq!(
+        try_token(&app, &fx.auth.sign(&claims)).await,
+        (
+            StatusCode::UNAUTHORIZED,
+            "the token is not meant for this service".to_string()
+        )
+    );
+}
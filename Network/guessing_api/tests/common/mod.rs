@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::http::header;
+use actix_web::{test, web, App};
+use guessing_api::{routes, Accounts, Auth, GameStore, MemoryStore, Metrics};
+use tempfile::TempDir;
+
+pub const SECRET: &[u8] = b"test secret";
+
+/// The app's shared state, kept around so tests can reach into it.
+pub struct Fixture {
+    pub store: Arc<MemoryStore>,
+    pub accounts: web::Data<Accounts>,
+    pub auth: web::Data<Auth>,
+    pub metrics: Metrics,
+    _dir: TempDir,
+}
+
+impl Fixture {
+    pub fn new() -> Fixture {
+        let dir = TempDir::new().unwrap();
+        let accounts = Accounts::open(dir.path().join("accounts.json")).unwrap();
+        Fixture {
+            store: Arc::new(MemoryStore::new()),
+            accounts: web::Data::new(accounts),
+            auth: web::Data::new(Auth::new(SECRET)),
+            metrics: Metrics::new(),
+            _dir: dir,
+        }
+    }
+
+    pub async fn app(
+        &self,
+    ) -> impl Service<actix_http::Request, Response = ServiceResponse, Error = actix_web::Error>
+    {
+        let store: Arc<dyn GameStore> = self.store.clone();
+        test::init_service(
+            App::new()
+                .wrap(self.metrics.middleware())
+                .app_data(web::Data::new(self.metrics.clone()))
+                .app_data(web::Data::from(store))
+                .app_data(self.accounts.clone())
+                .app_data(self.auth.clone())
+                .configure(routes),
+        )
+        .await
+    }
+
+    /// An `Authorization` header with a fresh access token for `player`.
+    pub fn bearer(&self, player: &str) -> (header::HeaderName, String) {
+        let token = self.auth.issue(player).access_token;
+        (header::AUTHORIZATION, format!("Bearer {}", token))
+    }
+}
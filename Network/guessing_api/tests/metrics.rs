@@ -0,0 +1,182 @@
+mod common;
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use actix_web::http::Method;
+use actix_web::test;
+use common::Fixture;
+use guessing_api::metrics::REQUEST_ID;
+use guessing_api::routes::GameView;
+use serde_json::json;
+
+/// Collects what the log subscriber writes.
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Captured {
+    fn lines(&self) -> Vec<serde_json::Value> {
+        let text = String::from_utf8(self.0.lock().unwrap().clone()).unwrap();
+        text.lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+}
+
+/// The value of one series in a scrape, e.g.
+/// `sample(text, r#"requests_total{route="/"}"#)`.
+fn sample(scrape: &str, series: &str) -> f64 {
+    scrape
+        .lines()
+        .find_map(|line| line.strip_prefix(series)?.strip_prefix(' '))
+        .unwrap_or_else(|| panic!("no {} in\n{}", series, scrape))
+        .parse()
+        .unwrap()
+}
+
+#[actix_web::test]
+async fn scrapes_request_and_game_metrics() {
+    let fx = Fixture::new();
+    let app = fx.app().await;
+
+    for _ in 0..2 {
+        let req = test::TestRequest::post()
+            .uri("/games")
+            .insert_header(fx.bearer("ann"))
+            .set_json(json!({ "difficulty": { "custom": { "range": { "start": 4, "end": 4 }, "max_attempts": 1 } } }))
+            .to_request();
+        let game: GameView = test::call_and_read_body_json(&app, req).await;
+        let req = test::TestRequest::post()
+            .uri(&format!("/games/{}/guesses", game.id))
+            .insert_header(fx.bearer("ann"))
+            .set_json(json!({ "guess": 4 }))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+    let req = test::TestRequest::post().uri("/games").to_request();
+    test::call_service(&app, req).await;
+    let req = test::TestRequest::get().uri("/wp-admin").to_request();
+    test::call_service(&app, req).await;
+    let req = test::TestRequest::default()
+        .method(Method::from_bytes(b"BREW").unwrap())
+        .uri("/wp-admin")
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp
+        .headers()
+        .get("content-type")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .starts_with("text/plain; version=0.0.4"));
+    let body = test::read_body(resp).await;
+    let text = std::str::from_utf8(&body).unwrap();
+
+    assert_eq!(sample(text, "guessing_games_started_total"), 2.0);
+    assert_eq!(sample(text, "guessing_games_won_total"), 2.0);
+
+    let requests =
+        |labels: &str| sample(text, &format!("guessing_http_requests_total{{{}}}", labels));
+    assert_eq!(
+        requests(r#"method="POST",route="/games",status="201""#),
+        2.0
+    );
+    assert_eq!(
+        requests(r#"method="POST",route="/games",status="401""#),
+        1.0
+    );
+    assert_eq!(
+        requests(r#"method="POST",route="/games/{id}/guesses",status="200""#),
+        2.0
+    );
+    assert_eq!(
+        requests(r#"method="GET",route="unmatched",status="404""#),
+        1.0
+    );
+    assert!(!text.contains("BREW"));
+    assert_eq!(
+        requests(r#"method="other",route="unmatched",status="404""#),
+        1.0
+    );
+
+    // One histogram per route, not per game id.
+    assert_eq!(
+        sample(
+            text,
+            r#"guessing_http_request_duration_seconds_count{method="POST",route="/games/{id}/guesses"}"#
+        ),
+        2.0
+    );
+}
+
+#[actix_web::test]
+async fn logs_each_request_with_its_id() {
+    let captured = Captured::default();
+    let writer = captured.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .json()
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let fx = Fixture::new();
+    let app = fx.app().await;
+
+    let req = test::TestRequest::post()
+        .uri("/games")
+        .insert_header(fx.bearer("ann"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let generated = resp
+        .headers()
+        .get(&REQUEST_ID)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert_eq!(generated.len(), 36, "{}", generated);
+
+    // An id from upstream is passed on; a malformed one is replaced.
+    let req = test::TestRequest::get()
+        .uri("/games/not-an-id")
+        .insert_header(fx.bearer("ann"))
+        .insert_header((REQUEST_ID, "edge-42"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.headers().get(&REQUEST_ID).unwrap(), "edge-42");
+    let req = test::TestRequest::get()
+        .uri("/metrics")
+        .insert_header((REQUEST_ID, "two words"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_ne!(resp.headers().get(&REQUEST_ID).unwrap(), "two words");
+
+    let logs = captured.lines();
+    assert_eq!(logs.len(), 3, "{:?}", logs);
+    let fields = &logs[0]["fields"];
+    assert_eq!(fields["message"], "request");
+    assert_eq!(fields["request_id"], generated.as_str());
+    assert_eq!(fields["method"], "POST");
+    assert_eq!(fields["route"], "/games");
+    assert_eq!(fields["status"], 201);
+    assert!(fields["latency_ms"].as_f64().unwrap() >= 0.0);
+
+    let fields = &logs[1]["fields"];
+    assert_eq!(fields["request_id"], "edge-42");
+    assert_eq!(fields["path"], "/games/not-an-id");
+    assert_eq!(fields["route"], "/games/{id}");
+    assert_eq!(fields["status"], 404);
+}
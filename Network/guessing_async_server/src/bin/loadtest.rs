@@ -0,0 +1,150 @@
+//! Opens many connections at once, plays a binary-search game on each and
+//! reports how long the server took to answer.
+
+use std::env;
+use std::io;
+use std::net::SocketAddr;
+use std::process;
+use std::time::{Duration, Instant};
+
+use guessing_async_server::{Client, Config, Percentiles, Server};
+use guessing_server::{Reply, Request};
+use tokio::task::JoinSet;
+
+const USAGE: &str = "\
+Usage: loadtest [--addr <ADDR>] [-n, --connections <N>]
+
+  --addr <ADDR>           server to test; without it one is started in-process
+  -n, --connections <N>   sessions to open at once [default: 2000]";
+
+/// Timings from one session.
+struct Sample {
+    connect: Duration,
+    replies: Vec<Duration>,
+}
+
+#[tokio::main]
+async fn main() {
+    let mut addr = None;
+    let mut connections = 2000;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--addr" => addr = Some(args.next().unwrap_or_else(|| fail("--addr needs a value"))),
+            "-n" | "--connections" => {
+                connections = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or_else(|| fail("--connections needs a number"))
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => fail(&format!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    let addr: SocketAddr = match addr {
+        Some(addr) => addr
+            .parse()
+            .unwrap_or_else(|_| fail("--addr must be ip:port")),
+        None => {
+            let config = Config {
+                max_sessions: connections,
+                ..Config::default()
+            };
+            let server = Server::bind("127.0.0.1:0", config)
+                .await
+                .unwrap_or_else(|e| fail(&e.to_string()));
+            let addr = server.local_addr().unwrap();
+            tokio::spawn(server.run_until(std::future::pending()));
+            addr
+        }
+    };
+
+    println!("opening {} sessions to {}", connections, addr);
+    let started = Instant::now();
+    let mut sessions = JoinSet::new();
+    for _ in 0..connections {
+        sessions.spawn(play(addr));
+    }
+
+    let mut connects = Vec::new();
+    let mut replies = Vec::new();
+    let mut errors = 0;
+    while let Some(result) = sessions.join_next().await {
+        match result.expect("session task panicked") {
+            Ok(sample) => {
+                connects.push(sample.connect);
+                replies.extend(sample.replies);
+            }
+            Err(e) => {
+                errors += 1;
+                if errors <= 5 {
+                    eprintln!("session failed: {}", e);
+                }
+            }
+        }
+    }
+    let elapsed = started.elapsed();
+
+    println!(
+        "{} sessions completed, {} failed, in {:.2?} ({:.0} replies/s)",
+        connects.len(),
+        errors,
+        elapsed,
+        replies.len() as f64 / elapsed.as_secs_f64()
+    );
+    if let Some(connect) = Percentiles::new(connects) {
+        println!("connect  {}", connect);
+    }
+    if let Some(reply) = Percentiles::new(replies) {
+        println!("reply    {}", reply);
+    }
+}
+
+async fn play(addr: SocketAddr) -> io::Result<Sample> {
+    let started = Instant::now();
+    let mut client = Client::connect(addr).await?;
+    let (mut low, mut high) = match (client.recv().await?, client.recv().await?) {
+        (Reply::Welcome { .. }, Reply::Start { low, high, .. }) => (low, high),
+        (Reply::Error(e), _) | (_, Reply::Error(e)) => return Err(io::Error::other(e)),
+        other => return Err(unexpected(other)),
+    };
+    let connect = started.elapsed();
+
+    let mut replies = Vec::new();
+    loop {
+        let guess = low + (high - low) / 2;
+        let sent = Instant::now();
+        client.send(&Request::Guess(guess.to_string())).await?;
+        let reply = client.recv().await?;
+        replies.push(sent.elapsed());
+        match reply {
+            Reply::TooSmall { .. } => low = guess + 1,
+            Reply::TooBig { .. } => high = guess - 1,
+            Reply::Win { .. } | Reply::Lose { .. } => break,
+            other => return Err(unexpected(other)),
+        }
+    }
+
+    client.send(&Request::Quit).await?;
+    match client.recv().await? {
+        Reply::Bye => Ok(Sample { connect, replies }),
+        other => Err(unexpected(other)),
+    }
+}
+
+fn unexpected<T: std::fmt::Debug>(reply: T) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("unexpected reply {:?}", reply),
+    )
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
@@ -0,0 +1,79 @@
+use std::env;
+use std::process;
+use std::time::Duration;
+
+use guessing_async_server::{Config, Server};
+
+const USAGE: &str = "\
+Usage: server [--addr <ADDR>] [--difficulty <LEVEL>] [--max-sessions <N>]
+              [--idle-timeout <SECS>] [--drain-timeout <SECS>]
+
+  --addr <ADDR>             address to listen on [default: 127.0.0.1:7878]
+  -d, --difficulty <LEVEL>  easy, medium or hard [default: medium]
+  --max-sessions <N>        players allowed at once [default: 10000]
+  --idle-timeout <SECS>     close sessions silent for this long [default: 60]
+  --drain-timeout <SECS>    on Ctrl-C, wait this long for games to finish [default: 30]";
+
+#[tokio::main]
+async fn main() {
+    let mut addr = "127.0.0.1:7878".to_string();
+    let mut config = Config::default();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .unwrap_or_else(|| fail(&format!("{} needs a value", arg)))
+        };
+        match arg.as_str() {
+            "--addr" => addr = value(),
+            "-d" | "--difficulty" => {
+                config.difficulty = value().parse().unwrap_or_else(|e: String| fail(&e))
+            }
+            "--max-sessions" => config.max_sessions = number(&arg, &value()),
+            "--idle-timeout" => config.idle_timeout = Duration::from_secs(number(&arg, &value())),
+            "--drain-timeout" => config.drain_timeout = Duration::from_secs(number(&arg, &value())),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => fail(&format!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    let server = Server::bind(&addr, config.clone())
+        .await
+        .unwrap_or_else(|e| fail(&e.to_string()));
+    println!(
+        "async server running on {}, difficulty {}, up to {} players",
+        server.local_addr().unwrap(),
+        config.difficulty,
+        config.max_sessions
+    );
+
+    let shutdown = async {
+        let _ = tokio::signal::ctrl_c().await;
+        println!(
+            "shutting down, waiting up to {:?} for games in progress",
+            config.drain_timeout
+        );
+    };
+    match server.run_until(shutdown).await {
+        Ok(drained) => println!(
+            "{} sessions finished, {} cut off",
+            drained.finished, drained.aborted
+        ),
+        Err(e) => fail(&e.to_string()),
+    }
+}
+
+fn number<T: std::str::FromStr>(flag: &str, value: &str) -> T {
+    value
+        .parse()
+        .unwrap_or_else(|_| fail(&format!("invalid value '{}' for {}", value, flag)))
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
@@ -0,0 +1,44 @@
+use std::io;
+
+use guessing_server::{Reply, Request};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpStream, ToSocketAddrs};
+
+/// An async connection to a [`crate::Server`], or to the threaded one.
+pub struct Client {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    writer: OwnedWriteHalf,
+}
+
+impl Client {
+    pub async fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Client> {
+        let (reader, writer) = TcpStream::connect(addr).await?.into_split();
+        Ok(Client {
+            lines: BufReader::new(reader).lines(),
+            writer,
+        })
+    }
+
+    pub async fn send(&mut self, request: &Request) -> io::Result<()> {
+        self.send_line(&request.to_string()).await
+    }
+
+    pub async fn send_line(&mut self, line: &str) -> io::Result<()> {
+        self.writer
+            .write_all(format!("{}\n", line).as_bytes())
+            .await
+    }
+
+    /// Waits for the next reply. Fails with `UnexpectedEof` once the server
+    /// hangs up.
+    pub async fn recv(&mut self) -> io::Result<Reply> {
+        let line = self
+            .lines
+            .next_line()
+            .await?
+            .ok_or(io::ErrorKind::UnexpectedEof)?;
+        line.parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
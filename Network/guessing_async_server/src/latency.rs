@@ -0,0 +1,70 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Latency percentiles over a set of samples, using the nearest-rank method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Percentiles {
+    pub count: usize,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl Percentiles {
+    /// Returns `None` if there are no samples.
+    pub fn new(mut samples: Vec<Duration>) -> Option<Percentiles> {
+        samples.sort_unstable();
+        Some(Percentiles {
+            count: samples.len(),
+            p50: percentile(&samples, 50.0)?,
+            p90: percentile(&samples, 90.0)?,
+            p99: percentile(&samples, 99.0)?,
+            max: *samples.last()?,
+        })
+    }
+}
+
+/// The smallest sample that at least `p` percent of `sorted` do not exceed.
+pub fn percentile(sorted: &[Duration], p: f64) -> Option<Duration> {
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.max(1) - 1).copied()
+}
+
+impl fmt::Display for Percentiles {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "p50 {:.2?}  p90 {:.2?}  p99 {:.2?}  max {:.2?}  ({} samples)",
+            self.p50, self.p90, self.p99, self.max, self.count
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn nearest_rank() {
+        let samples: Vec<Duration> = (1..=100).rev().map(ms).collect();
+        let stats = Percentiles::new(samples).unwrap();
+        assert_eq!(stats.count, 100);
+        assert_eq!(stats.p50, ms(50));
+        assert_eq!(stats.p90, ms(90));
+        assert_eq!(stats.p99, ms(99));
+        assert_eq!(stats.max, ms(100));
+    }
+
+    #[test]
+    fn small_and_empty_sets() {
+        assert_eq!(Percentiles::new(Vec::new()), None);
+        let one = Percentiles::new(vec![ms(7)]).unwrap();
+        assert_eq!((one.p50, one.p99, one.max), (ms(7), ms(7), ms(7)));
+        assert_eq!(percentile(&[ms(1), ms(2), ms(3)], 50.0), Some(ms(2)));
+    }
+}
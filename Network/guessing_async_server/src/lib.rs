@@ -0,0 +1,11 @@
+//! An async take on `guessing_server`, built on the `tokio` example in
+//! `Network.md`. It speaks the same line protocol in solo mode, but runs
+//! each connection as a task so one process can hold thousands of games.
+
+pub mod client;
+pub mod latency;
+pub mod server;
+
+pub use client::Client;
+pub use latency::Percentiles;
+pub use server::{Config, Drained, Server};
@@ -0,0 +1,226 @@
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use guessing_game::Difficulty;
+use guessing_server::{Mode, Reply, Request, SoloGame};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader,
+};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::{watch, Semaphore};
+use tokio::task::JoinSet;
+use tokio::time;
+
+pub const SERVER_FULL: &str = "the server is full, try again later";
+pub const IDLE: &str = "idle for too long, disconnecting";
+pub const SHUTTING_DOWN: &str = "the server is shutting down";
+
+/// The longest request line a session reads, newline included. Anything
+/// longer ends the session rather than being buffered without limit.
+const MAX_LINE: usize = 1024;
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub difficulty: Difficulty,
+    /// Connections beyond this many are turned away with an error.
+    pub max_sessions: usize,
+    /// A session that sends nothing for this long is closed.
+    pub idle_timeout: Duration,
+    /// How long shutdown waits for games in progress before dropping them.
+    pub drain_timeout: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            difficulty: Difficulty::default(),
+            max_sessions: 10_000,
+            idle_timeout: Duration::from_secs(60),
+            drain_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// What happened to the sessions that were open when shutdown began.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Drained {
+    /// Closed normally, after their game ended or they disconnected.
+    pub finished: usize,
+    /// Still playing when the drain timeout ran out.
+    pub aborted: usize,
+}
+
+/// Hosts solo guessing games, one task per connection.
+pub struct Server {
+    listener: TcpListener,
+    config: Config,
+}
+
+impl Server {
+    pub async fn bind<A: ToSocketAddrs>(addr: A, config: Config) -> io::Result<Server> {
+        let listener = TcpListener::bind(addr).await?;
+        Ok(Server { listener, config })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts connections until `shutdown` completes, then drains: new
+    /// connections are refused, players between games are sent `BYE`, and
+    /// players in the middle of a round may finish it first.
+    pub async fn run_until<F: Future<Output = ()>>(self, shutdown: F) -> io::Result<Drained> {
+        let Server { listener, config } = self;
+        let config = Arc::new(config);
+        let sessions = Arc::new(Semaphore::new(config.max_sessions));
+        let (stop, stopped) = watch::channel(false);
+        let mut tasks = JoinSet::new();
+        let mut next_player = 1;
+
+        tokio::pin!(shutdown);
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                // Reap finished sessions so the set only holds live ones.
+                Some(_) = tasks.join_next(), if !tasks.is_empty() => {}
+                accepted = listener.accept() => {
+                    let stream = match accepted {
+                        Ok((stream, _)) => stream,
+                        Err(e) => {
+                            // Usually out of file descriptors; back off
+                            // rather than spin.
+                            eprintln!("accept failed: {}", e);
+                            time::sleep(Duration::from_millis(100)).await;
+                            continue;
+                        }
+                    };
+                    let Ok(permit) = Arc::clone(&sessions).try_acquire_owned() else {
+                        tasks.spawn(async move {
+                            let mut stream = stream;
+                            let _ = send(&mut stream, &Reply::Error(SERVER_FULL.to_string())).await;
+                        });
+                        continue;
+                    };
+
+                    let player = next_player;
+                    next_player += 1;
+                    let config = Arc::clone(&config);
+                    let stopped = stopped.clone();
+                    tasks.spawn(async move {
+                        if let Err(e) = session(stream, player, &config, stopped).await {
+                            eprintln!("player {}: {}", player, e);
+                        }
+                        drop(permit);
+                    });
+                }
+            }
+        }
+
+        drop(listener);
+        let _ = stop.send(true);
+        let open = tasks.len();
+        let _ = time::timeout(config.drain_timeout, async {
+            while tasks.join_next().await.is_some() {}
+        })
+        .await;
+        let aborted = tasks.len();
+        tasks.shutdown().await;
+        Ok(Drained {
+            finished: open - aborted,
+            aborted,
+        })
+    }
+}
+
+async fn send<W: AsyncWrite + Unpin>(out: &mut W, reply: &Reply) -> io::Result<()> {
+    out.write_all(format!("{}\n", reply).as_bytes()).await
+}
+
+/// Reads the next line, or `None` at the end of the stream. Bytes read so
+/// far are kept in `buf`, so the read can be cancelled and started again.
+async fn next_line<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+) -> io::Result<Option<String>> {
+    let limit = (MAX_LINE - buf.len()) as u64;
+    let read = reader.take(limit).read_until(b'\n', buf).await?;
+    if read == 0 && buf.is_empty() {
+        return Ok(None);
+    }
+    if !buf.ends_with(b"\n") && buf.len() >= MAX_LINE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("request line longer than {} bytes", MAX_LINE),
+        ));
+    }
+    let mut line = std::mem::take(buf);
+    if line.ends_with(b"\n") {
+        line.pop();
+        if line.ends_with(b"\r") {
+            line.pop();
+        }
+    }
+    String::from_utf8(line)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+async fn session(
+    stream: TcpStream,
+    player: u32,
+    config: &Config,
+    mut stop: watch::Receiver<bool>,
+) -> io::Result<()> {
+    let (reader, mut out) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    let mut game = SoloGame::new(config.difficulty.clone(), StdRng::from_entropy());
+    let mut stopping = *stop.borrow();
+
+    send(
+        &mut out,
+        &Reply::Welcome {
+            mode: Mode::Solo,
+            player,
+        },
+    )
+    .await?;
+    send(&mut out, &game.start()).await?;
+
+    loop {
+        if stopping && !game.in_progress() {
+            send(&mut out, &Reply::Error(SHUTTING_DOWN.to_string())).await?;
+            return send(&mut out, &Reply::Bye).await;
+        }
+
+        let line = tokio::select! {
+            line = time::timeout(config.idle_timeout, next_line(&mut reader, &mut buf)) => match line {
+                Ok(Ok(Some(line))) => line,
+                Ok(Ok(None)) => return Ok(()),
+                Ok(Err(e)) => return Err(e),
+                Err(_) => return send(&mut out, &Reply::Error(IDLE.to_string())).await,
+            },
+            // The value only ever changes to true; a dropped sender means
+            // the server is gone, which counts as stopping too.
+            _ = stop.changed(), if !stopping => {
+                stopping = true;
+                continue;
+            }
+        };
+
+        let reply = match Request::parse(&line) {
+            // Starting over would keep the session alive indefinitely.
+            Request::New(_) if stopping => Reply::Error(SHUTTING_DOWN.to_string()),
+            request => game.handle(request),
+        };
+        send(&mut out, &reply).await?;
+        if reply == Reply::Bye {
+            return Ok(());
+        }
+    }
+}
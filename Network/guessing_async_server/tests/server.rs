@@ -0,0 +1,209 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use guessing_async_server::server::{IDLE, SERVER_FULL, SHUTTING_DOWN};
+use guessing_async_server::{Client, Config, Drained, Server};
+use guessing_game::Difficulty;
+use guessing_server::{Reply, Request};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time;
+
+type Running = (SocketAddr, oneshot::Sender<()>, JoinHandle<Drained>);
+
+async fn start(config: Config) -> Running {
+    let server = Server::bind("127.0.0.1:0", config).await.unwrap();
+    let addr = server.local_addr().unwrap();
+    let (stop, stopped) = oneshot::channel();
+    let handle = tokio::spawn(async move {
+        server
+            .run_until(async {
+                let _ = stopped.await;
+            })
+            .await
+            .unwrap()
+    });
+    (addr, stop, handle)
+}
+
+async fn join(addr: SocketAddr) -> Client {
+    let mut client = Client::connect(addr).await.unwrap();
+    assert!(matches!(
+        client.recv().await.unwrap(),
+        Reply::Welcome { .. }
+    ));
+    assert!(matches!(client.recv().await.unwrap(), Reply::Start { .. }));
+    client
+}
+
+/// On a 1..=2 game, guesses 1 until that is a miss, leaving a round in
+/// progress whose secret is 2.
+async fn leave_round_open(client: &mut Client) {
+    loop {
+        client.send_line("1").await.unwrap();
+        match client.recv().await.unwrap() {
+            Reply::TooSmall { .. } => return,
+            Reply::Win { .. } => {
+                client.send(&Request::New(None)).await.unwrap();
+                client.recv().await.unwrap();
+            }
+            other => panic!("unexpected reply {:?}", other),
+        }
+    }
+}
+
+fn coin_flip() -> Difficulty {
+    Difficulty::custom(1..=2, 2).unwrap()
+}
+
+#[tokio::test]
+async fn plays_a_game() {
+    let (addr, _stop, _) = start(Config::default()).await;
+    let mut client = join(addr).await;
+
+    let (mut low, mut high) = (1, 100);
+    loop {
+        let guess = low + (high - low) / 2;
+        client
+            .send(&Request::Guess(guess.to_string()))
+            .await
+            .unwrap();
+        match client.recv().await.unwrap() {
+            Reply::Win { attempts } => {
+                assert!(attempts <= 7);
+                break;
+            }
+            Reply::TooSmall { .. } => low = guess + 1,
+            Reply::TooBig { .. } => high = guess - 1,
+            other => panic!("unexpected reply {:?}", other),
+        }
+    }
+    client.send(&Request::Quit).await.unwrap();
+    assert_eq!(client.recv().await.unwrap(), Reply::Bye);
+}
+
+#[tokio::test]
+async fn turns_away_players_over_the_limit() {
+    let config = Config {
+        max_sessions: 1,
+        ..Config::default()
+    };
+    let (addr, _stop, _) = start(config).await;
+    let mut first = join(addr).await;
+
+    let mut second = Client::connect(addr).await.unwrap();
+    assert_eq!(
+        second.recv().await.unwrap(),
+        Reply::Error(SERVER_FULL.to_string())
+    );
+    assert!(second.recv().await.is_err());
+
+    first.send(&Request::Quit).await.unwrap();
+    assert_eq!(first.recv().await.unwrap(), Reply::Bye);
+
+    // The slot frees up once the first session's task has ended.
+    for _ in 0..50 {
+        let mut third = Client::connect(addr).await.unwrap();
+        if let Reply::Welcome { .. } = third.recv().await.unwrap() {
+            return;
+        }
+        time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("the freed slot was never reused");
+}
+
+#[tokio::test]
+async fn closes_idle_sessions() {
+    let config = Config {
+        idle_timeout: Duration::from_millis(100),
+        ..Config::default()
+    };
+    let (addr, _stop, _) = start(config).await;
+    let mut client = join(addr).await;
+
+    assert_eq!(client.recv().await.unwrap(), Reply::Error(IDLE.to_string()));
+    assert!(client.recv().await.is_err());
+}
+
+#[tokio::test]
+async fn closes_sessions_that_send_overlong_lines() {
+    let (addr, _stop, _) = start(Config::default()).await;
+    let mut client = join(addr).await;
+
+    client.send_line(&"5".repeat(4096)).await.unwrap();
+    assert!(client.recv().await.is_err());
+}
+
+#[tokio::test]
+async fn shutdown_lets_games_in_progress_finish() {
+    let config = Config {
+        difficulty: coin_flip(),
+        ..Config::default()
+    };
+    let (addr, stop, handle) = start(config).await;
+    let mut playing = join(addr).await;
+    leave_round_open(&mut playing).await;
+    let mut waiting = join(addr).await;
+
+    stop.send(()).unwrap();
+
+    // A player between games is let go straight away.
+    let shutting_down = Reply::Error(SHUTTING_DOWN.to_string());
+    assert_eq!(waiting.recv().await.unwrap(), shutting_down);
+    assert_eq!(waiting.recv().await.unwrap(), Reply::Bye);
+    assert!(Client::connect(addr).await.is_err());
+
+    // The other may not start over, but may finish the round.
+    playing.send(&Request::New(None)).await.unwrap();
+    assert_eq!(playing.recv().await.unwrap(), shutting_down);
+    playing.send_line("2").await.unwrap();
+    assert_eq!(playing.recv().await.unwrap(), Reply::Win { attempts: 2 });
+    assert_eq!(playing.recv().await.unwrap(), shutting_down);
+    assert_eq!(playing.recv().await.unwrap(), Reply::Bye);
+
+    assert_eq!(
+        handle.await.unwrap(),
+        Drained {
+            finished: 2,
+            aborted: 0
+        }
+    );
+}
+
+#[tokio::test]
+async fn shutdown_gives_up_after_the_drain_timeout() {
+    let config = Config {
+        difficulty: coin_flip(),
+        drain_timeout: Duration::from_millis(100),
+        ..Config::default()
+    };
+    let (addr, stop, handle) = start(config).await;
+    let mut playing = join(addr).await;
+    leave_round_open(&mut playing).await;
+
+    stop.send(()).unwrap();
+
+    assert_eq!(
+        handle.await.unwrap(),
+        Drained {
+            finished: 0,
+            aborted: 1
+        }
+    );
+    assert!(playing.recv().await.is_err());
+}
+
+#[tokio::test]
+async fn serves_many_sessions_at_once() {
+    let (addr, _stop, _) = start(Config::default()).await;
+    let mut clients = Vec::new();
+    for _ in 0..500 {
+        clients.push(join(addr).await);
+    }
+    for client in &mut clients {
+        client.send(&Request::Quit).await.unwrap();
+    }
+    for client in &mut clients {
+        assert_eq!(client.recv().await.unwrap(), Reply::Bye);
+    }
+}
@@ -0,0 +1,32 @@
+-- Every finished game, with the guesses that were made in it.
+CREATE TABLE games (
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    player     TEXT    NOT NULL,
+    -- The difficulty as JSON, so custom ranges survive the round trip.
+    difficulty TEXT    NOT NULL,
+    secret     INTEGER NOT NULL,
+    attempts   INTEGER NOT NULL,
+    outcome    TEXT    NOT NULL CHECK (outcome IN ('won', 'lost', 'quit')),
+    elapsed_ms INTEGER NOT NULL,
+    played_at  INTEGER NOT NULL
+);
+
+CREATE TABLE guesses (
+    game_id INTEGER NOT NULL REFERENCES games (id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL,
+    value   INTEGER NOT NULL,
+    PRIMARY KEY (game_id, attempt)
+);
+
+-- The high-score table. Rows mirror `guessing_game::scores::ScoreEntry`.
+CREATE TABLE scores (
+    game_id    INTEGER PRIMARY KEY REFERENCES games (id) ON DELETE CASCADE,
+    player     TEXT    NOT NULL,
+    difficulty TEXT    NOT NULL,
+    won        INTEGER NOT NULL,
+    attempts   INTEGER NOT NULL,
+    elapsed_ms INTEGER NOT NULL,
+    played_at  INTEGER NOT NULL
+);
+
+CREATE INDEX scores_by_rank ON scores (difficulty, won, attempts, elapsed_ms, played_at);
@@ -0,0 +1,107 @@
+//! Keeps finished guessing games and their scores in SQLite with sqlx.
+//!
+//! The `Network.md` sqlx example talks to PostgreSQL; SQLite needs no
+//! server, so the database is a single file next to the game. The schema
+//! lives in `migrations/` and is compiled into the crate, so opening a
+//! database brings it up to date.
+//!
+//! ```no_run
+//! # async fn demo(game: guessing_db::FinishedGame) -> Result<(), guessing_db::DbError> {
+//! use guessing_db::{Repository, SqliteRepository};
+//!
+//! let repo = SqliteRepository::open("games.db").await?;
+//! let id = repo.save(&game).await?;
+//! let best = repo.leaderboard("easy (1-10, 6 attempts)", 10).await?;
+//! # Ok(()) }
+//! ```
+
+pub mod memory;
+pub mod record;
+pub mod sqlite;
+
+use std::fmt;
+use std::future::Future;
+
+use guessing_game::scores::ScoreEntry;
+
+pub use memory::MemoryRepository;
+pub use record::FinishedGame;
+pub use sqlite::SqliteRepository;
+
+/// The row id of a saved game.
+pub type GameId = i64;
+
+#[derive(Debug)]
+pub enum DbError {
+    /// The game cannot be saved as it is, e.g. its guesses do not add up
+    /// to its outcome.
+    Invalid(String),
+    /// A stored row could not be turned back into a game.
+    Corrupt(String),
+    Database(sqlx::Error),
+    Migrate(sqlx::migrate::MigrateError),
+}
+
+impl fmt::Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DbError::Invalid(reason) => write!(f, "cannot save game: {}", reason),
+            DbError::Corrupt(reason) => write!(f, "stored game is damaged: {}", reason),
+            DbError::Database(e) => write!(f, "database error: {}", e),
+            DbError::Migrate(e) => write!(f, "could not migrate the database: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DbError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DbError::Database(e) => Some(e),
+            DbError::Migrate(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<sqlx::Error> for DbError {
+    fn from(e: sqlx::Error) -> Self {
+        DbError::Database(e)
+    }
+}
+
+impl From<sqlx::migrate::MigrateError> for DbError {
+    fn from(e: sqlx::migrate::MigrateError) -> Self {
+        DbError::Migrate(e)
+    }
+}
+
+/// Where finished games and scores are kept.
+///
+/// Saving is all or nothing: a game is never stored without its score, or
+/// a score without its game.
+pub trait Repository: Send + Sync {
+    /// Stores a finished game together with its score.
+    fn save(&self, game: &FinishedGame) -> impl Future<Output = Result<GameId, DbError>> + Send;
+
+    fn game(
+        &self,
+        id: GameId,
+    ) -> impl Future<Output = Result<Option<FinishedGame>, DbError>> + Send;
+
+    /// Every game `player` finished, oldest first.
+    fn games_of(
+        &self,
+        player: &str,
+    ) -> impl Future<Output = Result<Vec<(GameId, FinishedGame)>, DbError>> + Send;
+
+    /// Every score, in the order the games were saved.
+    fn scores(&self) -> impl Future<Output = Result<Vec<ScoreEntry>, DbError>> + Send;
+
+    /// The best `limit` wins at `difficulty`, ranked like
+    /// [`ScoreBoard::leaderboard`](guessing_game::scores::ScoreBoard::leaderboard).
+    fn leaderboard(
+        &self,
+        difficulty: &str,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<ScoreEntry>, DbError>> + Send;
+}
@@ -0,0 +1,106 @@
+use std::sync::Mutex;
+
+use guessing_game::scores::ScoreEntry;
+
+use crate::{DbError, FinishedGame, GameId, Repository};
+
+/// Keeps games in memory, e.g. for tests or when no database file is
+/// wanted.
+#[derive(Debug, Default)]
+pub struct MemoryRepository {
+    /// A game's id is its position plus one, like an SQLite row id.
+    games: Mutex<Vec<FinishedGame>>,
+}
+
+impl MemoryRepository {
+    pub fn new() -> MemoryRepository {
+        MemoryRepository::default()
+    }
+}
+
+impl Repository for MemoryRepository {
+    async fn save(&self, game: &FinishedGame) -> Result<GameId, DbError> {
+        game.check()?;
+        let mut games = self.games.lock().unwrap();
+        games.push(game.clone());
+        Ok(games.len() as GameId)
+    }
+
+    async fn game(&self, id: GameId) -> Result<Option<FinishedGame>, DbError> {
+        let games = self.games.lock().unwrap();
+        let index = usize::try_from(id - 1).ok();
+        Ok(index.and_then(|i| games.get(i)).cloned())
+    }
+
+    async fn games_of(&self, player: &str) -> Result<Vec<(GameId, FinishedGame)>, DbError> {
+        let games = self.games.lock().unwrap();
+        Ok((1..)
+            .zip(games.iter())
+            .filter(|(_, game)| game.player == player)
+            .map(|(id, game)| (id, game.clone()))
+            .collect())
+    }
+
+    async fn scores(&self) -> Result<Vec<ScoreEntry>, DbError> {
+        let games = self.games.lock().unwrap();
+        Ok(games.iter().map(FinishedGame::score).collect())
+    }
+
+    async fn leaderboard(
+        &self,
+        difficulty: &str,
+        limit: usize,
+    ) -> Result<Vec<ScoreEntry>, DbError> {
+        let mut wins: Vec<ScoreEntry> = self
+            .scores()
+            .await?
+            .into_iter()
+            .filter(|e| e.won && e.difficulty == difficulty)
+            .collect();
+        wins.sort_by_key(|e| (e.attempts, e.elapsed_ms, e.played_at));
+        wins.truncate(limit);
+        Ok(wins)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use guessing_game::{Difficulty, Outcome};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn refuses_games_that_do_not_add_up() {
+        let repo = MemoryRepository::new();
+        let game = FinishedGame {
+            player: "ann".to_string(),
+            difficulty: Difficulty::Easy,
+            guesses: vec![3, 7],
+            outcome: Outcome::Won {
+                secret: 7,
+                attempts: 3,
+            },
+            elapsed_ms: 100,
+            played_at: 0,
+        };
+        assert!(matches!(repo.save(&game).await, Err(DbError::Invalid(_))));
+
+        let ends_elsewhere = FinishedGame {
+            guesses: vec![3, 7, 8],
+            ..game.clone()
+        };
+        assert!(matches!(
+            repo.save(&ends_elsewhere).await,
+            Err(DbError::Invalid(_))
+        ));
+
+        let fine = FinishedGame {
+            guesses: vec![3, 8, 7],
+            ..game
+        };
+        assert_eq!(repo.save(&fine).await.unwrap(), 1);
+        assert_eq!(repo.game(1).await.unwrap(), Some(fine));
+        assert_eq!(repo.game(0).await.unwrap(), None);
+        assert_eq!(repo.game(2).await.unwrap(), None);
+    }
+}
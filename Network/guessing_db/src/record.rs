@@ -0,0 +1,102 @@
+use guessing_game::scores::{self, ScoreEntry};
+use guessing_game::{Difficulty, Outcome, Round};
+
+use crate::DbError;
+
+/// A game that has ended, as it is stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FinishedGame {
+    pub player: String,
+    pub difficulty: Difficulty,
+    /// Every accepted guess, oldest first.
+    pub guesses: Vec<u32>,
+    /// Holds the secret and the number of attempts.
+    pub outcome: Outcome,
+    pub elapsed_ms: u64,
+    /// Seconds since the Unix epoch.
+    pub played_at: u64,
+}
+
+impl FinishedGame {
+    /// Captures `round` once it is over, or `None` while it is still being
+    /// played. `guesses` are the values the round accepted.
+    pub fn from_round(
+        player: &str,
+        difficulty: Difficulty,
+        round: &Round,
+        guesses: Vec<u32>,
+        elapsed_ms: u64,
+    ) -> Option<FinishedGame> {
+        Some(FinishedGame {
+            player: player.to_string(),
+            difficulty,
+            guesses,
+            outcome: round.outcome()?,
+            elapsed_ms,
+            played_at: scores::now(),
+        })
+    }
+
+    pub fn secret(&self) -> u32 {
+        match self.outcome {
+            Outcome::Won { secret, .. }
+            | Outcome::Lost { secret, .. }
+            | Outcome::Quit { secret, .. } => secret,
+        }
+    }
+
+    pub fn attempts(&self) -> u32 {
+        match self.outcome {
+            Outcome::Won { attempts, .. }
+            | Outcome::Lost { attempts, .. }
+            | Outcome::Quit { attempts, .. } => attempts,
+        }
+    }
+
+    pub fn won(&self) -> bool {
+        matches!(self.outcome, Outcome::Won { .. })
+    }
+
+    /// The line this game adds to the high-score table.
+    pub fn score(&self) -> ScoreEntry {
+        ScoreEntry {
+            player: self.player.clone(),
+            difficulty: self.difficulty.to_string(),
+            won: self.won(),
+            attempts: self.attempts(),
+            elapsed_ms: self.elapsed_ms,
+            played_at: self.played_at,
+        }
+    }
+
+    /// Refuses games that could not have been played: the guesses must
+    /// match the attempts, and a win must end on the secret.
+    pub(crate) fn check(&self) -> Result<(), DbError> {
+        let invalid = |reason: String| Err(DbError::Invalid(reason));
+        if self.guesses.len() != self.attempts() as usize {
+            return invalid(format!(
+                "{} guesses recorded for {} attempts",
+                self.guesses.len(),
+                self.attempts()
+            ));
+        }
+        if self.attempts() > self.difficulty.max_attempts() {
+            return invalid(format!(
+                "{} attempts is more than {} allows",
+                self.attempts(),
+                self.difficulty
+            ));
+        }
+        if !self.difficulty.range().contains(&self.secret()) {
+            return invalid(format!(
+                "the secret {} is outside {}",
+                self.secret(),
+                self.difficulty
+            ));
+        }
+        if self.won() && self.guesses.last() != Some(&self.secret()) {
+            return invalid("a won game must end on the secret".to_string());
+        }
+        Ok(())
+    }
+}
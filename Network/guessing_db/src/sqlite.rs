@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use guessing_game::scores::ScoreEntry;
+use guessing_game::{Difficulty, Outcome};
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::{
+    SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions, SqliteRow,
+};
+use sqlx::Row;
+
+use crate::{DbError, FinishedGame, GameId, Repository};
+
+/// The schema, compiled in from `migrations/`.
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+/// The columns `game_from_row` reads. A macro rather than a constant, so
+/// every query stays a string literal.
+macro_rules! game_columns {
+    () => {
+        "id, player, difficulty, secret, attempts, outcome, elapsed_ms, played_at"
+    };
+}
+
+/// Stores games in an SQLite database file.
+#[derive(Debug, Clone)]
+pub struct SqliteRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteRepository {
+    /// Opens the database at `path`, creating it if needed, and applies
+    /// any migrations it is missing.
+    pub async fn open(path: impl AsRef<Path>) -> Result<SqliteRepository, DbError> {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true)
+            .foreign_keys(true)
+            .journal_mode(SqliteJournalMode::Wal);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(4)
+            .connect_with(options)
+            .await?;
+        SqliteRepository::with_pool(pool).await
+    }
+
+    /// Uses an existing pool, e.g. one opened with other options.
+    pub async fn with_pool(pool: SqlitePool) -> Result<SqliteRepository, DbError> {
+        MIGRATOR.run(&pool).await?;
+        Ok(SqliteRepository { pool })
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// Waits for open connections to finish and closes them.
+    pub async fn close(self) {
+        self.pool.close().await;
+    }
+
+    /// Loads the guesses of one game, in the order they were made.
+    async fn guesses_of_game(&self, id: GameId) -> Result<Vec<u32>, DbError> {
+        let rows =
+            sqlx::query("SELECT game_id, value FROM guesses WHERE game_id = ? ORDER BY attempt")
+                .bind(id)
+                .fetch_all(&self.pool)
+                .await?;
+        Ok(group_guesses(&rows)?.remove(&id).unwrap_or_default())
+    }
+
+    /// Loads the guesses of every game `player` played, keyed by game.
+    async fn guesses_of_player(&self, player: &str) -> Result<HashMap<GameId, Vec<u32>>, DbError> {
+        let rows = sqlx::query(
+            "SELECT game_id, value FROM guesses WHERE game_id IN \
+             (SELECT id FROM games WHERE player = ?) ORDER BY game_id, attempt",
+        )
+        .bind(player)
+        .fetch_all(&self.pool)
+        .await?;
+        group_guesses(&rows)
+    }
+}
+
+impl Repository for SqliteRepository {
+    async fn save(&self, game: &FinishedGame) -> Result<GameId, DbError> {
+        game.check()?;
+        let difficulty =
+            serde_json::to_string(&game.difficulty).map_err(|e| DbError::Invalid(e.to_string()))?;
+        let score = game.score();
+
+        // Dropping the transaction on an early return rolls it back, so
+        // neither the game nor its score is left behind half written.
+        let mut tx = self.pool.begin().await?;
+        let id: GameId = sqlx::query(
+            "INSERT INTO games (player, difficulty, secret, attempts, outcome, elapsed_ms, played_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?) RETURNING id",
+        )
+        .bind(&game.player)
+        .bind(difficulty)
+        .bind(i64::from(game.secret()))
+        .bind(i64::from(game.attempts()))
+        .bind(outcome_name(&game.outcome))
+        .bind(to_i64(game.elapsed_ms, "elapsed time")?)
+        .bind(to_i64(game.played_at, "play time")?)
+        .fetch_one(&mut *tx)
+        .await?
+        .try_get("id")?;
+
+        for (attempt, value) in (1i64..).zip(&game.guesses) {
+            sqlx::query("INSERT INTO guesses (game_id, attempt, value) VALUES (?, ?, ?)")
+                .bind(id)
+                .bind(attempt)
+                .bind(i64::from(*value))
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query(
+            "INSERT INTO scores (game_id, player, difficulty, won, attempts, elapsed_ms, played_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(id)
+        .bind(&score.player)
+        .bind(&score.difficulty)
+        .bind(score.won)
+        .bind(i64::from(score.attempts))
+        .bind(to_i64(score.elapsed_ms, "elapsed time")?)
+        .bind(to_i64(score.played_at, "play time")?)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    async fn game(&self, id: GameId) -> Result<Option<FinishedGame>, DbError> {
+        let Some(row) = sqlx::query(concat!(
+            "SELECT ",
+            game_columns!(),
+            " FROM games WHERE id = ?"
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+        let game = game_from_row(&row, self.guesses_of_game(id).await?)?;
+        Ok(Some(game))
+    }
+
+    async fn games_of(&self, player: &str) -> Result<Vec<(GameId, FinishedGame)>, DbError> {
+        let rows = sqlx::query(concat!(
+            "SELECT ",
+            game_columns!(),
+            " FROM games WHERE player = ? ORDER BY id"
+        ))
+        .bind(player)
+        .fetch_all(&self.pool)
+        .await?;
+        let mut guesses = self.guesses_of_player(player).await?;
+        rows.iter()
+            .map(|row| {
+                let id: GameId = row.try_get("id")?;
+                let game = game_from_row(row, guesses.remove(&id).unwrap_or_default())?;
+                Ok((id, game))
+            })
+            .collect()
+    }
+
+    async fn scores(&self) -> Result<Vec<ScoreEntry>, DbError> {
+        let rows = sqlx::query("SELECT * FROM scores ORDER BY game_id")
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(score_from_row).collect()
+    }
+
+    async fn leaderboard(
+        &self,
+        difficulty: &str,
+        limit: usize,
+    ) -> Result<Vec<ScoreEntry>, DbError> {
+        let rows = sqlx::query(
+            "SELECT * FROM scores WHERE won AND difficulty = ? \
+             ORDER BY attempts, elapsed_ms, played_at LIMIT ?",
+        )
+        .bind(difficulty)
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(score_from_row).collect()
+    }
+}
+
+fn outcome_name(outcome: &Outcome) -> &'static str {
+    match outcome {
+        Outcome::Won { .. } => "won",
+        Outcome::Lost { .. } => "lost",
+        Outcome::Quit { .. } => "quit",
+    }
+}
+
+fn to_i64(value: u64, what: &str) -> Result<i64, DbError> {
+    i64::try_from(value).map_err(|_| DbError::Invalid(format!("{} {} is too large", what, value)))
+}
+
+fn to_u32(value: i64, what: &str) -> Result<u32, DbError> {
+    u32::try_from(value)
+        .map_err(|_| DbError::Corrupt(format!("{} {} is out of range", what, value)))
+}
+
+fn to_u64(value: i64, what: &str) -> Result<u64, DbError> {
+    u64::try_from(value)
+        .map_err(|_| DbError::Corrupt(format!("{} {} is out of range", what, value)))
+}
+
+/// Groups `game_id, value` rows by game, keeping their order.
+fn group_guesses(rows: &[SqliteRow]) -> Result<HashMap<GameId, Vec<u32>>, DbError> {
+    let mut guesses: HashMap<GameId, Vec<u32>> = HashMap::new();
+    for row in rows {
+        let value: i64 = row.try_get("value")?;
+        guesses
+            .entry(row.try_get("game_id")?)
+            .or_default()
+            .push(to_u32(value, "guess")?);
+    }
+    Ok(guesses)
+}
+
+fn game_from_row(row: &SqliteRow, guesses: Vec<u32>) -> Result<FinishedGame, DbError> {
+    let id: GameId = row.try_get("id")?;
+    let corrupt = |reason: String| DbError::Corrupt(format!("game {}: {}", id, reason));
+
+    let difficulty: Difficulty = serde_json::from_str(row.try_get("difficulty")?)
+        .map_err(|e| corrupt(format!("bad difficulty: {}", e)))?;
+    let secret = to_u32(row.try_get("secret")?, "secret")?;
+    let attempts = to_u32(row.try_get("attempts")?, "attempts")?;
+    let outcome = match row.try_get::<&str, _>("outcome")? {
+        "won" => Outcome::Won { secret, attempts },
+        "lost" => Outcome::Lost { secret, attempts },
+        "quit" => Outcome::Quit { secret, attempts },
+        other => return Err(corrupt(format!("unknown outcome '{}'", other))),
+    };
+    let game = FinishedGame {
+        player: row.try_get("player")?,
+        difficulty,
+        guesses,
+        outcome,
+        elapsed_ms: to_u64(row.try_get("elapsed_ms")?, "elapsed time")?,
+        played_at: to_u64(row.try_get("played_at")?, "play time")?,
+    };
+    game.check().map_err(|e| match e {
+        DbError::Invalid(reason) => corrupt(reason),
+        other => other,
+    })?;
+    Ok(game)
+}
+
+fn score_from_row(row: &SqliteRow) -> Result<ScoreEntry, DbError> {
+    Ok(ScoreEntry {
+        player: row.try_get("player")?,
+        difficulty: row.try_get("difficulty")?,
+        won: row.try_get("won")?,
+        attempts: to_u32(row.try_get("attempts")?, "attempts")?,
+        elapsed_ms: to_u64(row.try_get("elapsed_ms")?, "elapsed time")?,
+        played_at: to_u64(row.try_get("played_at")?, "play time")?,
+    })
+}
@@ -0,0 +1,181 @@
+use guessing_db::{DbError, FinishedGame, MemoryRepository, Repository, SqliteRepository};
+use guessing_game::{Difficulty, Round};
+use tempfile::TempDir;
+
+/// Plays `guesses` against `secret` until the round ends.
+fn play(
+    player: &str,
+    difficulty: Difficulty,
+    secret: u32,
+    guesses: &[u32],
+    elapsed_ms: u64,
+) -> FinishedGame {
+    let mut round = Round::with_secret(&difficulty, secret);
+    let mut accepted = Vec::new();
+    for &guess in guesses {
+        round.guess(guess).unwrap();
+        accepted.push(guess);
+        if round.outcome().is_some() {
+            break;
+        }
+    }
+    if round.outcome().is_none() {
+        round.quit();
+    }
+    let mut game =
+        FinishedGame::from_round(player, difficulty, &round, accepted, elapsed_ms).unwrap();
+    game.played_at = 1_700_000_000 + elapsed_ms;
+    game
+}
+
+async fn open(dir: &TempDir) -> SqliteRepository {
+    SqliteRepository::open(dir.path().join("games.db"))
+        .await
+        .unwrap()
+}
+
+/// What every repository must do, whatever keeps the data.
+async fn behaves_like_a_repository<R: Repository>(repo: &R) {
+    let easy = Difficulty::Easy.to_string();
+    let quick = play("ann", Difficulty::Easy, 7, &[5, 7], 900);
+    let slow = play("bob", Difficulty::Easy, 7, &[5, 7], 4_000);
+    let lost = play("ann", Difficulty::Easy, 1, &[2, 3, 4, 5, 6, 7], 100);
+    let custom = Difficulty::custom(20..=30, 2).unwrap();
+    let quit = play("ann", custom, 25, &[21], 50);
+
+    let mut ids = Vec::new();
+    for game in [&slow, &quick, &lost, &quit] {
+        ids.push(repo.save(game).await.unwrap());
+    }
+    assert!(ids.windows(2).all(|w| w[0] < w[1]), "{:?}", ids);
+
+    assert_eq!(repo.game(ids[1]).await.unwrap().as_ref(), Some(&quick));
+    assert_eq!(repo.game(ids[3]).await.unwrap().as_ref(), Some(&quit));
+    assert_eq!(repo.game(ids[3] + 100).await.unwrap(), None);
+
+    let anns = repo.games_of("ann").await.unwrap();
+    assert_eq!(
+        anns,
+        vec![
+            (ids[1], quick.clone()),
+            (ids[2], lost.clone()),
+            (ids[3], quit.clone())
+        ]
+    );
+    assert!(repo.games_of("cat").await.unwrap().is_empty());
+
+    let scores = repo.scores().await.unwrap();
+    assert_eq!(
+        scores,
+        vec![slow.score(), quick.score(), lost.score(), quit.score()]
+    );
+
+    // Losses never rank, and ties on attempts go to the faster player.
+    let board = repo.leaderboard(&easy, 10).await.unwrap();
+    assert_eq!(board, vec![quick.score(), slow.score()]);
+    assert_eq!(
+        repo.leaderboard(&easy, 1).await.unwrap(),
+        vec![quick.score()]
+    );
+    assert!(repo
+        .leaderboard("medium (1-100, 10 attempts)", 10)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let short = FinishedGame {
+        guesses: vec![5],
+        ..quick
+    };
+    assert!(matches!(repo.save(&short).await, Err(DbError::Invalid(_))));
+    assert_eq!(repo.scores().await.unwrap().len(), 4);
+}
+
+#[tokio::test]
+async fn memory_repository() {
+    behaves_like_a_repository(&MemoryRepository::new()).await;
+}
+
+#[tokio::test]
+async fn sqlite_repository() {
+    let dir = TempDir::new().unwrap();
+    behaves_like_a_repository(&open(&dir).await).await;
+}
+
+#[tokio::test]
+async fn games_survive_reopening_the_file() {
+    let dir = TempDir::new().unwrap();
+    let game = play(
+        "ann",
+        Difficulty::Hard,
+        4_321,
+        &[5_000, 2_500, 4_321],
+        12_345,
+    );
+
+    let repo = open(&dir).await;
+    let id = repo.save(&game).await.unwrap();
+    repo.close().await;
+
+    // Opening again must not try to apply the migrations twice.
+    let repo = open(&dir).await;
+    assert_eq!(repo.game(id).await.unwrap(), Some(game.clone()));
+    assert_eq!(repo.scores().await.unwrap(), vec![game.score()]);
+}
+
+#[tokio::test]
+async fn a_failed_score_leaves_no_game_behind() {
+    let dir = TempDir::new().unwrap();
+    let repo = open(&dir).await;
+    sqlx::query(
+        "CREATE TRIGGER no_scores BEFORE INSERT ON scores \
+         BEGIN SELECT RAISE(ABORT, 'scores are read-only'); END",
+    )
+    .execute(repo.pool())
+    .await
+    .unwrap();
+
+    let game = play("ann", Difficulty::Easy, 3, &[5, 3], 700);
+    let err = repo.save(&game).await.unwrap_err();
+    assert!(err.to_string().contains("scores are read-only"), "{}", err);
+
+    let (games, guesses): (i64, i64) =
+        sqlx::query_as("SELECT (SELECT COUNT(*) FROM games), (SELECT COUNT(*) FROM guesses)")
+            .fetch_one(repo.pool())
+            .await
+            .unwrap();
+    assert_eq!((games, guesses), (0, 0));
+    assert!(repo.games_of("ann").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn reports_damaged_rows() {
+    let dir = TempDir::new().unwrap();
+    let repo = open(&dir).await;
+    let id = repo
+        .save(&play("ann", Difficulty::Easy, 3, &[5, 3], 700))
+        .await
+        .unwrap();
+
+    sqlx::query("UPDATE games SET secret = 4 WHERE id = ?")
+        .bind(id)
+        .execute(repo.pool())
+        .await
+        .unwrap();
+    let err = repo.game(id).await.unwrap_err();
+    assert!(matches!(err, DbError::Corrupt(_)), "{}", err);
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "stored game is damaged: game {}: a won game must end on the secret",
+            id
+        )
+    );
+
+    sqlx::query("UPDATE games SET difficulty = 'impossible' WHERE id = ?")
+        .bind(id)
+        .execute(repo.pool())
+        .await
+        .unwrap();
+    assert!(matches!(repo.game(id).await, Err(DbError::Corrupt(_))));
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use guessing_game::scores::{self, ScoreBoard, ScoreEntry};
+use guessing_game::{Difficulty, Hint, Outcome, Round, RoundError};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// How many guess events a slow subscriber may fall behind before it
+/// starts missing some.
+const EVENT_BUFFER: usize = 256;
+
+/// One accepted guess, as sent to subscribers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuessEvent {
+    pub game_id: String,
+    pub value: u32,
+    pub hint: Hint,
+    /// Which attempt this was, starting at 1.
+    pub attempt: u32,
+    pub attempts_left: u32,
+    pub outcome: Option<Outcome>,
+}
+
+/// A game in progress or finished.
+#[derive(Debug, Clone)]
+pub struct GameRecord {
+    pub id: String,
+    pub player: String,
+    pub difficulty: Difficulty,
+    pub round: Round,
+    pub guesses: Vec<GuessEvent>,
+    started: Instant,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameError {
+    NotFound,
+    GameOver,
+    InvalidGuess(String),
+}
+
+impl GameError {
+    /// A stable name for clients to match on, sent as the `code` error
+    /// extension.
+    pub fn code(&self) -> &'static str {
+        match self {
+            GameError::NotFound => "NOT_FOUND",
+            GameError::GameOver => "GAME_OVER",
+            GameError::InvalidGuess(_) => "INVALID_GUESS",
+        }
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::NotFound => write!(f, "no such game"),
+            GameError::GameOver => write!(f, "the game is over"),
+            GameError::InvalidGuess(reason) => f.write_str(reason),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+/// Every game, the score board finished games are recorded on, and the
+/// channel guess events are published to.
+pub struct Games {
+    games: Mutex<HashMap<String, GameRecord>>,
+    scores: Mutex<ScoreBoard>,
+    events: broadcast::Sender<GuessEvent>,
+}
+
+impl Games {
+    pub fn new(scores: ScoreBoard) -> Games {
+        Games {
+            games: Mutex::new(HashMap::new()),
+            scores: Mutex::new(scores),
+            events: broadcast::channel(EVENT_BUFFER).0,
+        }
+    }
+
+    pub fn create(&self, player: String, difficulty: Difficulty, round: Round) -> GameRecord {
+        let game = GameRecord {
+            id: Uuid::new_v4().to_string(),
+            player,
+            difficulty,
+            round,
+            guesses: Vec::new(),
+            started: Instant::now(),
+        };
+        self.games
+            .lock()
+            .unwrap()
+            .insert(game.id.clone(), game.clone());
+        game
+    }
+
+    pub fn get(&self, id: &str) -> Option<GameRecord> {
+        self.games.lock().unwrap().get(id).cloned()
+    }
+
+    /// Plays a guess, publishes it straight away and, if it ended the game,
+    /// records the score. Saving the score board is blocking file I/O, so it runs on the
+    /// blocking pool.
+    pub async fn guess(self: &Arc<Self>, id: &str, value: u32) -> Result<GuessEvent, GameError> {
+        let (event, finished) = {
+            let mut games = self.games.lock().unwrap();
+            let game = games.get_mut(id).ok_or(GameError::NotFound)?;
+            let hint = game.round.guess(value).map_err(|e| match e {
+                RoundError::GameOver(_) => GameError::GameOver,
+                e => GameError::InvalidGuess(e.to_string()),
+            })?;
+            let event = GuessEvent {
+                game_id: game.id.clone(),
+                value,
+                hint,
+                attempt: game.round.attempts(),
+                attempts_left: game.round.attempts_left(),
+                outcome: game.round.outcome(),
+            };
+            game.guesses.push(event.clone());
+            // Published under the lock, so subscribers see a game's guesses
+            // in order. Nobody listening is fine.
+            let _ = self.events.send(event.clone());
+            let finished = event.outcome.map(|_| game.clone());
+            (event, finished)
+        };
+
+        if let Some(game) = finished {
+            let games = Arc::clone(self);
+            let saved = tokio::task::spawn_blocking(move || games.record(&game)).await;
+            if let Err(e) = saved.unwrap_or_else(|e| Err(io::Error::other(e))) {
+                eprintln!("could not save scores: {}", e);
+            }
+        }
+        Ok(event)
+    }
+
+    fn record(&self, game: &GameRecord) -> io::Result<()> {
+        let mut scores = self.scores.lock().unwrap();
+        scores.record(ScoreEntry {
+            player: game.player.clone(),
+            difficulty: game.difficulty.to_string(),
+            won: matches!(game.round.outcome(), Some(Outcome::Won { .. })),
+            attempts: game.round.attempts(),
+            elapsed_ms: game.started.elapsed().as_millis() as u64,
+            played_at: scores::now(),
+        });
+        scores.save()
+    }
+
+    /// The best `limit` wins at `difficulty`.
+    pub fn leaderboard(&self, difficulty: &Difficulty, limit: usize) -> Vec<ScoreEntry> {
+        self.scores
+            .lock()
+            .unwrap()
+            .leaderboard(&difficulty.to_string())
+            .into_iter()
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<GuessEvent> {
+        self.events.subscribe()
+    }
+}
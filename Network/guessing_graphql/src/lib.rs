@@ -0,0 +1,45 @@
+//! A GraphQL API for the guessing game, built with async-graphql and served
+//! with warp as in the subscription example in `Network.md`.
+//!
+//! ```graphql
+//! mutation { newGame(level: EASY, player: "ann") { id low high } }
+//! mutation { guess(gameId: "...", value: 5) { hint attemptsLeft status } }
+//! subscription { guesses(gameId: "...") { value hint attempt } }
+//! query { leaderboard(level: EASY) { player attempts } }
+//! ```
+
+pub mod games;
+pub mod schema;
+
+use std::convert::Infallible;
+
+use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
+use async_graphql_warp::{graphql_subscription, GraphQLResponse};
+use warp::{http::Response, Filter, Rejection, Reply};
+
+pub use games::{GameError, Games};
+pub use schema::{build_schema, GuessSchema};
+
+/// `POST /graphql` for queries and mutations, a WebSocket upgrade on the
+/// same path for subscriptions, and a playground at `GET /`.
+pub fn routes(
+    schema: GuessSchema,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let subscriptions = warp::path("graphql").and(graphql_subscription(schema.clone()));
+    let queries = warp::path("graphql")
+        .and(warp::post())
+        .and(async_graphql_warp::graphql(schema))
+        .and_then(
+            |(schema, request): (GuessSchema, async_graphql::Request)| async move {
+                Ok::<_, Infallible>(GraphQLResponse::from(schema.execute(request).await))
+            },
+        );
+    let playground = warp::path::end().and(warp::get()).map(|| {
+        Response::builder()
+            .header("content-type", "text/html")
+            .body(playground_source(
+                GraphQLPlaygroundConfig::new("/graphql").subscription_endpoint("/graphql"),
+            ))
+    });
+    subscriptions.or(queries).or(playground)
+}
@@ -0,0 +1,62 @@
+use std::env;
+use std::path::PathBuf;
+use std::process;
+use std::sync::Arc;
+
+use guessing_game::scores::{self, LoadStatus, ScoreBoard};
+use guessing_graphql::{build_schema, routes, Games};
+
+const USAGE: &str = "\
+Usage: guessing_graphql [--addr <ADDR>] [--scores-file <PATH>]
+
+  --addr <ADDR>          address to listen on [default: 127.0.0.1:8080]
+  --scores-file <PATH>   where the leaderboard is kept [default: the game's score file]";
+
+#[tokio::main]
+async fn main() {
+    let mut addr = "127.0.0.1:8080".to_string();
+    let mut scores_file = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--addr" => addr = args.next().unwrap_or_else(|| fail("--addr needs a value")),
+            "--scores-file" => {
+                scores_file = Some(PathBuf::from(
+                    args.next()
+                        .unwrap_or_else(|| fail("--scores-file needs a value")),
+                ))
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => fail(&format!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    let addr: std::net::SocketAddr = addr
+        .parse()
+        .unwrap_or_else(|_| fail("--addr must be ip:port"));
+    let path = scores_file
+        .or_else(scores::default_path)
+        .unwrap_or_else(|| fail("no data directory, pass --scores-file"));
+    let (board, status) = ScoreBoard::open(&path).unwrap_or_else(|e| fail(&e.to_string()));
+    if let LoadStatus::Recovered { backup, reason } = status {
+        eprintln!(
+            "warning: {} could not be read because {}; moved it to {}",
+            path.display(),
+            reason,
+            backup.display()
+        );
+    }
+
+    let schema = build_schema(Arc::new(Games::new(board)));
+    println!("GraphQL playground on http://{}/", addr);
+    warp::serve(routes(schema)).run(addr).await;
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
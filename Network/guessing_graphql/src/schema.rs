@@ -0,0 +1,284 @@
+use std::sync::Arc;
+
+use async_graphql::{
+    Context, Enum, Error, ErrorExtensions, Object, Result, Schema, Subscription, ID,
+};
+use futures_util::stream::{self, Stream};
+use guessing_game::scores::ScoreEntry;
+use guessing_game::{Difficulty, Hint, Outcome, Round};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::games::{GameError, GameRecord, Games, GuessEvent};
+
+pub type GuessSchema = Schema<QueryRoot, MutationRoot, SubscriptionRoot>;
+
+/// Builds the schema around `games`, which may be shared with other code.
+pub fn build_schema(games: Arc<Games>) -> GuessSchema {
+    Schema::build(QueryRoot, MutationRoot, SubscriptionRoot)
+        .data(games)
+        .finish()
+}
+
+impl ErrorExtensions for GameError {
+    fn extend(&self) -> Error {
+        Error::new(self.to_string()).extend_with(|_, e| e.set("code", self.code()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum Level {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl From<Level> for Difficulty {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::Easy => Difficulty::Easy,
+            Level::Medium => Difficulty::Medium,
+            Level::Hard => Difficulty::Hard,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum GuessHint {
+    TooLow,
+    TooHigh,
+    Correct,
+}
+
+impl From<Hint> for GuessHint {
+    fn from(hint: Hint) -> Self {
+        match hint {
+            Hint::TooSmall => GuessHint::TooLow,
+            Hint::TooBig => GuessHint::TooHigh,
+            Hint::Correct => GuessHint::Correct,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum Status {
+    Playing,
+    Won,
+    Lost,
+}
+
+impl Status {
+    fn of(outcome: Option<Outcome>) -> Status {
+        match outcome {
+            None => Status::Playing,
+            Some(Outcome::Won { .. }) => Status::Won,
+            Some(Outcome::Lost { .. } | Outcome::Quit { .. }) => Status::Lost,
+        }
+    }
+}
+
+pub struct Game(GameRecord);
+
+#[Object]
+impl Game {
+    async fn id(&self) -> ID {
+        ID(self.0.id.clone())
+    }
+
+    async fn player(&self) -> &str {
+        &self.0.player
+    }
+
+    async fn difficulty(&self) -> &str {
+        self.0.difficulty.name()
+    }
+
+    async fn low(&self) -> u32 {
+        *self.round().range().start()
+    }
+
+    async fn high(&self) -> u32 {
+        *self.round().range().end()
+    }
+
+    async fn max_attempts(&self) -> u32 {
+        self.round().max_attempts()
+    }
+
+    async fn attempts(&self) -> u32 {
+        self.round().attempts()
+    }
+
+    async fn attempts_left(&self) -> u32 {
+        self.round().attempts_left()
+    }
+
+    async fn status(&self) -> Status {
+        Status::of(self.round().outcome())
+    }
+
+    /// Only revealed once the game is over.
+    async fn secret(&self) -> Option<u32> {
+        self.round().outcome().map(|_| self.round().secret())
+    }
+
+    async fn guesses(&self) -> Vec<Guess> {
+        self.0.guesses.iter().cloned().map(Guess).collect()
+    }
+}
+
+impl Game {
+    fn round(&self) -> &Round {
+        &self.0.round
+    }
+}
+
+pub struct Guess(GuessEvent);
+
+#[Object]
+impl Guess {
+    async fn game_id(&self) -> ID {
+        ID(self.0.game_id.clone())
+    }
+
+    async fn value(&self) -> u32 {
+        self.0.value
+    }
+
+    async fn hint(&self) -> GuessHint {
+        self.0.hint.into()
+    }
+
+    /// Which attempt this was, starting at 1.
+    async fn attempt(&self) -> u32 {
+        self.0.attempt
+    }
+
+    async fn attempts_left(&self) -> u32 {
+        self.0.attempts_left
+    }
+
+    /// The game's status right after this guess.
+    async fn status(&self) -> Status {
+        Status::of(self.0.outcome)
+    }
+}
+
+pub struct Score(ScoreEntry);
+
+#[Object]
+impl Score {
+    async fn player(&self) -> &str {
+        &self.0.player
+    }
+
+    async fn attempts(&self) -> u32 {
+        self.0.attempts
+    }
+
+    async fn elapsed_ms(&self) -> u64 {
+        self.0.elapsed_ms
+    }
+
+    /// `YYYY-MM-DD`, in UTC.
+    async fn date(&self) -> String {
+        self.0.date()
+    }
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// A game by id, or null if there is none.
+    async fn game(&self, ctx: &Context<'_>, id: ID) -> Result<Option<Game>> {
+        Ok(ctx.data::<Arc<Games>>()?.get(&id).map(Game))
+    }
+
+    /// The best wins at a level: fewest attempts, then fastest.
+    async fn leaderboard(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "Level::Medium")] level: Level,
+        #[graphql(default = 10)] limit: usize,
+    ) -> Result<Vec<Score>> {
+        let games = ctx.data::<Arc<Games>>()?;
+        Ok(games
+            .leaderboard(&level.into(), limit)
+            .into_iter()
+            .map(Score)
+            .collect())
+    }
+}
+
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    async fn new_game(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "Level::Medium")] level: Level,
+        #[graphql(default_with = "String::from(\"anonymous\")")] player: String,
+    ) -> Result<Game> {
+        let difficulty = Difficulty::from(level);
+        let round = Round::new(&difficulty, &mut rand::thread_rng());
+        Ok(Game(
+            ctx.data::<Arc<Games>>()?.create(player, difficulty, round),
+        ))
+    }
+
+    async fn guess(&self, ctx: &Context<'_>, game_id: ID, value: u32) -> Result<Guess> {
+        ctx.data::<Arc<Games>>()?
+            .guess(&game_id, value)
+            .await
+            .map(Guess)
+            .map_err(|e| e.extend())
+    }
+}
+
+pub struct SubscriptionRoot;
+
+#[Subscription]
+impl SubscriptionRoot {
+    /// Every guess made at a game from now on. The stream ends with the
+    /// guess that finishes the game.
+    async fn guesses(&self, ctx: &Context<'_>, game_id: ID) -> Result<impl Stream<Item = Guess>> {
+        let games = Arc::clone(ctx.data::<Arc<Games>>()?);
+        // Subscribe before looking at the game, so a guess made in between
+        // is not lost; if it ended the game, the stream would never end.
+        let events = games.subscribe();
+        let game = games
+            .get(&game_id)
+            .ok_or_else(|| GameError::NotFound.extend())?;
+        let over = game.round.outcome().is_some();
+
+        let id = game_id.0;
+        let events = stream::unfold((events, over), move |(mut events, done)| {
+            let (games, id) = (Arc::clone(&games), id.clone());
+            async move {
+                if done {
+                    return None;
+                }
+                loop {
+                    match events.recv().await {
+                        Ok(event) if event.game_id == id => {
+                            let done = event.outcome.is_some();
+                            return Some((Guess(event), (events, done)));
+                        }
+                        Ok(_) => continue,
+                        // A lagging subscriber skips what it missed, but
+                        // that may have been the guess that ended the game.
+                        Err(RecvError::Lagged(_)) => {
+                            let over = games.get(&id).map(|game| game.round.outcome().is_some());
+                            if over != Some(false) {
+                                return None;
+                            }
+                        }
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            }
+        });
+        Ok(events)
+    }
+}
@@ -0,0 +1,313 @@
+use std::sync::Arc;
+
+use async_graphql::{Request, Variables};
+use futures_util::{FutureExt, StreamExt};
+use guessing_game::scores::ScoreBoard;
+use guessing_game::{Difficulty, Round};
+use guessing_graphql::{build_schema, routes, Games, GuessSchema};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+struct Fixture {
+    schema: GuessSchema,
+    games: Arc<Games>,
+    _dir: TempDir,
+}
+
+fn fixture() -> Fixture {
+    let dir = TempDir::new().unwrap();
+    let (board, _) = ScoreBoard::open(dir.path().join("scores.json")).unwrap();
+    let games = Arc::new(Games::new(board));
+    Fixture {
+        schema: build_schema(Arc::clone(&games)),
+        games,
+        _dir: dir,
+    }
+}
+
+impl Fixture {
+    /// A game on easy whose secret is known.
+    fn game(&self, player: &str, secret: u32) -> String {
+        let round = Round::with_secret(&Difficulty::Easy, secret);
+        self.games
+            .create(player.to_string(), Difficulty::Easy, round)
+            .id
+    }
+
+    async fn run(&self, query: &str, variables: Value) -> Value {
+        let request = Request::new(query).variables(Variables::from_json(variables));
+        let response = self.schema.execute(request).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        response.data.into_json().unwrap()
+    }
+
+    /// Runs `query` expecting it to fail, and returns the error's code.
+    async fn fail(&self, query: &str, variables: Value) -> String {
+        let request = Request::new(query).variables(Variables::from_json(variables));
+        let response = self.schema.execute(request).await;
+        let response = serde_json::to_value(&response).unwrap();
+        response["errors"][0]["extensions"]["code"]
+            .as_str()
+            .expect("an error with a code")
+            .to_string()
+    }
+
+    async fn guess(&self, id: &str, value: u32) -> Value {
+        self.run(
+            "mutation($id: ID!, $value: Int!) {
+                guess(gameId: $id, value: $value) { value hint attempt attemptsLeft status }
+            }",
+            json!({ "id": id, "value": value }),
+        )
+        .await["guess"]
+            .clone()
+    }
+}
+
+#[tokio::test]
+async fn new_game_and_state() {
+    let fx = fixture();
+    let data = fx
+        .run(
+            r#"mutation { newGame(level: HARD, player: "ann") { id player difficulty low high maxAttempts status secret } }"#,
+            json!({}),
+        )
+        .await;
+    let game = &data["newGame"];
+    assert_eq!(game["player"], "ann");
+    assert_eq!(game["difficulty"], "hard");
+    assert_eq!(
+        (game["low"].clone(), game["high"].clone()),
+        (json!(1), json!(10_000))
+    );
+    assert_eq!(game["maxAttempts"], 14);
+    assert_eq!(game["status"], "PLAYING");
+    assert_eq!(game["secret"], Value::Null);
+
+    let data = fx
+        .run(
+            "query($id: ID!) { game(id: $id) { id attempts attemptsLeft guesses { value } } }",
+            json!({ "id": game["id"] }),
+        )
+        .await;
+    assert_eq!(data["game"]["id"], game["id"]);
+    assert_eq!(data["game"]["attemptsLeft"], 14);
+    assert_eq!(data["game"]["guesses"], json!([]));
+
+    let data = fx.run(r#"{ game(id: "nope") { id } }"#, json!({})).await;
+    assert_eq!(data["game"], Value::Null);
+}
+
+#[tokio::test]
+async fn guesses_play_the_game_and_fill_the_leaderboard() {
+    let fx = fixture();
+    let id = fx.game("ann", 7);
+
+    assert_eq!(
+        fx.guess(&id, 3).await,
+        json!({ "value": 3, "hint": "TOO_LOW", "attempt": 1, "attemptsLeft": 5, "status": "PLAYING" })
+    );
+    assert_eq!(fx.guess(&id, 9).await["hint"], "TOO_HIGH");
+    assert_eq!(
+        fx.guess(&id, 7).await,
+        json!({ "value": 7, "hint": "CORRECT", "attempt": 3, "attemptsLeft": 3, "status": "WON" })
+    );
+
+    let slower = fx.game("bob", 2);
+    for value in [1, 2] {
+        fx.guess(&slower, value).await;
+    }
+
+    let data = fx
+        .run(
+            "query($id: ID!) {
+                game(id: $id) { status secret guesses { value hint } }
+                leaderboard(level: EASY) { player attempts date }
+                medium: leaderboard { player }
+            }",
+            json!({ "id": id }),
+        )
+        .await;
+    assert_eq!(data["game"]["status"], "WON");
+    assert_eq!(data["game"]["secret"], 7);
+    assert_eq!(
+        data["game"]["guesses"][2],
+        json!({ "value": 7, "hint": "CORRECT" })
+    );
+    let board = data["leaderboard"].as_array().unwrap();
+    assert_eq!(board.len(), 2);
+    assert_eq!(board[0]["player"], "bob");
+    assert_eq!(board[1]["player"], "ann");
+    assert_eq!(board[1]["attempts"], 3);
+    assert_eq!(data["medium"], json!([]));
+}
+
+#[tokio::test]
+async fn errors_carry_a_code() {
+    let fx = fixture();
+    let id = fx.game("ann", 1);
+    let guess = "mutation($id: ID!, $value: Int!) { guess(gameId: $id, value: $value) { hint } }";
+
+    assert_eq!(
+        fx.fail(guess, json!({ "id": "nope", "value": 1 })).await,
+        "NOT_FOUND"
+    );
+    assert_eq!(
+        fx.fail(guess, json!({ "id": id, "value": 11 })).await,
+        "INVALID_GUESS"
+    );
+    fx.guess(&id, 1).await;
+    assert_eq!(
+        fx.fail(guess, json!({ "id": id, "value": 1 })).await,
+        "GAME_OVER"
+    );
+}
+
+#[tokio::test]
+async fn subscription_streams_guesses_until_the_game_ends() {
+    let fx = fixture();
+    let id = fx.game("ann", 4);
+    let other = fx.game("bob", 4);
+
+    let request = Request::new(
+        "subscription($id: ID!) { guesses(gameId: $id) { value hint attempt status } }",
+    )
+    .variables(Variables::from_json(json!({ "id": id })));
+    let mut stream = fx.schema.execute_stream(request);
+
+    // Start listening before any guess is made.
+    let events = tokio::spawn(async move {
+        let mut events = Vec::new();
+        while let Some(response) = stream.next().await {
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+            events.push(response.data.into_json().unwrap()["guesses"].clone());
+        }
+        events
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    fx.guess(&id, 9).await;
+    fx.guess(&other, 1).await;
+    fx.guess(&id, 4).await;
+
+    let events = events.await.unwrap();
+    assert_eq!(
+        events,
+        [
+            json!({ "value": 9, "hint": "TOO_HIGH", "attempt": 1, "status": "PLAYING" }),
+            json!({ "value": 4, "hint": "CORRECT", "attempt": 2, "status": "WON" }),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn subscription_ends_when_it_lagged_past_the_last_guess() {
+    let fx = fixture();
+    let id = fx.game("ann", 4);
+    let others: Vec<String> = (0..30)
+        .map(|_| {
+            let round = Round::with_secret(&Difficulty::Medium, 100);
+            fx.games
+                .create("bob".to_string(), Difficulty::Medium, round)
+                .id
+        })
+        .collect();
+
+    let request = Request::new("subscription($id: ID!) { guesses(gameId: $id) { value } }")
+        .variables(Variables::from_json(json!({ "id": id })));
+    let mut stream = fx.schema.execute_stream(request);
+    let events = tokio::spawn(async move {
+        let mut events = Vec::new();
+        while let Some(response) = stream.next().await {
+            events.push(response.data.into_json().unwrap()["guesses"].clone());
+        }
+        events
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    // The winning guess is followed by more events than the subscriber can
+    // buffer, before it gets a chance to read any of them. The win is
+    // published on the first poll, while its score is still being saved.
+    let win = fx.games.guess(&id, 4);
+    tokio::pin!(win);
+    assert!((&mut win).now_or_never().is_none());
+    for other in &others {
+        for value in 1..=9 {
+            fx.games.guess(other, value).await.unwrap();
+        }
+    }
+    win.await.unwrap();
+
+    let events = tokio::time::timeout(std::time::Duration::from_secs(5), events)
+        .await
+        .expect("the stream ended")
+        .unwrap();
+    assert_eq!(events, Vec::<Value>::new());
+}
+
+#[tokio::test]
+async fn served_over_http() {
+    let fx = fixture();
+    let id = fx.game("ann", 5);
+
+    let response = warp::test::request()
+        .method("POST")
+        .path("/graphql")
+        .json(&json!({
+            "query": "query($id: ID!) { game(id: $id) { high status } }",
+            "variables": { "id": id },
+        }))
+        .reply(&routes(fx.schema.clone()))
+        .await;
+    assert_eq!(response.status(), 200);
+    let body: Value = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(
+        body,
+        json!({ "data": { "game": { "high": 10, "status": "PLAYING" } } })
+    );
+}
+
+#[tokio::test]
+async fn subscriptions_served_over_websocket() {
+    let fx = fixture();
+    let id = fx.game("ann", 5);
+
+    let mut ws = warp::test::ws()
+        .path("/graphql")
+        .header("sec-websocket-protocol", "graphql-transport-ws")
+        .handshake(routes(fx.schema.clone()))
+        .await
+        .unwrap();
+    ws.send_text(json!({ "type": "connection_init" }).to_string())
+        .await;
+    let ack: Value = serde_json::from_str(ws.recv().await.unwrap().to_str().unwrap()).unwrap();
+    assert_eq!(ack["type"], "connection_ack");
+
+    ws.send_text(
+        json!({
+            "id": "1",
+            "type": "subscribe",
+            "payload": {
+                "query": "subscription($id: ID!) { guesses(gameId: $id) { value hint } }",
+                "variables": { "id": id },
+            },
+        })
+        .to_string(),
+    )
+    .await;
+    // Give the subscription a moment to start listening.
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    fx.guess(&id, 5).await;
+
+    let next: Value = serde_json::from_str(ws.recv().await.unwrap().to_str().unwrap()).unwrap();
+    assert_eq!(
+        next,
+        json!({
+            "id": "1",
+            "type": "next",
+            "payload": { "data": { "guesses": { "value": 5, "hint": "CORRECT" } } },
+        })
+    );
+    let complete: Value = serde_json::from_str(ws.recv().await.unwrap().to_str().unwrap()).unwrap();
+    assert_eq!(complete, json!({ "id": "1", "type": "complete" }));
+}
@@ -0,0 +1,51 @@
+use std::io;
+use std::net::TcpStream;
+
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+use crate::protocol::{decode, encode, ClientMessage, ServerMessage};
+
+/// A WebSocket connection to a [`crate::Server`].
+pub struct Client {
+    ws: WebSocket<MaybeTlsStream<TcpStream>>,
+}
+
+impl Client {
+    /// Connects to `addr`, given as `host:port`.
+    pub fn connect(addr: &str) -> tungstenite::Result<Client> {
+        let (ws, _) = tungstenite::connect(format!("ws://{}/", addr))?;
+        Ok(Client { ws })
+    }
+
+    pub fn send(&mut self, message: &ClientMessage) -> tungstenite::Result<()> {
+        self.send_text(&encode(message))
+    }
+
+    /// Sends a raw frame, e.g. to see how the server handles a bad one.
+    pub fn send_text(&mut self, text: &str) -> tungstenite::Result<()> {
+        self.ws.send(Message::text(text))
+    }
+
+    /// Waits for the next message from the server.
+    pub fn recv(&mut self) -> tungstenite::Result<ServerMessage> {
+        loop {
+            if let Message::Text(text) = self.ws.read()? {
+                return decode(text.as_str())
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()).into());
+            }
+        }
+    }
+
+    pub fn close(mut self) -> tungstenite::Result<()> {
+        self.ws.close(None)?;
+        // Wait for the server to answer the close frame.
+        loop {
+            match self.ws.read() {
+                Ok(_) => {}
+                Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
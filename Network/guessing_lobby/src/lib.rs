@@ -0,0 +1,12 @@
+//! A live lobby for the guessing game over WebSockets, growing the
+//! tungstenite example in `Network.md` into rooms where players race for
+//! the same secret and watch each other's progress.
+
+pub mod client;
+pub mod lobby;
+pub mod protocol;
+pub mod server;
+
+pub use client::Client;
+pub use protocol::{ClientMessage, PlayerInfo, SchemaError, ServerMessage, VERSION};
+pub use server::{Config, Server};
@@ -0,0 +1,412 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::mpsc::Sender;
+
+use guessing_game::{Difficulty, Hint, Round, RoundError};
+use rand::rngs::StdRng;
+
+use crate::protocol::{ClientMessage, PlayerInfo, ServerMessage};
+
+/// Where messages for one player go. The connection's thread drains it.
+pub type Outbox = Sender<ServerMessage>;
+
+struct Member {
+    name: String,
+    round: Round,
+    outbox: Outbox,
+}
+
+impl Member {
+    fn info(&self, id: u32) -> PlayerInfo {
+        PlayerInfo {
+            id,
+            name: self.name.clone(),
+            guesses: self.round.attempts(),
+        }
+    }
+
+    /// A player whose connection is gone is removed by its own thread, so
+    /// a failed send can be ignored here.
+    fn send(&self, message: ServerMessage) {
+        let _ = self.outbox.send(message);
+    }
+}
+
+/// A player who left during the current round, and the attempts they had
+/// spent on its secret.
+struct Departed {
+    player: u32,
+    name: String,
+    round: Round,
+}
+
+/// A group of players racing for the same secret. Each has their own
+/// attempts; the first correct guess wins the round for the room.
+struct Room {
+    round_no: u32,
+    secret: u32,
+    members: BTreeMap<u32, Member>,
+    /// Cleared with every new round. Rejoining under the same connection
+    /// or name picks the round up again, so leaving is no way to get more
+    /// guesses at the same secret.
+    departed: Vec<Departed>,
+}
+
+impl Room {
+    fn broadcast(&self, message: &ServerMessage) {
+        for member in self.members.values() {
+            member.send(message.clone());
+        }
+    }
+
+    fn broadcast_except(&self, player: u32, message: &ServerMessage) {
+        for (_, member) in self.members.iter().filter(|(&id, _)| id != player) {
+            member.send(message.clone());
+        }
+    }
+}
+
+/// Every room and who is in it. Messages are queued while the lobby is
+/// borrowed, so all players in a room see events in the same order.
+pub struct Lobby {
+    difficulty: Difficulty,
+    rng: StdRng,
+    rooms: HashMap<String, Room>,
+    /// The room each player is in.
+    locations: HashMap<u32, String>,
+}
+
+impl Lobby {
+    pub fn new(difficulty: Difficulty, rng: StdRng) -> Lobby {
+        Lobby {
+            difficulty,
+            rng,
+            rooms: HashMap::new(),
+            locations: HashMap::new(),
+        }
+    }
+
+    pub fn handle(&mut self, player: u32, message: ClientMessage, outbox: &Outbox) {
+        match message {
+            ClientMessage::Join { room, name } => self.join(player, room, name, outbox.clone()),
+            ClientMessage::Guess { value } => self.guess(player, value, outbox),
+            ClientMessage::Leave => self.leave(player),
+        }
+    }
+
+    pub fn join(&mut self, player: u32, room: String, name: String, outbox: Outbox) {
+        let (room, name) = (room.trim().to_string(), name.trim().to_string());
+        if room.is_empty() || name.is_empty() {
+            let _ = outbox.send(error("room and name must not be empty"));
+            return;
+        }
+        self.leave(player);
+
+        let (difficulty, rng) = (&self.difficulty, &mut self.rng);
+        let entry = self.rooms.entry(room.clone()).or_insert_with(|| Room {
+            round_no: 1,
+            secret: Round::new(difficulty, rng).secret(),
+            members: BTreeMap::new(),
+            departed: Vec::new(),
+        });
+        let round = match entry
+            .departed
+            .iter()
+            .position(|departed| departed.player == player || departed.name == name)
+        {
+            Some(i) => entry.departed.swap_remove(i).round,
+            None => Round::with_secret(difficulty, entry.secret),
+        };
+        let member = Member {
+            name,
+            round,
+            outbox,
+        };
+        entry.broadcast(&ServerMessage::PlayerJoined {
+            player: member.info(player),
+        });
+        entry.members.insert(player, member);
+
+        let range = difficulty.range();
+        entry.members[&player].send(ServerMessage::Joined {
+            room: room.clone(),
+            round: entry.round_no,
+            low: *range.start(),
+            high: *range.end(),
+            max_attempts: difficulty.max_attempts(),
+            players: entry
+                .members
+                .iter()
+                .map(|(&id, member)| member.info(id))
+                .collect(),
+        });
+        self.locations.insert(player, room);
+    }
+
+    pub fn guess(&mut self, player: u32, value: u32, outbox: &Outbox) {
+        let Some(name) = self.locations.get(&player) else {
+            let _ = outbox.send(error("join a room first"));
+            return;
+        };
+        let name = name.clone();
+        let room = self
+            .rooms
+            .get_mut(&name)
+            .expect("located players have a room");
+        let member = room
+            .members
+            .get_mut(&player)
+            .expect("located players are members");
+
+        let hint = match member.round.guess(value) {
+            Ok(hint) => hint,
+            Err(RoundError::GameOver(_)) => {
+                member.send(error("you are out of attempts, wait for the next round"));
+                return;
+            }
+            Err(e) => {
+                member.send(error(&e.to_string()));
+                return;
+            }
+        };
+        let guesses = member.round.attempts();
+        member.send(ServerMessage::Hint {
+            value,
+            hint,
+            attempts_left: member.round.attempts_left(),
+        });
+        let winner = member.name.clone();
+        room.broadcast_except(player, &ServerMessage::Progress { player, guesses });
+
+        if hint == Hint::Correct {
+            room.broadcast(&ServerMessage::Winner {
+                player,
+                name: winner,
+                secret: room.secret,
+                guesses,
+            });
+            self.next_round(&name);
+        } else {
+            self.finish_if_everyone_lost(&name);
+        }
+    }
+
+    /// Takes `player` out of their room, if they are in one.
+    pub fn leave(&mut self, player: u32) {
+        let Some(name) = self.locations.remove(&player) else {
+            return;
+        };
+        let room = self
+            .rooms
+            .get_mut(&name)
+            .expect("located players have a room");
+        let member = room
+            .members
+            .remove(&player)
+            .expect("located players are members");
+        room.departed.push(Departed {
+            player,
+            name: member.name,
+            round: member.round,
+        });
+        if room.members.is_empty() {
+            self.rooms.remove(&name);
+            return;
+        }
+        room.broadcast(&ServerMessage::PlayerLeft { player });
+        self.finish_if_everyone_lost(&name);
+    }
+
+    fn finish_if_everyone_lost(&mut self, name: &str) {
+        let room = &self.rooms[name];
+        if room
+            .members
+            .values()
+            .all(|member| member.round.outcome().is_some())
+        {
+            room.broadcast(&ServerMessage::RoundOver {
+                secret: room.secret,
+            });
+            self.next_round(name);
+        }
+    }
+
+    fn next_round(&mut self, name: &str) {
+        let difficulty = &self.difficulty;
+        let room = self
+            .rooms
+            .get_mut(name)
+            .expect("rounds are only started in existing rooms");
+        room.round_no += 1;
+        room.secret = Round::new(difficulty, &mut self.rng).secret();
+        for member in room.members.values_mut() {
+            member.round = Round::with_secret(difficulty, room.secret);
+        }
+        room.departed.clear();
+        let range = difficulty.range();
+        room.broadcast(&ServerMessage::RoundStarted {
+            round: room.round_no,
+            low: *range.start(),
+            high: *range.end(),
+            max_attempts: difficulty.max_attempts(),
+        });
+    }
+
+    /// How many players are in `room`, or `None` if it does not exist.
+    pub fn room_size(&self, room: &str) -> Option<usize> {
+        self.rooms.get(room).map(|room| room.members.len())
+    }
+}
+
+fn error(message: &str) -> ServerMessage {
+    ServerMessage::Error {
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::{self, Receiver};
+
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn lobby(difficulty: Difficulty) -> Lobby {
+        Lobby::new(difficulty, StdRng::seed_from_u64(3))
+    }
+
+    fn player() -> (Outbox, Receiver<ServerMessage>) {
+        mpsc::channel()
+    }
+
+    fn join(lobby: &mut Lobby, id: u32, room: &str, outbox: &Outbox) {
+        lobby.handle(
+            id,
+            ClientMessage::Join {
+                room: room.to_string(),
+                name: format!("p{}", id),
+            },
+            outbox,
+        );
+    }
+
+    #[test]
+    fn the_only_number_wins_straight_away() {
+        let mut lobby = lobby(Difficulty::custom(4..=4, 3).unwrap());
+        let (a, a_inbox) = player();
+        let (b, b_inbox) = player();
+        join(&mut lobby, 1, "den", &a);
+        join(&mut lobby, 2, "den", &b);
+        lobby.handle(2, ClientMessage::Guess { value: 4 }, &b);
+
+        let a_seen: Vec<_> = a_inbox.try_iter().collect();
+        assert!(matches!(a_seen[0], ServerMessage::Joined { .. }));
+        assert!(matches!(a_seen[1], ServerMessage::PlayerJoined { .. }));
+        assert_eq!(
+            a_seen[2],
+            ServerMessage::Progress {
+                player: 2,
+                guesses: 1
+            }
+        );
+        let winner = ServerMessage::Winner {
+            player: 2,
+            name: "p2".to_string(),
+            secret: 4,
+            guesses: 1,
+        };
+        assert_eq!(a_seen[3], winner);
+        assert!(matches!(
+            a_seen[4],
+            ServerMessage::RoundStarted { round: 2, .. }
+        ));
+
+        let b_seen: Vec<_> = b_inbox.try_iter().collect();
+        assert_eq!(
+            b_seen[1],
+            ServerMessage::Hint {
+                value: 4,
+                hint: Hint::Correct,
+                attempts_left: 2
+            }
+        );
+        assert_eq!(b_seen[2], winner);
+    }
+
+    #[test]
+    fn rooms_are_separate_and_removed_when_empty() {
+        let mut lobby = lobby(Difficulty::Easy);
+        let (a, _a_inbox) = player();
+        let (b, b_inbox) = player();
+        join(&mut lobby, 1, "den", &a);
+        join(&mut lobby, 2, "attic", &b);
+        assert_eq!(lobby.room_size("den"), Some(1));
+
+        lobby.handle(1, ClientMessage::Guess { value: 5 }, &a);
+        assert_eq!(b_inbox.try_iter().count(), 1);
+
+        join(&mut lobby, 2, "den", &b);
+        assert_eq!(lobby.room_size("attic"), None);
+        assert_eq!(lobby.room_size("den"), Some(2));
+        lobby.leave(1);
+        lobby.leave(2);
+        assert_eq!(lobby.room_size("den"), None);
+    }
+
+    #[test]
+    fn rejoining_does_not_give_back_attempts() {
+        let mut lobby = lobby(Difficulty::custom(1..=100, 3).unwrap());
+        let (a, a_inbox) = player();
+        let (b, _b_inbox) = player();
+        join(&mut lobby, 1, "den", &a);
+        join(&mut lobby, 2, "den", &b);
+        let secret = lobby.rooms["den"].secret;
+        let wrong = if secret == 1 { 2 } else { 1 };
+
+        let attempts_left = |inbox: &Receiver<ServerMessage>| {
+            inbox
+                .try_iter()
+                .filter_map(|message| match message {
+                    ServerMessage::Hint { attempts_left, .. } => Some(attempts_left),
+                    _ => None,
+                })
+                .last()
+        };
+        lobby.handle(1, ClientMessage::Guess { value: wrong }, &a);
+        assert_eq!(attempts_left(&a_inbox), Some(2));
+
+        // Leaving and coming back, or joining again, keeps the count.
+        lobby.handle(1, ClientMessage::Leave, &a);
+        join(&mut lobby, 1, "den", &a);
+        lobby.handle(1, ClientMessage::Guess { value: wrong }, &a);
+        assert_eq!(attempts_left(&a_inbox), Some(1));
+        join(&mut lobby, 1, "den", &a);
+        lobby.handle(1, ClientMessage::Guess { value: wrong }, &a);
+        assert_eq!(attempts_left(&a_inbox), Some(0));
+
+        // So does a new connection under the same name.
+        lobby.leave(1);
+        let (c, c_inbox) = player();
+        lobby.handle(
+            3,
+            ClientMessage::Join {
+                room: "den".to_string(),
+                name: "p1".to_string(),
+            },
+            &c,
+        );
+        lobby.handle(3, ClientMessage::Guess { value: secret }, &c);
+        assert_eq!(
+            c_inbox.try_iter().last(),
+            Some(error("you are out of attempts, wait for the next round"))
+        );
+    }
+
+    #[test]
+    fn guesses_need_a_room() {
+        let mut lobby = lobby(Difficulty::Easy);
+        let (a, a_inbox) = player();
+        lobby.handle(1, ClientMessage::Guess { value: 5 }, &a);
+        assert_eq!(a_inbox.try_recv().unwrap(), error("join a room first"));
+    }
+}
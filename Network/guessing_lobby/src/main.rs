@@ -0,0 +1,44 @@
+use std::env;
+use std::process;
+
+use guessing_lobby::{Config, Server};
+
+const USAGE: &str = "\
+Usage: guessing_lobby [--addr <ADDR>] [--difficulty <LEVEL>]
+
+  --addr <ADDR>             address to listen on [default: 127.0.0.1:9001]
+  -d, --difficulty <LEVEL>  easy, medium or hard [default: medium]";
+
+fn main() {
+    let mut addr = "127.0.0.1:9001".to_string();
+    let mut config = Config::default();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--addr" => addr = args.next().unwrap_or_else(|| fail("--addr needs a value")),
+            "-d" | "--difficulty" => {
+                let level = args
+                    .next()
+                    .unwrap_or_else(|| fail("--difficulty needs a value"));
+                config.difficulty = level.parse().unwrap_or_else(|e: String| fail(&e));
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => fail(&format!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    let server = Server::bind(&addr, config).unwrap_or_else(|e| fail(&e.to_string()));
+    println!("lobby running on ws://{}", server.local_addr().unwrap());
+    if let Err(e) = server.run() {
+        fail(&e.to_string());
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
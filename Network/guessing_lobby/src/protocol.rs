@@ -0,0 +1,210 @@
+//! The JSON message schema. Every message is one WebSocket text frame
+//! holding an object with the schema version `v` and a `type`:
+//!
+//! ```text
+//! C: {"v":1,"type":"join","room":"den","name":"ann"}
+//! S: {"v":1,"type":"joined","room":"den","round":1,"low":1,"high":100,"max_attempts":10,"players":[...]}
+//! C: {"v":1,"type":"guess","value":50}
+//! S: {"v":1,"type":"hint","value":50,"hint":"too_big","attempts_left":9}     to the guesser
+//! S: {"v":1,"type":"progress","player":1,"guesses":1}                       to everyone else
+//! S: {"v":1,"type":"winner","player":1,"name":"ann","secret":37,"guesses":6}
+//! S: {"v":1,"type":"round_started","round":2,"low":1,"high":100,"max_attempts":10}
+//! ```
+//!
+//! A message with a version the server does not speak is answered with an
+//! `error` rather than guessed at.
+
+use std::fmt;
+
+use guessing_game::Hint;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The schema version this crate speaks.
+pub const VERSION: u64 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Enter a room, leaving any other one first. Rooms are created on
+    /// first use.
+    Join {
+        room: String,
+        name: String,
+    },
+    Guess {
+        value: u32,
+    },
+    Leave,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerInfo {
+    pub id: u32,
+    pub name: String,
+    /// Guesses made this round.
+    pub guesses: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// Sent once on connect.
+    Welcome {
+        player: u32,
+    },
+    /// You are in `room`; `players` includes you.
+    Joined {
+        room: String,
+        round: u32,
+        low: u32,
+        high: u32,
+        max_attempts: u32,
+        players: Vec<PlayerInfo>,
+    },
+    PlayerJoined {
+        player: PlayerInfo,
+    },
+    PlayerLeft {
+        player: u32,
+    },
+    /// The answer to your own guess.
+    Hint {
+        value: u32,
+        hint: Hint,
+        attempts_left: u32,
+    },
+    /// Someone else in the room guessed. Only the count is shared, never
+    /// the value.
+    Progress {
+        player: u32,
+        guesses: u32,
+    },
+    Winner {
+        player: u32,
+        name: String,
+        secret: u32,
+        guesses: u32,
+    },
+    /// Everyone in the room ran out of attempts.
+    RoundOver {
+        secret: u32,
+    },
+    RoundStarted {
+        round: u32,
+        low: u32,
+        high: u32,
+        max_attempts: u32,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// Why a frame could not be read as a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaError {
+    Malformed(String),
+    MissingVersion,
+    UnsupportedVersion(u64),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::Malformed(reason) => write!(f, "malformed message: {}", reason),
+            SchemaError::MissingVersion => write!(f, "message has no schema version 'v'"),
+            SchemaError::UnsupportedVersion(v) => write!(
+                f,
+                "unsupported schema version {}, this server speaks version {}",
+                v, VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// Serializes `message` with the current version stamped on it.
+pub fn encode<T: Serialize>(message: &T) -> String {
+    let mut value = serde_json::to_value(message).expect("messages always serialize");
+    if let Value::Object(fields) = &mut value {
+        fields.insert("v".to_string(), VERSION.into());
+    }
+    value.to_string()
+}
+
+/// Parses a message, checking its version before anything else.
+pub fn decode<T: DeserializeOwned>(text: &str) -> Result<T, SchemaError> {
+    let mut value: Value =
+        serde_json::from_str(text).map_err(|e| SchemaError::Malformed(e.to_string()))?;
+    let fields = value
+        .as_object_mut()
+        .ok_or_else(|| SchemaError::Malformed("expected a JSON object".to_string()))?;
+    match fields.remove("v").as_ref().map(Value::as_u64) {
+        None => return Err(SchemaError::MissingVersion),
+        Some(Some(VERSION)) => {}
+        Some(Some(v)) => return Err(SchemaError::UnsupportedVersion(v)),
+        Some(None) => return Err(SchemaError::Malformed("'v' must be a number".to_string())),
+    }
+    serde_json::from_value(value).map_err(|e| SchemaError::Malformed(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stamps_and_checks_the_version() {
+        let text = encode(&ClientMessage::Guess { value: 50 });
+        let value: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(value["v"], 1);
+        assert_eq!(value["type"], "guess");
+        assert_eq!(decode(&text), Ok(ClientMessage::Guess { value: 50 }));
+    }
+
+    #[test]
+    fn server_messages_round_trip() {
+        let messages = [
+            ServerMessage::Hint {
+                value: 3,
+                hint: Hint::TooSmall,
+                attempts_left: 4,
+            },
+            ServerMessage::Progress {
+                player: 2,
+                guesses: 5,
+            },
+            ServerMessage::Winner {
+                player: 1,
+                name: "ann".to_string(),
+                secret: 42,
+                guesses: 6,
+            },
+        ];
+        for message in messages {
+            assert_eq!(decode(&encode(&message)), Ok(message));
+        }
+    }
+
+    #[test]
+    fn rejects_bad_frames() {
+        assert_eq!(
+            decode::<ClientMessage>(r#"{"type":"leave"}"#),
+            Err(SchemaError::MissingVersion)
+        );
+        assert_eq!(
+            decode::<ClientMessage>(r#"{"v":2,"type":"leave"}"#),
+            Err(SchemaError::UnsupportedVersion(2))
+        );
+        assert!(matches!(
+            decode::<ClientMessage>(r#"{"v":1,"type":"dance"}"#),
+            Err(SchemaError::Malformed(_))
+        ));
+        assert!(matches!(
+            decode::<ClientMessage>("[1]"),
+            Err(SchemaError::Malformed(_))
+        ));
+    }
+}
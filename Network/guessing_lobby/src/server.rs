@@ -0,0 +1,122 @@
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use guessing_game::Difficulty;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use tungstenite::{Message, WebSocket};
+
+use crate::lobby::{Lobby, Outbox};
+use crate::protocol::{decode, encode, ClientMessage, ServerMessage};
+
+/// How long a connection's thread waits for a frame before checking its
+/// outbox for broadcasts.
+const POLL: Duration = Duration::from_millis(20);
+
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    pub difficulty: Difficulty,
+}
+
+/// Serves the lobby over WebSockets, one thread per connection.
+pub struct Server {
+    listener: TcpListener,
+    lobby: Arc<Mutex<Lobby>>,
+    next_player: AtomicU32,
+}
+
+impl Server {
+    pub fn bind<A: ToSocketAddrs>(addr: A, config: Config) -> io::Result<Server> {
+        let listener = TcpListener::bind(addr)?;
+        let lobby = Lobby::new(config.difficulty, StdRng::from_entropy());
+        Ok(Server {
+            listener,
+            lobby: Arc::new(Mutex::new(lobby)),
+            next_player: AtomicU32::new(1),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts connections until the listener fails.
+    pub fn run(&self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let player = self.next_player.fetch_add(1, Ordering::Relaxed);
+            let lobby = Arc::clone(&self.lobby);
+
+            thread::spawn(move || {
+                if let Err(e) = handle(stream, player, &lobby) {
+                    eprintln!("player {}: {}", player, e);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Runs the server on a background thread, e.g. for tests.
+    pub fn spawn(self) -> io::Result<SocketAddr> {
+        let addr = self.local_addr()?;
+        thread::spawn(move || self.run());
+        Ok(addr)
+    }
+}
+
+fn handle(stream: TcpStream, player: u32, lobby: &Mutex<Lobby>) -> tungstenite::Result<()> {
+    let mut ws = tungstenite::accept(stream).map_err(|e| match e {
+        tungstenite::HandshakeError::Failure(e) => e,
+        tungstenite::HandshakeError::Interrupted(_) => unreachable!("the stream is blocking"),
+    })?;
+    ws.get_ref().set_read_timeout(Some(POLL))?;
+
+    let (outbox, inbox) = mpsc::channel();
+    let _ = outbox.send(ServerMessage::Welcome { player });
+    let result = serve(&mut ws, player, lobby, &outbox, &inbox);
+    lobby.lock().unwrap().leave(player);
+    result
+}
+
+fn serve(
+    ws: &mut WebSocket<TcpStream>,
+    player: u32,
+    lobby: &Mutex<Lobby>,
+    outbox: &Outbox,
+    inbox: &Receiver<ServerMessage>,
+) -> tungstenite::Result<()> {
+    loop {
+        for message in inbox.try_iter() {
+            ws.send(Message::text(encode(&message)))?;
+        }
+
+        let text = match ws.read() {
+            Ok(Message::Text(text)) => text,
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(e))
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                continue
+            }
+            Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        match decode::<ClientMessage>(text.as_str()) {
+            Ok(message) => lobby.lock().unwrap().handle(player, message, outbox),
+            Err(e) => {
+                let _ = outbox.send(ServerMessage::Error {
+                    message: e.to_string(),
+                });
+            }
+        }
+    }
+}
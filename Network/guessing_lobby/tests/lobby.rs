@@ -0,0 +1,149 @@
+use guessing_game::{Difficulty, Hint};
+use guessing_lobby::{Client, ClientMessage, Config, PlayerInfo, Server, ServerMessage};
+
+fn spawn(difficulty: Difficulty) -> String {
+    Server::bind("127.0.0.1:0", Config { difficulty })
+        .unwrap()
+        .spawn()
+        .unwrap()
+        .to_string()
+}
+
+/// Connects and returns the client with its player id.
+fn connect(addr: &str) -> (Client, u32) {
+    let mut client = Client::connect(addr).unwrap();
+    match client.recv().unwrap() {
+        ServerMessage::Welcome { player } => (client, player),
+        other => panic!("expected welcome, got {:?}", other),
+    }
+}
+
+fn join(client: &mut Client, room: &str, name: &str) -> ServerMessage {
+    client
+        .send(&ClientMessage::Join {
+            room: room.to_string(),
+            name: name.to_string(),
+        })
+        .unwrap();
+    client.recv().unwrap()
+}
+
+#[test]
+fn players_see_each_others_progress_and_the_winner() {
+    let addr = spawn(Difficulty::Medium);
+    let (mut ann, ann_id) = connect(&addr);
+    let (mut bob, bob_id) = connect(&addr);
+
+    join(&mut ann, "den", "ann");
+    let joined = join(&mut bob, "den", "bob");
+    let ServerMessage::Joined {
+        round: 1,
+        low,
+        high,
+        players,
+        ..
+    } = joined
+    else {
+        panic!("expected joined, got {:?}", joined);
+    };
+    assert_eq!(
+        players.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(),
+        ["ann", "bob"]
+    );
+    assert_eq!(
+        ann.recv().unwrap(),
+        ServerMessage::PlayerJoined {
+            player: PlayerInfo {
+                id: bob_id,
+                name: "bob".to_string(),
+                guesses: 0
+            }
+        }
+    );
+
+    // Ann binary-searches; Bob only ever learns how many guesses she made.
+    let (mut low, mut high, mut guesses) = (low, high, 0);
+    let secret = loop {
+        let value = low + (high - low) / 2;
+        ann.send(&ClientMessage::Guess { value }).unwrap();
+        guesses += 1;
+        let ServerMessage::Hint { hint, .. } = ann.recv().unwrap() else {
+            panic!("expected a hint");
+        };
+        assert_eq!(
+            bob.recv().unwrap(),
+            ServerMessage::Progress {
+                player: ann_id,
+                guesses
+            }
+        );
+        match hint {
+            Hint::TooSmall => low = value + 1,
+            Hint::TooBig => high = value - 1,
+            Hint::Correct => break value,
+        }
+    };
+
+    let winner = ServerMessage::Winner {
+        player: ann_id,
+        name: "ann".to_string(),
+        secret,
+        guesses,
+    };
+    for client in [&mut ann, &mut bob] {
+        assert_eq!(client.recv().unwrap(), winner);
+        assert!(matches!(
+            client.recv().unwrap(),
+            ServerMessage::RoundStarted { round: 2, .. }
+        ));
+    }
+
+    bob.close().unwrap();
+    assert_eq!(
+        ann.recv().unwrap(),
+        ServerMessage::PlayerLeft { player: bob_id }
+    );
+}
+
+#[test]
+fn rooms_do_not_hear_each_other() {
+    let addr = spawn(Difficulty::Easy);
+    let (mut ann, _) = connect(&addr);
+    let (mut cat, _) = connect(&addr);
+    join(&mut ann, "den", "ann");
+    join(&mut cat, "attic", "cat");
+
+    ann.send(&ClientMessage::Guess { value: 5 }).unwrap();
+    assert!(matches!(ann.recv().unwrap(), ServerMessage::Hint { .. }));
+
+    // Had Ann's progress reached the attic, it would arrive first.
+    cat.send(&ClientMessage::Guess { value: 5 }).unwrap();
+    assert!(matches!(
+        cat.recv().unwrap(),
+        ServerMessage::Hint { value: 5, .. }
+    ));
+}
+
+fn expect_error(client: &mut Client, containing: &str) {
+    match client.recv().unwrap() {
+        ServerMessage::Error { message } => assert!(message.contains(containing), "{}", message),
+        other => panic!("expected an error, got {:?}", other),
+    }
+}
+
+#[test]
+fn bad_messages_get_errors() {
+    let addr = spawn(Difficulty::Easy);
+    let (mut ann, _) = connect(&addr);
+
+    ann.send_text(r#"{"v":2,"type":"leave"}"#).unwrap();
+    expect_error(&mut ann, "unsupported schema version 2");
+    ann.send_text("not json").unwrap();
+    expect_error(&mut ann, "malformed");
+    ann.send(&ClientMessage::Guess { value: 1 }).unwrap();
+    expect_error(&mut ann, "join a room first");
+
+    join(&mut ann, "den", "ann");
+    ann.send(&ClientMessage::Guess { value: 11 }).unwrap();
+    expect_error(&mut ann, "out of range");
+}
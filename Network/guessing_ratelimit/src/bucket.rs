@@ -0,0 +1,118 @@
+use std::time::{Duration, Instant};
+
+/// How fast one client may call one route: a bucket of `capacity` tokens
+/// that gains a token every `interval`. Each request takes a token, so a
+/// client can burst up to the capacity and then keeps the refill pace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limit {
+    pub capacity: u32,
+    pub interval: Duration,
+}
+
+impl Limit {
+    /// `n` requests a second, all of which may come at once.
+    pub fn per_second(n: u32) -> Limit {
+        Limit::spread(n, Duration::from_secs(1))
+    }
+
+    /// `n` requests a minute, all of which may come at once.
+    pub fn per_minute(n: u32) -> Limit {
+        Limit::spread(n, Duration::from_secs(60))
+    }
+
+    fn spread(n: u32, over: Duration) -> Limit {
+        assert!(n > 0, "a limit must allow at least one request");
+        Limit {
+            capacity: n,
+            interval: over / n,
+        }
+    }
+
+    /// The same pace, but at most `burst` requests back to back.
+    pub fn with_burst(self, burst: u32) -> Limit {
+        assert!(burst > 0, "a limit must allow at least one request");
+        Limit {
+            capacity: burst,
+            ..self
+        }
+    }
+}
+
+/// The tokens one client has left on one route.
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    limit: Limit,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// A full bucket.
+    pub fn new(limit: Limit, now: Instant) -> TokenBucket {
+        TokenBucket {
+            limit,
+            tokens: f64::from(limit.capacity),
+            updated: now,
+        }
+    }
+
+    /// Takes a token, or says how long until one is available.
+    pub fn try_take(&mut self, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+        Err(self.limit.interval.mul_f64(1.0 - self.tokens))
+    }
+
+    /// Whether the bucket has refilled completely, so forgetting it would
+    /// change nothing.
+    pub fn is_full(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.tokens >= f64::from(self.limit.capacity)
+    }
+
+    fn refill(&mut self, now: Instant) {
+        // A clock that went backwards adds nothing.
+        let elapsed = now.saturating_duration_since(self.updated);
+        let gained = elapsed.as_secs_f64() / self.limit.interval.as_secs_f64();
+        self.tokens = (self.tokens + gained).min(f64::from(self.limit.capacity));
+        self.updated = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bursts_then_keeps_the_pace() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(Limit::per_second(2).with_burst(3), start);
+        for _ in 0..3 {
+            assert_eq!(bucket.try_take(start), Ok(()));
+        }
+        assert_eq!(bucket.try_take(start), Err(Duration::from_millis(500)));
+
+        let later = start + Duration::from_millis(200);
+        assert_eq!(bucket.try_take(later), Err(Duration::from_millis(300)));
+        let later = start + Duration::from_millis(500);
+        assert_eq!(bucket.try_take(later), Ok(()));
+        assert_eq!(bucket.try_take(later), Err(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn never_holds_more_than_its_capacity() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(Limit::per_minute(2), start);
+        bucket.try_take(start).unwrap();
+        assert!(!bucket.is_full(start));
+
+        let much_later = start + Duration::from_secs(3600);
+        assert!(bucket.is_full(much_later));
+        assert_eq!(bucket.try_take(much_later), Ok(()));
+        assert_eq!(bucket.try_take(much_later), Ok(()));
+        assert_eq!(bucket.try_take(much_later), Err(Duration::from_secs(30)));
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Mutex;
+
+use guessing_game::{Difficulty, Hint, Round, RoundError};
+use hyper::body::HttpBody;
+use hyper::header::{HeaderValue, CONTENT_TYPE, LOCATION};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+/// The most a request body may hold. A guess is a few bytes of JSON, so
+/// anything much bigger is refused before it is read into memory.
+const MAX_BODY: usize = 1024;
+
+/// The body of `POST /games/{id}/guesses`.
+#[derive(Debug, Deserialize)]
+struct NewGuess {
+    guess: u32,
+}
+
+/// The games being played, all at one difficulty.
+#[derive(Debug, Default)]
+pub struct Games {
+    difficulty: Difficulty,
+    rounds: Mutex<HashMap<Uuid, Round>>,
+}
+
+impl Games {
+    pub fn new(difficulty: Difficulty) -> Games {
+        Games {
+            difficulty,
+            rounds: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Starts a game with a known secret, e.g. for tests.
+    pub fn start_with_secret(&self, secret: u32) -> Uuid {
+        let id = Uuid::new_v4();
+        let round = Round::with_secret(&self.difficulty, secret);
+        self.rounds.lock().unwrap().insert(id, round);
+        id
+    }
+
+    /// Serves `POST /games` and `POST /games/{id}/guesses`.
+    pub async fn handle(&self, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+        let path: Vec<&str> = req.uri().path().trim_matches('/').split('/').collect();
+        let response = match (req.method(), path.as_slice()) {
+            (&Method::POST, ["games"]) => self.create(),
+            (&Method::POST, ["games", id, "guesses"]) => match id.parse() {
+                Ok(id) => match read_body(req.into_body()).await {
+                    Some(body) => self.guess(id, &body),
+                    None => error(
+                        StatusCode::PAYLOAD_TOO_LARGE,
+                        &format!("the body may be at most {} bytes", MAX_BODY),
+                    ),
+                },
+                Err(_) => error(StatusCode::NOT_FOUND, "no such game"),
+            },
+            _ => error(StatusCode::NOT_FOUND, "no such route"),
+        };
+        Ok(response)
+    }
+
+    fn create(&self) -> Response<Body> {
+        let round = Round::new(&self.difficulty, &mut rand::thread_rng());
+        let range = round.range();
+        let id = Uuid::new_v4();
+        let body = json!({
+            "id": id,
+            "low": range.start(),
+            "high": range.end(),
+            "max_attempts": round.max_attempts(),
+        });
+        self.rounds.lock().unwrap().insert(id, round);
+
+        let mut response = reply(StatusCode::CREATED, body);
+        let location = HeaderValue::from_str(&format!("/games/{}", id)).unwrap();
+        response.headers_mut().insert(LOCATION, location);
+        response
+    }
+
+    fn guess(&self, id: Uuid, body: &[u8]) -> Response<Body> {
+        let guess = match serde_json::from_slice::<NewGuess>(body) {
+            Ok(body) => body.guess,
+            Err(e) => return error(StatusCode::BAD_REQUEST, &format!("bad request: {}", e)),
+        };
+        let mut rounds = self.rounds.lock().unwrap();
+        let Some(round) = rounds.get_mut(&id) else {
+            return error(StatusCode::NOT_FOUND, "no such game");
+        };
+        let result = match round.guess(guess) {
+            Ok(Hint::TooSmall) => "too_low",
+            Ok(Hint::TooBig) => "too_high",
+            Ok(Hint::Correct) => "correct",
+            Err(e @ RoundError::GameOver(_)) => return error(StatusCode::CONFLICT, &e.to_string()),
+            Err(e) => return error(StatusCode::UNPROCESSABLE_ENTITY, &e.to_string()),
+        };
+        let mut body = json!({
+            "result": result,
+            "attempts": round.attempts(),
+            "attempts_left": round.attempts_left(),
+        });
+        if round.outcome().is_some() {
+            body["secret"] = json!(round.secret());
+        }
+        reply(StatusCode::OK, body)
+    }
+}
+
+/// Reads a body of up to [`MAX_BODY`] bytes, or `None` if it is longer. A
+/// body that fails to arrive in full is cut short, and then fails to parse.
+async fn read_body(mut body: Body) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    while let Some(Ok(chunk)) = body.data().await {
+        if bytes.len() + chunk.len() > MAX_BODY {
+            return None;
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Some(bytes)
+}
+
+fn reply(status: StatusCode, body: Value) -> Response<Body> {
+    let mut response = Response::new(Body::from(body.to_string()));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+fn error(status: StatusCode, message: &str) -> Response<Body> {
+    reply(status, json!({ "error": message }))
+}
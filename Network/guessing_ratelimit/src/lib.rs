@@ -0,0 +1,70 @@
+//! The "Rate Limiting with Tower" example from `Network.md`, grown into a
+//! guess endpoint that scripts cannot hammer to brute-force the secret.
+//!
+//! tower's own `RateLimitLayer` shares one budget between every caller.
+//! [`PerClientLimitLayer`] keeps a token bucket per client instead, keyed
+//! by the authenticated [`Player`] if there is one and by IP address
+//! otherwise, with a [`Limit`] per route. A client over its limit gets
+//! `429 Too Many Requests` and a `Retry-After` header.
+//!
+//! ```text
+//! POST /games                 -> 201 {"id": ..., "low": 1, "high": 100, "max_attempts": 10}
+//! POST /games/{id}/guesses    {"guess": 50}  -> {"result": "too_low", ...}
+//! ```
+
+pub mod bucket;
+pub mod clock;
+pub mod games;
+pub mod limiter;
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::TcpListener;
+use std::sync::Arc;
+
+use hyper::server::conn::AddrStream;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use tower::util::BoxCloneService;
+use tower::{ServiceBuilder, ServiceExt};
+
+pub use bucket::{Limit, TokenBucket};
+pub use clock::{Clock, MockClock, SystemClock};
+pub use games::Games;
+pub use limiter::{ClientKey, Limits, PerClientLimit, PerClientLimitLayer, Player, RemoteAddr};
+
+/// The whole service, limiter included.
+pub type App = BoxCloneService<Request<Body>, Response<Body>, Infallible>;
+
+/// Puts `games` behind the limiter.
+pub fn app(games: Arc<Games>, limits: Limits, clock: impl Clock + 'static) -> App {
+    let service = ServiceBuilder::new()
+        .layer(PerClientLimitLayer::new(limits, clock))
+        .service(service_fn(move |req| {
+            let games = Arc::clone(&games);
+            async move { games.handle(req).await }
+        }));
+    BoxCloneService::new(service)
+}
+
+/// Serves `app` on `listener` until `shutdown` completes. Each request is
+/// tagged with the [`RemoteAddr`] of its connection before the limiter
+/// sees it.
+pub async fn serve(
+    listener: TcpListener,
+    app: App,
+    shutdown: impl Future<Output = ()>,
+) -> hyper::Result<()> {
+    let make_svc = make_service_fn(move |conn: &AddrStream| {
+        let remote = RemoteAddr(conn.remote_addr());
+        let svc = app.clone().map_request(move |mut req: Request<Body>| {
+            req.extensions_mut().insert(remote);
+            req
+        });
+        async move { Ok::<_, Infallible>(svc) }
+    });
+    Server::from_tcp(listener)?
+        .serve(make_svc)
+        .with_graceful_shutdown(shutdown)
+        .await
+}
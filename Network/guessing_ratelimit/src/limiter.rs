@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use hyper::header::{HeaderValue, CONTENT_TYPE, RETRY_AFTER};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use serde_json::json;
+use tower::{Layer, Service};
+
+use crate::bucket::{Limit, TokenBucket};
+use crate::clock::Clock;
+
+/// How often buckets that have refilled are dropped, so clients that went
+/// away do not hold memory forever. Pruning walks every bucket, so it runs
+/// on this timer rather than on every request.
+const PRUNE_EVERY: Duration = Duration::from_secs(60);
+
+/// How many buckets are kept by default; see [`Limits::max_clients`].
+const MAX_CLIENTS: usize = 100_000;
+
+/// An authenticated player, put in the request extensions by whatever
+/// checked their credentials. Requests without one are limited by address.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Player(pub String);
+
+/// The peer address of the connection, put in the request extensions by
+/// the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemoteAddr(pub SocketAddr);
+
+/// Who a bucket belongs to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClientKey {
+    Player(String),
+    Ip(IpAddr),
+    /// Neither a player nor an address; such requests share one bucket.
+    Unknown,
+}
+
+impl ClientKey {
+    pub fn of<B>(req: &Request<B>) -> ClientKey {
+        if let Some(Player(name)) = req.extensions().get() {
+            return ClientKey::Player(name.clone());
+        }
+        match req.extensions().get() {
+            Some(RemoteAddr(addr)) => ClientKey::Ip(addr.ip()),
+            None => ClientKey::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Literal(String),
+    /// `{name}` matches any one segment.
+    Any,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    method: Method,
+    pattern: Vec<Segment>,
+    limit: Limit,
+}
+
+impl Rule {
+    fn matches(&self, method: &Method, path: &str) -> bool {
+        let mut segments = path.trim_matches('/').split('/');
+        *method == self.method
+            && self
+                .pattern
+                .iter()
+                .all(|want| match (want, segments.next()) {
+                    (Segment::Literal(want), Some(got)) => want == got,
+                    (Segment::Any, Some(got)) => !got.is_empty(),
+                    (_, None) => false,
+                })
+            && segments.next().is_none()
+    }
+}
+
+/// Which routes are limited and how. Routes that match no rule are not
+/// limited at all.
+///
+/// ```
+/// use guessing_ratelimit::{Limit, Limits};
+/// use hyper::Method;
+///
+/// let limits = Limits::new()
+///     .route(Method::POST, "/games/{id}/guesses", Limit::per_second(2).with_burst(5))
+///     .route(Method::POST, "/games", Limit::per_minute(10));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Limits {
+    rules: Vec<Rule>,
+    max_clients: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits {
+            rules: Vec::new(),
+            max_clients: MAX_CLIENTS,
+        }
+    }
+}
+
+impl Limits {
+    pub fn new() -> Limits {
+        Limits::default()
+    }
+
+    /// Caps the buckets kept at once, one per client and rule. While the
+    /// cap is reached, clients without a bucket are turned away until the
+    /// next prune makes room; clients that have one are unaffected.
+    pub fn max_clients(mut self, max: usize) -> Limits {
+        self.max_clients = max;
+        self
+    }
+
+    /// Limits `method` requests to paths matching `pattern`, where a
+    /// `{name}` segment matches anything. The first matching rule wins.
+    pub fn route(mut self, method: Method, pattern: &str, limit: Limit) -> Limits {
+        let pattern = pattern
+            .trim_matches('/')
+            .split('/')
+            .map(|segment| {
+                if segment.starts_with('{') && segment.ends_with('}') {
+                    Segment::Any
+                } else {
+                    Segment::Literal(segment.to_string())
+                }
+            })
+            .collect();
+        self.rules.push(Rule {
+            method,
+            pattern,
+            limit,
+        });
+        self
+    }
+
+    /// The index and limit of the first rule for this request.
+    fn find(&self, method: &Method, path: &str) -> Option<(usize, Limit)> {
+        self.rules
+            .iter()
+            .position(|rule| rule.matches(method, path))
+            .map(|i| (i, self.rules[i].limit))
+    }
+}
+
+/// The token buckets, one per client and rule, shared by every clone of
+/// the service.
+struct Buckets {
+    limits: Limits,
+    clock: Arc<dyn Clock>,
+    table: Mutex<Table>,
+}
+
+struct Table {
+    buckets: HashMap<(usize, ClientKey), TokenBucket>,
+    pruned_at: Instant,
+}
+
+impl Buckets {
+    /// `Ok` if the request may go ahead, or how long the client should
+    /// wait.
+    fn check<B>(&self, req: &Request<B>) -> Result<(), Duration> {
+        let Some((rule, limit)) = self.limits.find(req.method(), req.uri().path()) else {
+            return Ok(());
+        };
+        let now = self.clock.now();
+        let mut table = self.table.lock().unwrap();
+        if now.saturating_duration_since(table.pruned_at) >= PRUNE_EVERY {
+            table.buckets.retain(|_, bucket| !bucket.is_full(now));
+            table.pruned_at = now;
+        }
+
+        let key = (rule, ClientKey::of(req));
+        if !table.buckets.contains_key(&key) && table.buckets.len() >= self.limits.max_clients {
+            // No room for another client until the next prune.
+            return Err(table.pruned_at + PRUNE_EVERY - now);
+        }
+        table
+            .buckets
+            .entry(key)
+            .or_insert_with(|| TokenBucket::new(limit, now))
+            .try_take(now)
+    }
+}
+
+/// Wraps a service in [`PerClientLimit`].
+#[derive(Clone)]
+pub struct PerClientLimitLayer {
+    buckets: Arc<Buckets>,
+}
+
+impl PerClientLimitLayer {
+    pub fn new(limits: Limits, clock: impl Clock + 'static) -> PerClientLimitLayer {
+        let now = clock.now();
+        PerClientLimitLayer {
+            buckets: Arc::new(Buckets {
+                limits,
+                clock: Arc::new(clock),
+                table: Mutex::new(Table {
+                    buckets: HashMap::new(),
+                    pruned_at: now,
+                }),
+            }),
+        }
+    }
+}
+
+impl<S> Layer<S> for PerClientLimitLayer {
+    type Service = PerClientLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        PerClientLimit {
+            inner,
+            buckets: Arc::clone(&self.buckets),
+        }
+    }
+}
+
+/// Answers `429 Too Many Requests` with a `Retry-After` header once a
+/// client has used up its tokens for a route.
+///
+/// Unlike tower's `RateLimit`, which makes every caller wait for one shared
+/// budget, each client gets its own budget and is turned away rather than
+/// queued, so one script hammering the guess route cannot slow anyone
+/// else down.
+#[derive(Clone)]
+pub struct PerClientLimit<S> {
+    inner: S,
+    buckets: Arc<Buckets>,
+}
+
+impl<S> Service<Request<Body>> for PerClientLimit<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>>,
+    S::Future: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response<Body>, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        match self.buckets.check(&req) {
+            Ok(()) => Box::pin(self.inner.call(req)),
+            Err(wait) => {
+                let response = too_many_requests(wait);
+                Box::pin(async move { Ok(response) })
+            }
+        }
+    }
+}
+
+fn too_many_requests(wait: Duration) -> Response<Body> {
+    // Retry-After is in whole seconds; rounding down would invite a retry
+    // that is refused again.
+    let seconds = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+    let body = json!({
+        "error": format!("too many requests, try again in {} s", seconds),
+    });
+    let mut response = Response::new(Body::from(body.to_string()));
+    *response.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+    let headers = response.headers_mut();
+    headers.insert(RETRY_AFTER, HeaderValue::from(seconds));
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    #[test]
+    fn caps_the_buckets_and_prunes_them_on_a_timer() {
+        let clock = MockClock::new();
+        let limits = Limits::new()
+            .route(Method::POST, "/games", Limit::per_second(1))
+            .max_clients(2);
+        let layer = PerClientLimitLayer::new(limits, clock.clone());
+        let check = |player: &str| {
+            let mut req = Request::post("/games").body(()).unwrap();
+            req.extensions_mut().insert(Player(player.to_string()));
+            layer.buckets.check(&req)
+        };
+        let buckets = || layer.buckets.table.lock().unwrap().buckets.len();
+
+        assert_eq!(check("ann"), Ok(()));
+        assert_eq!(check("bob"), Ok(()));
+        assert_eq!(check("cat"), Err(PRUNE_EVERY));
+        // Clients that have a bucket keep being served.
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(check("ann"), Ok(()));
+        assert_eq!(buckets(), 2);
+
+        // Refilled buckets go at the next prune, which makes room.
+        clock.advance(PRUNE_EVERY);
+        assert_eq!(check("cat"), Ok(()));
+        assert_eq!(buckets(), 1);
+    }
+
+    #[test]
+    fn matches_routes_by_method_and_segments() {
+        let limits = Limits::new()
+            .route(Method::POST, "/games/{id}/guesses", Limit::per_second(1))
+            .route(Method::POST, "/games", Limit::per_second(2));
+        let find = |method: Method, path: &str| limits.find(&method, path).map(|(i, _)| i);
+
+        assert_eq!(find(Method::POST, "/games/abc/guesses"), Some(0));
+        assert_eq!(find(Method::POST, "/games/abc/guesses/"), Some(0));
+        assert_eq!(find(Method::POST, "/games"), Some(1));
+        assert_eq!(find(Method::GET, "/games/abc/guesses"), None);
+        assert_eq!(find(Method::POST, "/games//guesses"), None);
+        assert_eq!(find(Method::POST, "/games/abc/guesses/more"), None);
+        assert_eq!(find(Method::POST, "/"), None);
+    }
+}
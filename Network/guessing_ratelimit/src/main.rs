@@ -0,0 +1,81 @@
+use std::env;
+use std::net::TcpListener;
+use std::process;
+use std::sync::Arc;
+
+use guessing_game::Difficulty;
+use guessing_ratelimit::{app, serve, Games, Limit, Limits, SystemClock};
+use hyper::Method;
+
+const USAGE: &str = "\
+Usage: guessing_ratelimit [--addr <ADDR>] [--difficulty <LEVEL>]
+                          [--guesses-per-minute <N>] [--guess-burst <N>]
+
+  --addr <ADDR>               address to listen on [default: 127.0.0.1:3000]
+  -d, --difficulty <LEVEL>    easy, medium or hard [default: medium]
+  --guesses-per-minute <N>    guesses each client may make a minute [default: 30]
+  --guess-burst <N>           guesses allowed back to back [default: 10]";
+
+#[tokio::main]
+async fn main() {
+    let mut addr = "127.0.0.1:3000".to_string();
+    let mut difficulty = Difficulty::default();
+    let mut per_minute = 30;
+    let mut burst = 10;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .unwrap_or_else(|| fail(&format!("{} needs a value", arg)))
+        };
+        match arg.as_str() {
+            "--addr" => addr = value(),
+            "-d" | "--difficulty" => {
+                difficulty = value().parse().unwrap_or_else(|e: String| fail(&e))
+            }
+            "--guesses-per-minute" => per_minute = positive(&arg, &value()),
+            "--guess-burst" => burst = positive(&arg, &value()),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => fail(&format!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    let limits = Limits::new()
+        .route(
+            Method::POST,
+            "/games/{id}/guesses",
+            Limit::per_minute(per_minute).with_burst(burst),
+        )
+        .route(Method::POST, "/games", Limit::per_minute(20).with_burst(5));
+    let listener = TcpListener::bind(&addr).unwrap_or_else(|e| fail(&e.to_string()));
+    println!(
+        "guess API on http://{}, {} guesses a minute per client (bursts of {})",
+        listener.local_addr().unwrap(),
+        per_minute,
+        burst
+    );
+
+    let games = Arc::new(Games::new(difficulty));
+    let shutdown = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    if let Err(e) = serve(listener, app(games, limits, SystemClock), shutdown).await {
+        fail(&e.to_string());
+    }
+}
+
+fn positive(flag: &str, value: &str) -> u32 {
+    match value.parse() {
+        Ok(n) if n > 0 => n,
+        _ => fail(&format!("invalid value '{}' for {}", value, flag)),
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
@@ -0,0 +1,194 @@
+use std::net::{SocketAddr, TcpListener};
+use std::sync::Arc;
+use std::time::Duration;
+
+use guessing_game::Difficulty;
+use guessing_ratelimit::{app, serve, App, Games, Limit, Limits, MockClock, Player, RemoteAddr};
+use hyper::header::RETRY_AFTER;
+use hyper::{Body, Method, Request, Response, StatusCode};
+use serde_json::Value;
+use tokio::sync::oneshot;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+/// Games with secret 50 behind three guesses a second, bursts of two.
+struct Setup {
+    games: Arc<Games>,
+    clock: MockClock,
+    app: App,
+}
+
+impl Setup {
+    fn new() -> Setup {
+        let games = Arc::new(Games::new(Difficulty::Hard));
+        let clock = MockClock::new();
+        let limits = Limits::new().route(
+            Method::POST,
+            "/games/{id}/guesses",
+            Limit::per_second(3).with_burst(2),
+        );
+        let app = app(Arc::clone(&games), limits, clock.clone());
+        Setup { games, clock, app }
+    }
+
+    async fn guess(&self, game: Uuid, from: &str, player: Option<&str>) -> Response<Body> {
+        let mut req = Request::post(format!("/games/{}/guesses", game))
+            .body(Body::from(r#"{"guess": 10}"#))
+            .unwrap();
+        req.extensions_mut()
+            .insert(RemoteAddr(format!("{}:4000", from).parse().unwrap()));
+        if let Some(name) = player {
+            req.extensions_mut().insert(Player(name.to_string()));
+        }
+        self.app.clone().oneshot(req).await.unwrap()
+    }
+
+    async fn status(&self, game: Uuid, from: &str, player: Option<&str>) -> StatusCode {
+        self.guess(game, from, player).await.status()
+    }
+}
+
+async fn json(response: Response<Body>) -> Value {
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn refuses_guesses_beyond_the_limit_until_tokens_refill() {
+    let setup = Setup::new();
+    let game = setup.games.start_with_secret(50);
+    let ip = "10.0.0.1";
+
+    assert_eq!(setup.status(game, ip, None).await, StatusCode::OK);
+    assert_eq!(setup.status(game, ip, None).await, StatusCode::OK);
+    let refused = setup.guess(game, ip, None).await;
+    assert_eq!(refused.status(), StatusCode::TOO_MANY_REQUESTS);
+    // A third of a second rounds up to a whole one.
+    assert_eq!(refused.headers().get(RETRY_AFTER).unwrap(), "1");
+    assert_eq!(
+        json(refused).await["error"],
+        "too many requests, try again in 1 s"
+    );
+
+    setup.clock.advance(Duration::from_millis(200));
+    assert_eq!(
+        setup.status(game, ip, None).await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    setup.clock.advance(Duration::from_millis(200));
+    let allowed = setup.guess(game, ip, None).await;
+    assert_eq!(allowed.status(), StatusCode::OK);
+    // Refused guesses never reached the game, so they cost no attempts.
+    assert_eq!(json(allowed).await["attempts"], 3);
+}
+
+#[tokio::test]
+async fn keeps_a_budget_per_address_and_per_player() {
+    let setup = Setup::new();
+    let game = setup.games.start_with_secret(50);
+
+    for _ in 0..2 {
+        assert_eq!(setup.status(game, "10.0.0.1", None).await, StatusCode::OK);
+    }
+    assert_eq!(
+        setup.status(game, "10.0.0.1", None).await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    assert_eq!(setup.status(game, "10.0.0.2", None).await, StatusCode::OK);
+
+    // Players behind the same address are told apart, and a player keeps
+    // their budget when their address changes.
+    for _ in 0..2 {
+        assert_eq!(
+            setup.status(game, "10.0.0.1", Some("ann")).await,
+            StatusCode::OK
+        );
+    }
+    assert_eq!(
+        setup.status(game, "10.0.0.1", Some("bob")).await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        setup.status(game, "10.0.0.3", Some("ann")).await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+}
+
+#[tokio::test]
+async fn only_limits_the_configured_routes() {
+    let setup = Setup::new();
+    let game = setup.games.start_with_secret(50);
+    let other = setup.games.start_with_secret(50);
+
+    for _ in 0..2 {
+        setup.status(game, "10.0.0.1", None).await;
+    }
+    // The budget is per client and route, not per game.
+    assert_eq!(
+        setup.status(other, "10.0.0.1", None).await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+
+    for _ in 0..5 {
+        let req = Request::post("/games").body(Body::empty()).unwrap();
+        let response = setup.app.clone().oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+}
+
+#[tokio::test]
+async fn refuses_oversized_bodies() {
+    let setup = Setup::new();
+    let game = setup.games.start_with_secret(50);
+    let mut req = Request::post(format!("/games/{}/guesses", game))
+        .body(Body::from(format!(
+            r#"{{"guess": 10, "pad": "{}"}}"#,
+            "x".repeat(4096)
+        )))
+        .unwrap();
+    req.extensions_mut()
+        .insert(RemoteAddr("10.0.0.9:4000".parse().unwrap()));
+    let response = setup.app.clone().oneshot(req).await.unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(
+        json(response).await["error"],
+        "the body may be at most 1024 bytes"
+    );
+}
+
+#[tokio::test]
+async fn limits_clients_over_the_network() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr: SocketAddr = listener.local_addr().unwrap();
+    let limits = Limits::new().route(Method::POST, "/games", Limit::per_minute(1));
+    let app = app(
+        Arc::new(Games::new(Difficulty::Easy)),
+        limits,
+        MockClock::new(),
+    );
+    let (stop, stopped) = oneshot::channel::<()>();
+    let server = tokio::spawn(serve(listener, app, async {
+        let _ = stopped.await;
+    }));
+
+    let client = hyper::Client::new();
+    let new_game = || {
+        Request::post(format!("http://{}/games", addr))
+            .body(Body::empty())
+            .unwrap()
+    };
+    let created = client.request(new_game()).await.unwrap();
+    assert_eq!(created.status(), StatusCode::CREATED);
+    let game = json(created).await;
+    assert_eq!(
+        (game["low"].as_u64(), game["high"].as_u64()),
+        (Some(1), Some(10))
+    );
+
+    let refused = client.request(new_game()).await.unwrap();
+    assert_eq!(refused.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(refused.headers().get(RETRY_AFTER).unwrap(), "60");
+
+    stop.send(()).unwrap();
+    server.await.unwrap().unwrap();
+}
@@ -0,0 +1,89 @@
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::net::TcpStream;
+use std::process;
+use std::thread;
+
+use guessing_server::{Client, Reply, Request};
+
+fn main() {
+    let addr = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:7878".to_string());
+    let mut client = match Client::connect(&addr) {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("error: could not connect to {}: {}", addr, e);
+            process::exit(1);
+        }
+    };
+    let mut writer = client
+        .try_clone_writer()
+        .expect("failed to clone connection");
+
+    // Print whatever the server says while the main thread forwards input.
+    let printer = thread::spawn(move || loop {
+        match client.recv() {
+            Ok(Reply::Bye) => break,
+            Ok(reply) => println!("{}", describe(&reply)),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                println!("The server closed the connection.");
+                break;
+            }
+            Err(e) => {
+                eprintln!("error: {}", e);
+                break;
+            }
+        }
+    });
+
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else { break };
+        if send(&mut writer, &Request::parse(&line)).is_err() {
+            break;
+        }
+    }
+    let _ = send(&mut writer, &Request::Quit);
+    let _ = printer.join();
+}
+
+fn send(writer: &mut TcpStream, request: &Request) -> io::Result<()> {
+    writer.write_all(format!("{}\n", request).as_bytes())
+}
+
+fn describe(reply: &Reply) -> String {
+    match reply {
+        Reply::Welcome { mode, player } => {
+            format!("Connected to a {} game as player {}.", mode, player)
+        }
+        Reply::Start {
+            round,
+            low,
+            high,
+            attempts,
+        } => format!(
+            "Round {}: the secret is between {} and {}, you have {} attempts.",
+            round, low, high, attempts
+        ),
+        Reply::TooSmall { attempts_left } => {
+            format!("Too small! {} attempts left.", attempts_left)
+        }
+        Reply::TooBig { attempts_left } => format!("Too big! {} attempts left.", attempts_left),
+        Reply::Win { attempts } => format!("You win! It took you {} attempts.", attempts),
+        Reply::Lose {
+            secret: Some(secret),
+        } => {
+            format!(
+                "You lose! The secret number was {}. Type NEW to play again.",
+                secret
+            )
+        }
+        Reply::Lose { secret: None } => "You are out of attempts for this round.".to_string(),
+        Reply::Winner { player, secret } => {
+            format!("Player {} found the secret, it was {}.", player, secret)
+        }
+        Reply::Nobody { secret } => format!("Nobody found the secret, it was {}.", secret),
+        Reply::Bye => "Bye!".to_string(),
+        Reply::Error(message) => message.clone(),
+    }
+}
@@ -0,0 +1,51 @@
+use std::env;
+use std::process;
+
+use guessing_server::{Config, Mode, Server};
+
+const USAGE: &str = "\
+Usage: server [--addr <ADDR>] [--race] [--difficulty <LEVEL>]
+
+  --addr <ADDR>             address to listen on [default: 127.0.0.1:7878]
+  --race                    everyone guesses the same secret, first correct guess wins
+  -d, --difficulty <LEVEL>  easy, medium or hard [default: medium]";
+
+fn main() {
+    let mut addr = "127.0.0.1:7878".to_string();
+    let mut config = Config::default();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--race" => config.mode = Mode::Race,
+            "--addr" => addr = args.next().unwrap_or_else(|| fail("--addr needs a value")),
+            "-d" | "--difficulty" => {
+                let level = args
+                    .next()
+                    .unwrap_or_else(|| fail("--difficulty needs a value"));
+                config.difficulty = level.parse().unwrap_or_else(|e: String| fail(&e));
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => fail(&format!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    let server = Server::bind(&addr, config.clone()).unwrap_or_else(|e| fail(&e.to_string()));
+    println!(
+        "{} server running on {}, difficulty {}",
+        config.mode,
+        server.local_addr().unwrap(),
+        config.difficulty
+    );
+    if let Err(e) = server.run() {
+        fail(&e.to_string());
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
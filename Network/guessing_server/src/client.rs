@@ -0,0 +1,43 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+
+use crate::protocol::{Reply, Request};
+
+/// A connection to a [`crate::Server`].
+pub struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Client {
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Client> {
+        let writer = TcpStream::connect(addr)?;
+        let reader = BufReader::new(writer.try_clone()?);
+        Ok(Client { reader, writer })
+    }
+
+    pub fn send(&mut self, request: &Request) -> io::Result<()> {
+        self.send_line(&request.to_string())
+    }
+
+    /// Sends a raw line, exactly as a person would type it.
+    pub fn send_line(&mut self, line: &str) -> io::Result<()> {
+        self.writer.write_all(format!("{}\n", line).as_bytes())
+    }
+
+    /// Waits for the next reply. Fails with `UnexpectedEof` once the server
+    /// hangs up.
+    pub fn recv(&mut self) -> io::Result<Reply> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        line.parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// A second handle on the connection, for writing from another thread.
+    pub fn try_clone_writer(&self) -> io::Result<TcpStream> {
+        self.writer.try_clone()
+    }
+}
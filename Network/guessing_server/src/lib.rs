@@ -0,0 +1,12 @@
+//! The guessing game served over plain TCP, building on the `TcpListener`
+//! example in `Network.md`.
+
+pub mod client;
+pub mod protocol;
+pub mod server;
+pub mod solo;
+
+pub use client::Client;
+pub use protocol::{Mode, Reply, Request};
+pub use server::{Config, Server};
+pub use solo::SoloGame;
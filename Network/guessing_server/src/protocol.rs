@@ -0,0 +1,298 @@
+//! The plain-text line protocol. Every message is one line of
+//! space-separated words, so a session can be played with `nc` or `telnet`.
+//!
+//! ```text
+//! S: WELCOME solo 1            mode and player id
+//! S: START 1 1 100 10          round, lowest, highest, attempts
+//! C: 50                        a guess (`GUESS 50` works too)
+//! S: TOO_BIG 9                 attempts left
+//! C: 25
+//! S: WIN 2                     attempts taken
+//! C: NEW hard                  solo only: start over, optionally at another level
+//! C: QUIT
+//! S: BYE
+//! ```
+//!
+//! In race mode every player chases the same secret. The first to find it
+//! ends the round for everyone with `WINNER <player> <secret>`, and the next
+//! round `START`s straight away.
+
+use std::fmt;
+use std::str::FromStr;
+
+use guessing_game::{parse_guess, Hint, Outcome, Round, RoundError};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Every connection plays its own game.
+    #[default]
+    Solo,
+    /// All connections guess the same secret; the first correct guess wins.
+    Race,
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Mode::Solo => "solo",
+            Mode::Race => "race",
+        })
+    }
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "solo" => Ok(Mode::Solo),
+            "race" => Ok(Mode::Race),
+            other => Err(format!("unknown mode '{}'", other)),
+        }
+    }
+}
+
+/// A line sent by the client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request {
+    /// The text of a guess, not parsed yet so the server can report errors
+    /// the same way the terminal game does.
+    Guess(String),
+    /// Start a new game, optionally at a named difficulty.
+    New(Option<String>),
+    Quit,
+}
+
+impl Request {
+    pub fn parse(line: &str) -> Request {
+        let line = line.trim();
+        let (word, rest) = match line.split_once(char::is_whitespace) {
+            Some((word, rest)) => (word, rest.trim()),
+            None => (line, ""),
+        };
+        match word.to_ascii_uppercase().as_str() {
+            "GUESS" => Request::Guess(rest.to_string()),
+            "NEW" if rest.is_empty() => Request::New(None),
+            "NEW" => Request::New(Some(rest.to_string())),
+            "QUIT" => Request::Quit,
+            _ => Request::Guess(line.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Request {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Request::Guess(guess) => write!(f, "GUESS {}", guess),
+            Request::New(None) => write!(f, "NEW"),
+            Request::New(Some(level)) => write!(f, "NEW {}", level),
+            Request::Quit => write!(f, "QUIT"),
+        }
+    }
+}
+
+/// A line sent by the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    Welcome {
+        mode: Mode,
+        player: u32,
+    },
+    Start {
+        round: u32,
+        low: u32,
+        high: u32,
+        attempts: u32,
+    },
+    TooSmall {
+        attempts_left: u32,
+    },
+    TooBig {
+        attempts_left: u32,
+    },
+    Win {
+        attempts: u32,
+    },
+    /// Out of attempts. The secret is only revealed in solo mode, since in a
+    /// race the others are still guessing.
+    Lose {
+        secret: Option<u32>,
+    },
+    /// Race mode: someone found the secret.
+    Winner {
+        player: u32,
+        secret: u32,
+    },
+    /// Race mode: every player ran out of attempts.
+    Nobody {
+        secret: u32,
+    },
+    Bye,
+    Error(String),
+}
+
+impl Reply {
+    pub(crate) fn start(round_no: u32, round: &Round) -> Reply {
+        let range = round.range();
+        Reply::Start {
+            round: round_no,
+            low: *range.start(),
+            high: *range.end(),
+            attempts: round.max_attempts(),
+        }
+    }
+
+    /// Plays `text` against `round`. `reveal` says whether a loss may show
+    /// the secret, `over` is the error for a round that already ended.
+    pub(crate) fn guess(round: &mut Round, text: &str, reveal: bool, over: &str) -> Reply {
+        let guess = match parse_guess(text, &round.range()) {
+            Ok(guess) => guess,
+            Err(e) => return Reply::Error(e.to_string()),
+        };
+        match round.guess(guess) {
+            Ok(Hint::Correct) => Reply::Win {
+                attempts: round.attempts(),
+            },
+            Ok(_) if matches!(round.outcome(), Some(Outcome::Lost { .. })) => Reply::Lose {
+                secret: reveal.then(|| round.secret()),
+            },
+            Ok(Hint::TooSmall) => Reply::TooSmall {
+                attempts_left: round.attempts_left(),
+            },
+            Ok(Hint::TooBig) => Reply::TooBig {
+                attempts_left: round.attempts_left(),
+            },
+            Err(RoundError::GameOver(_)) => Reply::Error(over.to_string()),
+            Err(e) => Reply::Error(e.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Reply {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reply::Welcome { mode, player } => write!(f, "WELCOME {} {}", mode, player),
+            Reply::Start {
+                round,
+                low,
+                high,
+                attempts,
+            } => write!(f, "START {} {} {} {}", round, low, high, attempts),
+            Reply::TooSmall { attempts_left } => write!(f, "TOO_SMALL {}", attempts_left),
+            Reply::TooBig { attempts_left } => write!(f, "TOO_BIG {}", attempts_left),
+            Reply::Win { attempts } => write!(f, "WIN {}", attempts),
+            Reply::Lose {
+                secret: Some(secret),
+            } => write!(f, "LOSE {}", secret),
+            Reply::Lose { secret: None } => write!(f, "LOSE"),
+            Reply::Winner { player, secret } => write!(f, "WINNER {} {}", player, secret),
+            Reply::Nobody { secret } => write!(f, "NOBODY {}", secret),
+            Reply::Bye => write!(f, "BYE"),
+            Reply::Error(message) => write!(f, "ERROR {}", message),
+        }
+    }
+}
+
+impl FromStr for Reply {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim_end();
+        let (word, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let fields: Vec<&str> = rest.split_whitespace().collect();
+        let bad = || format!("malformed reply '{}'", line);
+        let num = |i: usize| -> Result<u32, String> {
+            fields.get(i).and_then(|f| f.parse().ok()).ok_or_else(bad)
+        };
+
+        let reply = match (word, fields.len()) {
+            ("WELCOME", 2) => Reply::Welcome {
+                mode: fields[0].parse()?,
+                player: num(1)?,
+            },
+            ("START", 4) => Reply::Start {
+                round: num(0)?,
+                low: num(1)?,
+                high: num(2)?,
+                attempts: num(3)?,
+            },
+            ("TOO_SMALL", 1) => Reply::TooSmall {
+                attempts_left: num(0)?,
+            },
+            ("TOO_BIG", 1) => Reply::TooBig {
+                attempts_left: num(0)?,
+            },
+            ("WIN", 1) => Reply::Win { attempts: num(0)? },
+            ("LOSE", 0) => Reply::Lose { secret: None },
+            ("LOSE", 1) => Reply::Lose {
+                secret: Some(num(0)?),
+            },
+            ("WINNER", 2) => Reply::Winner {
+                player: num(0)?,
+                secret: num(1)?,
+            },
+            ("NOBODY", 1) => Reply::Nobody { secret: num(0)? },
+            ("BYE", 0) => Reply::Bye,
+            ("ERROR", _) => Reply::Error(rest.to_string()),
+            _ => return Err(bad()),
+        };
+        Ok(reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_requests() {
+        assert_eq!(Request::parse("42\r\n"), Request::Guess("42".to_string()));
+        assert_eq!(Request::parse("guess  7"), Request::Guess("7".to_string()));
+        assert_eq!(Request::parse("NEW"), Request::New(None));
+        assert_eq!(
+            Request::parse("new Hard"),
+            Request::New(Some("Hard".to_string()))
+        );
+        assert_eq!(Request::parse("quit"), Request::Quit);
+        assert_eq!(Request::parse("lots"), Request::Guess("lots".to_string()));
+    }
+
+    #[test]
+    fn replies_round_trip() {
+        let replies = [
+            Reply::Welcome {
+                mode: Mode::Race,
+                player: 3,
+            },
+            Reply::Start {
+                round: 2,
+                low: 1,
+                high: 100,
+                attempts: 10,
+            },
+            Reply::TooSmall { attempts_left: 9 },
+            Reply::TooBig { attempts_left: 0 },
+            Reply::Win { attempts: 4 },
+            Reply::Lose { secret: Some(17) },
+            Reply::Lose { secret: None },
+            Reply::Winner {
+                player: 1,
+                secret: 64,
+            },
+            Reply::Nobody { secret: 5 },
+            Reply::Bye,
+            Reply::Error("'x' is not a whole number.".to_string()),
+        ];
+        for reply in replies {
+            assert_eq!(reply.to_string().parse(), Ok(reply));
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_replies() {
+        assert!("WIN".parse::<Reply>().is_err());
+        assert!("START 1 2 3".parse::<Reply>().is_err());
+        assert!("WELCOME duel 1".parse::<Reply>().is_err());
+        assert!("HELLO".parse::<Reply>().is_err());
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use guessing_game::{Difficulty, Round};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::protocol::{Mode, Reply, Request};
+use crate::solo::SoloGame;
+
+/// The longest request line the server reads, newline included. Anything
+/// longer ends the connection rather than being buffered without limit.
+const MAX_LINE: u64 = 1024;
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub mode: Mode,
+    pub difficulty: Difficulty,
+    /// How long a write to a player may block. In a race the writes happen
+    /// under the shared lock, so one player who stops reading would
+    /// otherwise hold up everyone else.
+    pub write_timeout: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            mode: Mode::default(),
+            difficulty: Difficulty::default(),
+            write_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Hosts guessing games over TCP, one thread per connection.
+pub struct Server {
+    listener: TcpListener,
+    config: Config,
+    race: Arc<Mutex<Race>>,
+    next_player: AtomicU32,
+}
+
+impl Server {
+    pub fn bind<A: ToSocketAddrs>(addr: A, config: Config) -> io::Result<Server> {
+        let listener = TcpListener::bind(addr)?;
+        let race = Arc::new(Mutex::new(Race::new(config.difficulty.clone())));
+        Ok(Server {
+            listener,
+            config,
+            race,
+            next_player: AtomicU32::new(1),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts connections until the listener fails.
+    pub fn run(&self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            stream.set_write_timeout(Some(self.config.write_timeout))?;
+            let player = self.next_player.fetch_add(1, Ordering::Relaxed);
+            let difficulty = self.config.difficulty.clone();
+            let race = Arc::clone(&self.race);
+            let mode = self.config.mode;
+
+            thread::spawn(move || {
+                let result = match mode {
+                    Mode::Solo => handle_solo(stream, player, difficulty),
+                    Mode::Race => handle_race(stream, player, &race),
+                };
+                if let Err(e) = result {
+                    eprintln!("player {}: {}", player, e);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Runs the server on a background thread, e.g. for tests.
+    pub fn spawn(self) -> io::Result<SocketAddr> {
+        let addr = self.local_addr()?;
+        thread::spawn(move || self.run());
+        Ok(addr)
+    }
+}
+
+fn send(out: &mut TcpStream, reply: &Reply) -> io::Result<()> {
+    out.write_all(format!("{}\n", reply).as_bytes())
+}
+
+/// Reads one request line, or `None` at the end of the stream.
+fn read_line(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if reader.take(MAX_LINE).read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    if !line.ends_with('\n') && line.len() as u64 == MAX_LINE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("request line longer than {} bytes", MAX_LINE),
+        ));
+    }
+    Ok(Some(line))
+}
+
+fn handle_solo(stream: TcpStream, player: u32, difficulty: Difficulty) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut out = stream;
+    let mut game = SoloGame::new(difficulty, StdRng::from_entropy());
+
+    send(
+        &mut out,
+        &Reply::Welcome {
+            mode: Mode::Solo,
+            player,
+        },
+    )?;
+    send(&mut out, &game.start())?;
+
+    while let Some(line) = read_line(&mut reader)? {
+        let reply = game.handle(Request::parse(&line));
+        send(&mut out, &reply)?;
+        if reply == Reply::Bye {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn handle_race(stream: TcpStream, player: u32, race: &Mutex<Race>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    race.lock().unwrap().join(player, stream)?;
+
+    let result = (|| {
+        while let Some(line) = read_line(&mut reader)? {
+            let mut race = race.lock().unwrap();
+            match Request::parse(&line) {
+                Request::Guess(text) => race.guess(player, &text),
+                Request::New(_) => {
+                    race.send(
+                        player,
+                        &Reply::Error("NEW is not available in a race".to_string()),
+                    );
+                    // The send may have dropped this player, and with it
+                    // the last one still guessing.
+                    race.finish_if_everyone_lost();
+                }
+                Request::Quit => {
+                    race.send(player, &Reply::Bye);
+                    break;
+                }
+            }
+        }
+        Ok(())
+    })();
+
+    race.lock().unwrap().leave(player);
+    result
+}
+
+struct Racer {
+    out: TcpStream,
+    addr: IpAddr,
+    round: Round,
+}
+
+/// The shared state of race mode. Every reply is written while the lock is
+/// held, so each player sees events in the same order.
+struct Race {
+    difficulty: Difficulty,
+    rng: StdRng,
+    round_no: u32,
+    secret: u32,
+    players: BTreeMap<u32, Racer>,
+    /// The rounds of players who left during the current round, by address.
+    /// A client that reconnects picks its round up again instead of getting
+    /// fresh attempts at the same secret. Cleared with every new round.
+    departed: HashMap<IpAddr, Round>,
+}
+
+impl Race {
+    fn new(difficulty: Difficulty) -> Race {
+        let mut rng = StdRng::from_entropy();
+        let secret = Round::new(&difficulty, &mut rng).secret();
+        Race {
+            difficulty,
+            rng,
+            round_no: 1,
+            secret,
+            players: BTreeMap::new(),
+            departed: HashMap::new(),
+        }
+    }
+
+    fn join(&mut self, player: u32, mut out: TcpStream) -> io::Result<()> {
+        let addr = out.peer_addr()?.ip();
+        let round = self
+            .departed
+            .remove(&addr)
+            .unwrap_or_else(|| Round::with_secret(&self.difficulty, self.secret));
+        send(
+            &mut out,
+            &Reply::Welcome {
+                mode: Mode::Race,
+                player,
+            },
+        )?;
+        send(&mut out, &Reply::start(self.round_no, &round))?;
+        self.players.insert(player, Racer { out, addr, round });
+        Ok(())
+    }
+
+    fn leave(&mut self, player: u32) {
+        if let Some(racer) = self.players.remove(&player) {
+            self.remember(racer);
+            self.finish_if_everyone_lost();
+        }
+    }
+
+    /// Keeps a departing player's round. Of several players behind one
+    /// address, the one who spent the most attempts counts.
+    fn remember(&mut self, racer: Racer) {
+        let spent = racer.round.attempts();
+        let kept = self.departed.get(&racer.addr).map(Round::attempts);
+        if kept.is_none_or(|kept| spent > kept) {
+            self.departed.insert(racer.addr, racer.round);
+        }
+    }
+
+    /// A player whose write fails or times out is dropped from the race.
+    fn send(&mut self, player: u32, reply: &Reply) {
+        let failed = match self.players.get_mut(&player) {
+            Some(racer) => send(&mut racer.out, reply).is_err(),
+            None => false,
+        };
+        if failed {
+            self.drop_player(player);
+        }
+    }
+
+    fn broadcast(&mut self, reply: &Reply) {
+        let failed: Vec<u32> = self
+            .players
+            .iter_mut()
+            .filter_map(|(&player, racer)| send(&mut racer.out, reply).err().map(|_| player))
+            .collect();
+        for player in failed {
+            self.drop_player(player);
+        }
+    }
+
+    /// Removes a player without touching the round; the callers decide
+    /// whether it has to move on. Shutting the connection down ends the
+    /// player's own thread, whose `leave` then finds nothing left to do.
+    fn drop_player(&mut self, player: u32) {
+        if let Some(racer) = self.players.remove(&player) {
+            let _ = racer.out.shutdown(Shutdown::Both);
+            self.remember(racer);
+        }
+    }
+
+    fn guess(&mut self, player: u32, text: &str) {
+        let Some(racer) = self.players.get_mut(&player) else {
+            return;
+        };
+        let reply = Reply::guess(
+            &mut racer.round,
+            text,
+            false,
+            "you are out of attempts, wait for the next round",
+        );
+        let won = matches!(reply, Reply::Win { .. });
+        self.send(player, &reply);
+
+        if won {
+            let secret = self.secret;
+            self.broadcast(&Reply::Winner { player, secret });
+            self.next_round();
+        } else {
+            self.finish_if_everyone_lost();
+        }
+    }
+
+    fn finish_if_everyone_lost(&mut self) {
+        let everyone_lost = !self.players.is_empty()
+            && self
+                .players
+                .values()
+                .all(|racer| racer.round.outcome().is_some());
+        if everyone_lost {
+            let secret = self.secret;
+            self.broadcast(&Reply::Nobody { secret });
+            self.next_round();
+        }
+    }
+
+    fn next_round(&mut self) {
+        self.round_no += 1;
+        self.secret = Round::new(&self.difficulty, &mut self.rng).secret();
+        self.departed.clear();
+        for racer in self.players.values_mut() {
+            racer.round = Round::with_secret(&self.difficulty, self.secret);
+        }
+        let players: Vec<u32> = self.players.keys().copied().collect();
+        for player in players {
+            let start = Reply::start(self.round_no, &self.players[&player].round);
+            self.send(player, &start);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use guessing_game::{Difficulty, Round};
+use rand::rngs::StdRng;
+
+use crate::protocol::{Reply, Request};
+
+/// One player's games in solo mode, kept apart from the connection so any
+/// transport can drive it.
+pub struct SoloGame {
+    difficulty: Difficulty,
+    rng: StdRng,
+    round_no: u32,
+    round: Round,
+}
+
+impl SoloGame {
+    pub fn new(difficulty: Difficulty, mut rng: StdRng) -> SoloGame {
+        let round = Round::new(&difficulty, &mut rng);
+        SoloGame {
+            difficulty,
+            rng,
+            round_no: 1,
+            round,
+        }
+    }
+
+    /// The `START` line for the current round.
+    pub fn start(&self) -> Reply {
+        Reply::start(self.round_no, &self.round)
+    }
+
+    /// Answers one request. `QUIT` gets `BYE`; closing the connection is up
+    /// to the caller.
+    pub fn handle(&mut self, request: Request) -> Reply {
+        match request {
+            Request::Guess(text) => {
+                Reply::guess(&mut self.round, &text, true, "the game is over, send NEW")
+            }
+            Request::New(level) => {
+                if let Some(level) = level {
+                    match level.parse() {
+                        Ok(level) => self.difficulty = level,
+                        Err(e) => return Reply::Error(e),
+                    }
+                }
+                self.round_no += 1;
+                self.round = Round::new(&self.difficulty, &mut self.rng);
+                self.start()
+            }
+            Request::Quit => Reply::Bye,
+        }
+    }
+
+    /// Whether the current round has been guessed at and is not over yet.
+    pub fn in_progress(&self) -> bool {
+        self.round.attempts() > 0 && self.round.outcome().is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn in_progress_only_between_first_guess_and_end() {
+        let difficulty = Difficulty::custom(1..=2, 2).unwrap();
+        let mut game = SoloGame::new(difficulty, StdRng::seed_from_u64(1));
+        assert!(!game.in_progress());
+
+        let first = game.handle(Request::Guess("1".to_string()));
+        if first == (Reply::Win { attempts: 1 }) {
+            assert!(!game.in_progress());
+        } else {
+            assert!(game.in_progress());
+            assert_eq!(
+                game.handle(Request::Guess("2".to_string())),
+                Reply::Win { attempts: 2 }
+            );
+            assert!(!game.in_progress());
+        }
+
+        assert!(matches!(
+            game.handle(Request::New(None)),
+            Reply::Start { round: 2, .. }
+        ));
+        assert!(!game.in_progress());
+    }
+}
@@ -0,0 +1,283 @@
+use std::io::Write;
+use std::net::TcpStream;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use guessing_game::Difficulty;
+use guessing_server::{Client, Config, Mode, Reply, Request, Server};
+
+fn spawn(mode: Mode, difficulty: Difficulty) -> std::net::SocketAddr {
+    let config = Config {
+        mode,
+        difficulty,
+        ..Config::default()
+    };
+    Server::bind("127.0.0.1:0", config)
+        .unwrap()
+        .spawn()
+        .unwrap()
+}
+
+/// Reads the greeting and returns the player id and the range of round 1.
+fn join(client: &mut Client) -> (u32, u32, u32) {
+    let player = match client.recv().unwrap() {
+        Reply::Welcome { player, .. } => player,
+        other => panic!("expected WELCOME, got {:?}", other),
+    };
+    match client.recv().unwrap() {
+        Reply::Start {
+            round: 1,
+            low,
+            high,
+            ..
+        } => (player, low, high),
+        other => panic!("expected START, got {:?}", other),
+    }
+}
+
+/// Binary-searches until the server says WIN, returning the attempts taken
+/// and the secret.
+fn binary_search(client: &mut Client, mut low: u32, mut high: u32) -> (u32, u32) {
+    loop {
+        let guess = low + (high - low) / 2;
+        client.send(&Request::Guess(guess.to_string())).unwrap();
+        match client.recv().unwrap() {
+            Reply::Win { attempts } => return (attempts, guess),
+            Reply::TooSmall { .. } => low = guess + 1,
+            Reply::TooBig { .. } => high = guess - 1,
+            other => panic!("unexpected reply {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn solo_game_can_be_won_and_restarted() {
+    let addr = spawn(Mode::Solo, Difficulty::Medium);
+    let mut client = Client::connect(addr).unwrap();
+    let (_, low, high) = join(&mut client);
+    assert_eq!((low, high), (1, 100));
+
+    let (attempts, _) = binary_search(&mut client, low, high);
+    assert!(attempts <= 7);
+
+    client.send_line("50").unwrap();
+    assert_eq!(
+        client.recv().unwrap(),
+        Reply::Error("the game is over, send NEW".to_string())
+    );
+
+    client
+        .send(&Request::New(Some("easy".to_string())))
+        .unwrap();
+    assert_eq!(
+        client.recv().unwrap(),
+        Reply::Start {
+            round: 2,
+            low: 1,
+            high: 10,
+            attempts: 6
+        }
+    );
+
+    client.send(&Request::Quit).unwrap();
+    assert_eq!(client.recv().unwrap(), Reply::Bye);
+}
+
+#[test]
+fn solo_players_get_their_own_games() {
+    let addr = spawn(Mode::Solo, Difficulty::Easy);
+    let mut first = Client::connect(addr).unwrap();
+    let mut second = Client::connect(addr).unwrap();
+    let (a, ..) = join(&mut first);
+    let (b, ..) = join(&mut second);
+    assert_ne!(a, b);
+
+    first.send_line("5").unwrap();
+    assert!(!matches!(first.recv().unwrap(), Reply::Error(_)));
+
+    // The second player's attempts are untouched by the first one's guess.
+    second.send_line("1").unwrap();
+    match second.recv().unwrap() {
+        Reply::TooSmall { attempts_left } | Reply::TooBig { attempts_left } => {
+            assert_eq!(attempts_left, 5)
+        }
+        Reply::Win { attempts } => assert_eq!(attempts, 1),
+        other => panic!("unexpected reply {:?}", other),
+    }
+}
+
+#[test]
+fn bad_guesses_are_reported_without_costing_attempts() {
+    let addr = spawn(Mode::Solo, Difficulty::Easy);
+    let mut client = Client::connect(addr).unwrap();
+    join(&mut client);
+
+    client.send_line("lots").unwrap();
+    assert_eq!(
+        client.recv().unwrap(),
+        Reply::Error("'lots' is not a whole number.".to_string())
+    );
+    client.send_line("GUESS 11").unwrap();
+    assert_eq!(
+        client.recv().unwrap(),
+        Reply::Error("11 is out of range, pick a number between 1 and 10.".to_string())
+    );
+    client.send_line("NEW impossible").unwrap();
+    assert!(matches!(client.recv().unwrap(), Reply::Error(_)));
+
+    let (attempts, _) = binary_search(&mut client, 1, 10);
+    assert!(attempts <= 4);
+}
+
+#[test]
+fn first_correct_guess_wins_the_race_for_everyone() {
+    let addr = spawn(Mode::Race, Difficulty::Medium);
+    let mut alice = Client::connect(addr).unwrap();
+    let (a, low, high) = join(&mut alice);
+    let mut bob = Client::connect(addr).unwrap();
+    let (b, ..) = join(&mut bob);
+
+    let (_, secret) = binary_search(&mut alice, low, high);
+
+    let winner = Reply::Winner { player: a, secret };
+    assert_eq!(alice.recv().unwrap(), winner);
+    assert_eq!(bob.recv().unwrap(), winner);
+    for client in [&mut alice, &mut bob] {
+        assert!(matches!(
+            client.recv().unwrap(),
+            Reply::Start { round: 2, .. }
+        ));
+    }
+
+    // Bob can play straight on in round 2, and NEW is refused mid-race.
+    bob.send(&Request::New(None)).unwrap();
+    assert_eq!(
+        bob.recv().unwrap(),
+        Reply::Error("NEW is not available in a race".to_string())
+    );
+    bob.send(&Request::Quit).unwrap();
+    assert_eq!(bob.recv().unwrap(), Reply::Bye);
+    assert_ne!(a, b);
+}
+
+#[test]
+fn race_moves_on_when_everyone_is_out_of_attempts() {
+    let difficulty = Difficulty::custom(1..=100, 1).unwrap();
+    let addr = spawn(Mode::Race, difficulty);
+    let mut alice = Client::connect(addr).unwrap();
+    join(&mut alice);
+
+    // Guessing 1 and then 100 cannot both be right, so one of two fresh
+    // rounds must end with nobody finding the secret.
+    for guess in ["1", "100"] {
+        alice.send_line(guess).unwrap();
+        match alice.recv().unwrap() {
+            Reply::Win { .. } => {
+                assert!(matches!(alice.recv().unwrap(), Reply::Winner { .. }));
+            }
+            Reply::Lose { secret: None } => {
+                assert!(matches!(alice.recv().unwrap(), Reply::Nobody { .. }));
+            }
+            other => panic!("unexpected reply {:?}", other),
+        }
+        assert!(matches!(alice.recv().unwrap(), Reply::Start { .. }));
+    }
+}
+
+#[test]
+fn a_racer_who_stops_reading_is_dropped() {
+    let config = Config {
+        mode: Mode::Race,
+        difficulty: Difficulty::Medium,
+        write_timeout: Duration::from_millis(200),
+    };
+    let addr = Server::bind("127.0.0.1:0", config)
+        .unwrap()
+        .spawn()
+        .unwrap();
+
+    // Floods the server with refused guesses and never reads a reply, until
+    // the server hangs up on it.
+    let mut stalled = TcpStream::connect(addr).unwrap();
+    let flood = thread::spawn(move || {
+        let lines = "0\n".repeat(4096);
+        while stalled.write_all(lines.as_bytes()).is_ok() {}
+    });
+    flood.join().unwrap();
+
+    let mut alice = Client::connect(addr).unwrap();
+    let (_, low, high) = join(&mut alice);
+    let (attempts, _) = binary_search(&mut alice, low, high);
+    assert!(attempts <= 7);
+}
+
+#[test]
+fn reconnecting_does_not_give_back_attempts() {
+    let difficulty = Difficulty::custom(1..=100, 3).unwrap();
+    let addr = spawn(Mode::Race, difficulty);
+    let mut alice = Client::connect(addr).unwrap();
+    join(&mut alice);
+
+    // Guessing 1 only wins if it is the secret; then try the next round.
+    loop {
+        alice.send_line("1").unwrap();
+        match alice.recv().unwrap() {
+            Reply::TooSmall { attempts_left } => {
+                assert_eq!(attempts_left, 2);
+                break;
+            }
+            Reply::Win { .. } => {
+                assert!(matches!(alice.recv().unwrap(), Reply::Winner { .. }));
+                assert!(matches!(alice.recv().unwrap(), Reply::Start { .. }));
+            }
+            other => panic!("unexpected reply {:?}", other),
+        }
+    }
+    alice.send(&Request::Quit).unwrap();
+    assert_eq!(alice.recv().unwrap(), Reply::Bye);
+    drop(alice);
+
+    let mut again = Client::connect(addr).unwrap();
+    assert!(matches!(again.recv().unwrap(), Reply::Welcome { .. }));
+    assert!(matches!(again.recv().unwrap(), Reply::Start { .. }));
+    again.send_line("1").unwrap();
+    assert_eq!(again.recv().unwrap(), Reply::TooSmall { attempts_left: 1 });
+}
+
+#[test]
+fn overlong_lines_end_the_connection() {
+    let addr = spawn(Mode::Solo, Difficulty::Medium);
+    let mut client = Client::connect(addr).unwrap();
+    join(&mut client);
+
+    client.send_line(&"5".repeat(4096)).unwrap();
+    assert!(client.recv().is_err());
+}
+
+#[test]
+fn client_binary_plays_against_the_server() {
+    let difficulty = Difficulty::custom(1..=10, 10).unwrap();
+    let addr = spawn(Mode::Solo, difficulty);
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_client"))
+        .arg(addr.to_string())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let guesses: String = (1..=10).map(|n| format!("{}\n", n)).collect();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(guesses.as_bytes())
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Connected to a solo game as player 1."));
+    assert!(stdout.contains("Round 1: the secret is between 1 and 10, you have 10 attempts."));
+    assert!(stdout.contains("You win!"));
+}
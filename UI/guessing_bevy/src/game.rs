@@ -0,0 +1,273 @@
+use bevy::prelude::*;
+use guessing_game::{Difficulty, Hint, Outcome, Round};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Which screen is up: the number pad, or the end of a round.
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Screen {
+    #[default]
+    Playing,
+    Over,
+}
+
+/// One key of the number pad.
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Digit(u8),
+    /// Deletes the last digit typed.
+    Back,
+    /// Sends what was typed as a guess.
+    Enter,
+}
+
+impl Key {
+    /// The pad, row by row, the way a phone lays it out.
+    pub const PAD: [Key; 12] = [
+        Key::Digit(1),
+        Key::Digit(2),
+        Key::Digit(3),
+        Key::Digit(4),
+        Key::Digit(5),
+        Key::Digit(6),
+        Key::Digit(7),
+        Key::Digit(8),
+        Key::Digit(9),
+        Key::Back,
+        Key::Digit(0),
+        Key::Enter,
+    ];
+
+    pub fn label(&self) -> String {
+        match self {
+            Key::Digit(digit) => digit.to_string(),
+            Key::Back => "Del".to_string(),
+            Key::Enter => "OK".to_string(),
+        }
+    }
+}
+
+/// Asks for a fresh round, e.g. from the "play again" button.
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NewRound;
+
+/// The round being played and what has been typed towards the next guess.
+/// Guesses are checked by the same [`Round`] engine as the terminal game.
+#[derive(Resource, Debug)]
+pub struct Game {
+    difficulty: Difficulty,
+    rng: StdRng,
+    round: Round,
+    entry: String,
+    /// The last guess and its hint, or why the last guess was refused.
+    last: Option<Result<(u32, Hint), String>>,
+}
+
+impl Game {
+    pub fn new(difficulty: Difficulty, seed: u64) -> Game {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let round = Round::new(&difficulty, &mut rng);
+        Game {
+            difficulty,
+            rng,
+            round,
+            entry: String::new(),
+            last: None,
+        }
+    }
+
+    /// Deals the next round from the same random stream.
+    pub fn restart(&mut self) {
+        self.round = Round::new(&self.difficulty, &mut self.rng);
+        self.entry.clear();
+        self.last = None;
+    }
+
+    /// Handles one key. Digits that cannot make a number in range, such as
+    /// a leading zero or one digit too many, are ignored. A lone 0 is fine
+    /// when the range starts at 0.
+    pub fn press(&mut self, key: Key) {
+        if self.round.outcome().is_some() {
+            return;
+        }
+        match key {
+            Key::Digit(digit) => {
+                let range = self.round.range();
+                let longest = range.end().to_string().len();
+                let leading_zero = digit == 0 && self.entry.is_empty() && *range.start() != 0;
+                if digit > 9 || leading_zero || self.entry == "0" || self.entry.len() == longest {
+                    return;
+                }
+                self.entry.push(char::from(b'0' + digit));
+            }
+            Key::Back => {
+                self.entry.pop();
+            }
+            Key::Enter => {
+                let Ok(guess) = self.entry.parse() else {
+                    return;
+                };
+                self.entry.clear();
+                self.last = Some(
+                    self.round
+                        .guess(guess)
+                        .map(|hint| (guess, hint))
+                        .map_err(|e| e.to_string()),
+                );
+            }
+        }
+    }
+
+    pub fn round(&self) -> &Round {
+        &self.round
+    }
+
+    pub fn difficulty(&self) -> &Difficulty {
+        &self.difficulty
+    }
+
+    /// The digits typed so far.
+    pub fn entry(&self) -> &str {
+        &self.entry
+    }
+
+    /// One line telling the player where they stand.
+    pub fn hint(&self) -> String {
+        let bounds = self.round.bounds();
+        let left = self.round.attempts_left();
+        let standing = format!(
+            "Still possible: {}-{}, {} attempts left.",
+            bounds.start(),
+            bounds.end(),
+            left
+        );
+        match &self.last {
+            None => {
+                let range = self.round.range();
+                format!(
+                    "Pick a number between {} and {}. You have {} attempts.",
+                    range.start(),
+                    range.end(),
+                    left
+                )
+            }
+            Some(Ok((guess, Hint::TooSmall))) => format!("{} is too small. {}", guess, standing),
+            Some(Ok((guess, Hint::TooBig))) => format!("{} is too big. {}", guess, standing),
+            Some(Ok((guess, Hint::Correct))) => format!("{} is correct!", guess),
+            Some(Err(e)) => e.clone(),
+        }
+    }
+
+    /// The headline and the details for the end screen, once the round is
+    /// over.
+    pub fn result(&self) -> Option<(&'static str, String)> {
+        Some(match self.round.outcome()? {
+            Outcome::Won { secret, attempts } => (
+                "You win!",
+                format!("{} it was. It took you {} attempts.", secret, attempts),
+            ),
+            Outcome::Lost { secret, .. } => (
+                "Out of attempts",
+                format!("The secret number was {}.", secret),
+            ),
+            Outcome::Quit { secret, .. } => {
+                ("You gave up", format!("The secret number was {}.", secret))
+            }
+        })
+    }
+}
+
+/// Feeds pad keys to the game and moves to the end screen when the round
+/// is over.
+pub fn apply_keys(
+    mut keys: MessageReader<Key>,
+    mut game: ResMut<Game>,
+    mut screen: ResMut<NextState<Screen>>,
+) {
+    let mut pressed = false;
+    for &key in keys.read() {
+        game.press(key);
+        pressed = true;
+    }
+    if pressed && game.round().outcome().is_some() {
+        screen.set(Screen::Over);
+    }
+}
+
+/// Deals a new round and goes back to the pad.
+pub fn start_round(
+    mut requests: MessageReader<NewRound>,
+    mut game: ResMut<Game>,
+    mut screen: ResMut<NextState<Screen>>,
+) {
+    if requests.read().count() > 0 {
+        game.restart();
+        screen.set(Screen::Playing);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typing_and_guessing() {
+        let mut game = Game::new(Difficulty::Medium, 7);
+        let secret = game.round().secret();
+        assert!(game.hint().starts_with("Pick a number between 1 and 100."));
+
+        for key in [Key::Digit(0), Key::Digit(1), Key::Digit(2), Key::Digit(3)] {
+            game.press(key);
+        }
+        assert_eq!(game.entry(), "123");
+        game.press(Key::Digit(4));
+        assert_eq!(game.entry(), "123");
+        game.press(Key::Enter);
+        assert_eq!(game.entry(), "");
+        assert_eq!(
+            game.hint(),
+            "123 is out of range, pick a number between 1 and 100."
+        );
+        assert_eq!(game.round().attempts(), 0);
+
+        game.press(Key::Digit(1));
+        game.press(Key::Back);
+        game.press(Key::Enter);
+        assert_eq!(game.round().attempts(), 0);
+
+        let low = if secret == 1 { 2 } else { 1 };
+        game.press(Key::Digit(low as u8));
+        game.press(Key::Enter);
+        let hint = if low < secret { "too small" } else { "too big" };
+        assert!(game.hint().starts_with(&format!("{} is {}.", low, hint)));
+        assert!(game.hint().ends_with("9 attempts left."), "{}", game.hint());
+        assert_eq!(game.result(), None);
+
+        for digit in secret.to_string().bytes() {
+            game.press(Key::Digit(digit - b'0'));
+        }
+        game.press(Key::Enter);
+        assert_eq!(game.hint(), format!("{} is correct!", secret));
+        let (headline, details) = game.result().unwrap();
+        assert_eq!(headline, "You win!");
+        assert!(details.ends_with("It took you 2 attempts."), "{}", details);
+
+        game.restart();
+        assert_eq!(game.round().attempts(), 0);
+        assert_eq!(game.result(), None);
+    }
+
+    #[test]
+    fn zero_only_when_in_range() {
+        let mut game = Game::new(Difficulty::custom(0..=10, 5).unwrap(), 7);
+        game.press(Key::Digit(10));
+        assert_eq!(game.entry(), "");
+        game.press(Key::Digit(0));
+        game.press(Key::Digit(5));
+        assert_eq!(game.entry(), "0");
+        game.press(Key::Back);
+        game.press(Key::Digit(1));
+        game.press(Key::Digit(0));
+        assert_eq!(game.entry(), "10");
+    }
+}
@@ -0,0 +1,48 @@
+//! The guessing game in Bevy: a number pad, a hint line and an end screen.
+//! The secret and the hints come from `guessing_game`, so a round here
+//! plays exactly like one in the terminal.
+//!
+//! Everything is plain ECS data and systems. [`GuessingPlugin`] needs only
+//! `MinimalPlugins` and `StatesPlugin` besides, which is how the tests run
+//! whole rounds without a window or a GPU; `cargo run --features window`
+//! puts it on screen.
+
+use bevy::prelude::*;
+use guessing_game::Difficulty;
+
+pub mod game;
+pub mod ui;
+
+pub use game::{Game, Key, NewRound, Screen};
+
+/// The game itself. Add a camera and the default plugins to see it.
+#[derive(Debug, Clone, Default)]
+pub struct GuessingPlugin {
+    pub difficulty: Difficulty,
+    /// Seed for the secrets; a random one if `None`.
+    pub seed: Option<u64>,
+}
+
+impl Plugin for GuessingPlugin {
+    fn build(&self, app: &mut App) {
+        let seed = self.seed.unwrap_or_else(rand::random);
+        app.init_state::<Screen>()
+            .add_message::<Key>()
+            .add_message::<NewRound>()
+            .insert_resource(Game::new(self.difficulty.clone(), seed))
+            .add_systems(Startup, ui::spawn_board)
+            .add_systems(OnEnter(Screen::Over), ui::spawn_end_screen)
+            .add_systems(
+                Update,
+                (
+                    ui::press_pad.run_if(in_state(Screen::Playing)),
+                    ui::press_play_again.run_if(in_state(Screen::Over)),
+                    game::apply_keys,
+                    game::start_round,
+                    ui::show_game.run_if(resource_changed::<Game>),
+                )
+                    .chain(),
+            )
+            .add_systems(Update, ui::shade_buttons);
+    }
+}
@@ -0,0 +1,34 @@
+use std::env;
+use std::process;
+
+use bevy::prelude::*;
+use guessing_bevy::GuessingPlugin;
+use guessing_game::Difficulty;
+
+/// Usage: guessing_bevy [easy|medium|hard]
+fn main() {
+    let difficulty = match env::args().nth(1) {
+        Some(arg) => arg.parse::<Difficulty>().unwrap_or_else(|e| {
+            eprintln!("error: {}", e);
+            process::exit(2);
+        }),
+        None => Difficulty::default(),
+    };
+
+    App::new()
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "Guess the number!".to_string(),
+                ..default()
+            }),
+            ..default()
+        }))
+        .add_plugins(GuessingPlugin {
+            difficulty,
+            seed: None,
+        })
+        .add_systems(Startup, |mut commands: Commands| {
+            commands.spawn(Camera2d);
+        })
+        .run();
+}
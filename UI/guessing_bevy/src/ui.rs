@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+
+use crate::game::{Game, Key, NewRound, Screen};
+
+const BACKGROUND: Color = Color::srgb(0.1, 0.1, 0.14);
+const KEY_IDLE: Color = Color::srgb(0.22, 0.24, 0.3);
+const KEY_HOVERED: Color = Color::srgb(0.3, 0.33, 0.42);
+const KEY_PRESSED: Color = Color::srgb(0.35, 0.6, 0.35);
+
+/// A number pad button and the key it stands for.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PadButton(pub Key);
+
+/// The text showing the digits typed so far.
+#[derive(Component, Debug)]
+pub struct EntryText;
+
+/// The text telling the player how their last guess went.
+#[derive(Component, Debug)]
+pub struct HintText;
+
+/// The overlay shown once a round is over.
+#[derive(Component, Debug)]
+pub struct EndScreen;
+
+#[derive(Component, Debug)]
+pub struct PlayAgainButton;
+
+/// Builds the board: a title, the typed digits, the hint and the pad.
+pub fn spawn_board(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            width: percent(100),
+            height: percent(100),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: px(16),
+            ..default()
+        },
+        BackgroundColor(BACKGROUND),
+        children![
+            (
+                Text::new("Guess the number!"),
+                TextFont::from_font_size(40.0)
+            ),
+            (EntryText, Text::new("_"), TextFont::from_font_size(48.0)),
+            (HintText, Text::new(""), TextFont::from_font_size(20.0)),
+            (
+                Node {
+                    display: Display::Grid,
+                    grid_template_columns: RepeatedGridTrack::flex(3, 1.0),
+                    row_gap: px(8),
+                    column_gap: px(8),
+                    ..default()
+                },
+                Children::spawn(SpawnIter(Key::PAD.into_iter().map(pad_button))),
+            ),
+        ],
+    ));
+}
+
+fn pad_button(key: Key) -> impl Bundle {
+    (
+        PadButton(key),
+        button_node(80.0),
+        children![(Text::new(key.label()), TextFont::from_font_size(32.0))],
+    )
+}
+
+fn button_node(width: f32) -> impl Bundle {
+    (
+        Button,
+        Node {
+            width: px(width),
+            height: px(80),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        BackgroundColor(KEY_IDLE),
+    )
+}
+
+/// Turns pad presses into [`Key`] messages.
+pub fn press_pad(
+    buttons: Query<(&Interaction, &PadButton), Changed<Interaction>>,
+    mut keys: MessageWriter<Key>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction == Interaction::Pressed {
+            keys.write(button.0);
+        }
+    }
+}
+
+pub fn press_play_again(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<PlayAgainButton>)>,
+    mut requests: MessageWriter<NewRound>,
+) {
+    if buttons.iter().any(|i| *i == Interaction::Pressed) {
+        requests.write(NewRound);
+    }
+}
+
+/// Lights up buttons under the pointer and while they are held down.
+pub fn shade_buttons(
+    mut buttons: Query<(&Interaction, &mut BackgroundColor), Changed<Interaction>>,
+) {
+    for (interaction, mut colour) in &mut buttons {
+        colour.0 = match interaction {
+            Interaction::Pressed => KEY_PRESSED,
+            Interaction::Hovered => KEY_HOVERED,
+            Interaction::None => KEY_IDLE,
+        };
+    }
+}
+
+/// Copies the typed digits and the hint from [`Game`] into their texts.
+pub fn show_game(
+    game: Res<Game>,
+    mut entry: Single<&mut Text, With<EntryText>>,
+    mut hint: Single<&mut Text, (With<HintText>, Without<EntryText>)>,
+) {
+    entry.0 = match game.entry() {
+        "" => "_".to_string(),
+        digits => digits.to_string(),
+    };
+    hint.0 = game.hint();
+}
+
+/// Covers the board with the result and a "play again" button. It goes
+/// away by itself when the screen changes back.
+pub fn spawn_end_screen(mut commands: Commands, game: Res<Game>) {
+    let Some((headline, details)) = game.result() else {
+        return;
+    };
+    commands.spawn((
+        EndScreen,
+        DespawnOnExit(Screen::Over),
+        Node {
+            position_type: PositionType::Absolute,
+            width: percent(100),
+            height: percent(100),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: px(24),
+            ..default()
+        },
+        BackgroundColor(BACKGROUND.with_alpha(0.95)),
+        GlobalZIndex(1),
+        children![
+            (Text::new(headline), TextFont::from_font_size(56.0)),
+            (Text::new(details), TextFont::from_font_size(24.0)),
+            (
+                PlayAgainButton,
+                button_node(200.0),
+                children![(Text::new("Play again"), TextFont::from_font_size(28.0))],
+            ),
+        ],
+    ));
+}
@@ -0,0 +1,196 @@
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use guessing_bevy::ui::{EndScreen, EntryText, HintText, PadButton, PlayAgainButton};
+use guessing_bevy::{Game, GuessingPlugin, Key, Screen};
+use guessing_game::Difficulty;
+
+/// The game with no window: `MinimalPlugins` drives the schedules and the
+/// tests play the part of the UI picking system by setting `Interaction`.
+fn headless(difficulty: Difficulty, seed: u64) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        StatesPlugin,
+        GuessingPlugin {
+            difficulty,
+            seed: Some(seed),
+        },
+    ));
+    app.update();
+    app
+}
+
+/// Presses and releases `button`, running a frame after each so state
+/// changes land.
+fn tap(app: &mut App, button: Entity) {
+    for interaction in [Interaction::Pressed, Interaction::None] {
+        app.world_mut().entity_mut(button).insert(interaction);
+        app.update();
+    }
+}
+
+fn click<M: Component>(app: &mut App) {
+    let button = app
+        .world_mut()
+        .query_filtered::<Entity, (With<Button>, With<M>)>()
+        .iter(app.world())
+        .next()
+        .expect("no such button");
+    tap(app, button);
+}
+
+fn press(app: &mut App, key: Key) {
+    let button = app
+        .world_mut()
+        .query::<(Entity, &PadButton)>()
+        .iter(app.world())
+        .find(|(_, pad)| pad.0 == key)
+        .map(|(entity, _)| entity)
+        .expect("key not on the pad");
+    tap(app, button);
+}
+
+fn enter(app: &mut App, guess: u32) {
+    for digit in guess.to_string().bytes() {
+        press(app, Key::Digit(digit - b'0'));
+    }
+    press(app, Key::Enter);
+}
+
+fn text<M: Component>(app: &mut App) -> String {
+    app.world_mut()
+        .query_filtered::<&Text, With<M>>()
+        .single(app.world())
+        .unwrap()
+        .0
+        .clone()
+}
+
+fn all_text(app: &mut App) -> Vec<String> {
+    app.world_mut()
+        .query::<&Text>()
+        .iter(app.world())
+        .map(|text| text.0.clone())
+        .collect()
+}
+
+fn screen(app: &App) -> Screen {
+    *app.world().resource::<State<Screen>>().get()
+}
+
+fn end_screens(app: &mut App) -> usize {
+    app.world_mut()
+        .query_filtered::<(), With<EndScreen>>()
+        .iter(app.world())
+        .count()
+}
+
+fn secret(app: &App) -> u32 {
+    app.world().resource::<Game>().round().secret()
+}
+
+#[test]
+fn board_has_a_pad_and_a_hint() {
+    let mut app = headless(Difficulty::Medium, 1);
+    let mut keys: Vec<Key> = app
+        .world_mut()
+        .query::<&PadButton>()
+        .iter(app.world())
+        .map(|pad| pad.0)
+        .collect();
+    assert_eq!(keys.len(), 12);
+    keys.sort_by_key(|key| Key::PAD.iter().position(|k| k == key));
+    assert_eq!(keys, Key::PAD);
+
+    let labels = all_text(&mut app);
+    for label in ["Guess the number!", "0", "9", "Del", "OK"] {
+        assert!(labels.iter().any(|l| l == label), "{:?}", labels);
+    }
+    assert_eq!(text::<EntryText>(&mut app), "_");
+    assert_eq!(
+        text::<HintText>(&mut app),
+        "Pick a number between 1 and 100. You have 10 attempts."
+    );
+    assert_eq!(screen(&app), Screen::Playing);
+    assert_eq!(end_screens(&mut app), 0);
+}
+
+#[test]
+fn pad_presses_become_guesses() {
+    let mut app = headless(Difficulty::Medium, 2);
+    let secret = secret(&app);
+    let wrong = if secret > 50 { 25 } else { 75 };
+
+    press(&mut app, Key::Digit(4));
+    press(&mut app, Key::Digit(2));
+    assert_eq!(text::<EntryText>(&mut app), "42");
+    press(&mut app, Key::Back);
+    press(&mut app, Key::Back);
+    assert_eq!(text::<EntryText>(&mut app), "_");
+
+    enter(&mut app, wrong);
+    assert_eq!(text::<EntryText>(&mut app), "_");
+    let hint = text::<HintText>(&mut app);
+    let expected = if wrong < secret {
+        "too small"
+    } else {
+        "too big"
+    };
+    assert!(
+        hint.starts_with(&format!("{} is {}.", wrong, expected)),
+        "{}",
+        hint
+    );
+    assert!(hint.ends_with("9 attempts left."), "{}", hint);
+    assert_eq!(screen(&app), Screen::Playing);
+}
+
+#[test]
+fn winning_shows_the_end_screen_until_play_again() {
+    let mut app = headless(Difficulty::Medium, 3);
+    let first = secret(&app);
+    enter(&mut app, first);
+
+    assert_eq!(screen(&app), Screen::Over);
+    assert_eq!(end_screens(&mut app), 1);
+    let texts = all_text(&mut app);
+    assert!(texts.iter().any(|t| t == "You win!"), "{:?}", texts);
+    assert!(
+        texts
+            .iter()
+            .any(|t| *t == format!("{} it was. It took you 1 attempts.", first)),
+        "{:?}",
+        texts
+    );
+
+    // The pad is dead while the end screen is up.
+    press(&mut app, Key::Digit(1));
+    assert_eq!(text::<EntryText>(&mut app), "_");
+
+    click::<PlayAgainButton>(&mut app);
+    assert_eq!(screen(&app), Screen::Playing);
+    assert_eq!(end_screens(&mut app), 0);
+    assert_eq!(app.world().resource::<Game>().round().attempts(), 0);
+    assert!(text::<HintText>(&mut app).starts_with("Pick a number"));
+}
+
+#[test]
+fn running_out_of_attempts_loses() {
+    let mut app = headless(Difficulty::Easy, 4);
+    let secret = secret(&app);
+    let wrong = if secret == 1 { 2 } else { 1 };
+    for _ in 0..Difficulty::Easy.max_attempts() {
+        enter(&mut app, wrong);
+    }
+
+    assert_eq!(screen(&app), Screen::Over);
+    let texts = all_text(&mut app);
+    assert!(texts.iter().any(|t| t == "Out of attempts"), "{:?}", texts);
+    assert!(
+        texts
+            .iter()
+            .any(|t| *t == format!("The secret number was {}.", secret)),
+        "{:?}",
+        texts
+    );
+}
@@ -0,0 +1,5 @@
+fn main() {
+    // Only the Tauri shell has a config and capabilities to compile in.
+    #[cfg(feature = "shell")]
+    tauri_build::build();
+}
@@ -0,0 +1,104 @@
+// Talks to the commands registered in src/main.rs. Errors come back as
+// { kind, message }; the message is meant to be shown as it is.
+const { invoke } = window.__TAURI__.core;
+
+const $ = (id) => document.getElementById(id);
+const HINTS = { too_small: "too small", too_big: "too big", correct: "correct!" };
+
+function showError(error) {
+  $("error").textContent = error ? error.message : "";
+}
+
+function showState(state) {
+  const status = $("status");
+  const outcome = state.outcome;
+  status.className = "status " + (outcome ? outcome.result : "");
+  if (!outcome) {
+    status.textContent = `Still possible: ${state.bounds[0]}-${state.bounds[1]}. ${state.attempts_left} attempts left.`;
+  } else if (outcome.result === "won") {
+    status.textContent = `You win! It took you ${outcome.attempts} attempts.`;
+  } else {
+    status.textContent = `You lose! The secret number was ${outcome.secret}.`;
+  }
+  $("guess").disabled = outcome !== null;
+
+  const history = $("history");
+  history.replaceChildren(
+    ...state.history
+      .slice()
+      .reverse()
+      .map(({ guess, hint }) => {
+        const item = document.createElement("li");
+        item.className = hint;
+        item.textContent = `${guess} is ${HINTS[hint]}`;
+        return item;
+      }),
+  );
+}
+
+async function showScores() {
+  const boards = await invoke("get_scores", { filter: null });
+  const scores = $("scores");
+  if (boards.length === 0) {
+    scores.textContent = "No games won yet.";
+    return;
+  }
+  scores.replaceChildren(
+    ...boards.map(({ difficulty, entries }) => {
+      const section = document.createElement("section");
+      const title = document.createElement("h3");
+      title.textContent = difficulty;
+      const list = document.createElement("ol");
+      for (const entry of entries.slice(0, 5)) {
+        const item = document.createElement("li");
+        item.textContent = `${entry.player}: ${entry.attempts} attempts`;
+        list.append(item);
+      }
+      section.append(title, list);
+      return section;
+    }),
+  );
+}
+
+$("start").addEventListener("submit", async (event) => {
+  event.preventDefault();
+  try {
+    const state = await invoke("new_game", {
+      difficulty: $("difficulty").value,
+      player: $("player").value || null,
+    });
+    showError(null);
+    showState(state);
+  } catch (error) {
+    showError(error);
+  }
+});
+
+$("play").addEventListener("submit", async (event) => {
+  event.preventDefault();
+  const input = $("guess");
+  try {
+    const reply = await invoke("guess", { input: input.value });
+    input.value = "";
+    showError(reply.warning ? { message: reply.warning } : null);
+    showState(reply.state);
+    if (reply.state.outcome) {
+      await showScores();
+    }
+  } catch (error) {
+    showError(error);
+  }
+});
+
+window.addEventListener("DOMContentLoaded", async () => {
+  try {
+    showState(await invoke("get_state"));
+  } catch (error) {
+    // A fresh window has no game yet; start one straight away.
+    if (error.kind !== "no_game") {
+      showError(error);
+    }
+    showState(await invoke("new_game", { difficulty: "medium", player: null }));
+  }
+  await showScores();
+});
@@ -0,0 +1,358 @@
+use std::fmt;
+use std::time::Instant;
+
+use guessing_game::scores::{self, ScoreEntry};
+use guessing_game::{parse_guess, Difficulty, GuessError, Hint, Outcome, RoundError};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use crate::manager::{GameManager, Session};
+
+/// Why a command was refused. The front end gets `{ "kind", "message" }`,
+/// so it can branch on `kind` and show `message` as it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    /// The window has not started a game yet.
+    NoGame,
+    /// A custom difficulty with an empty range or no attempts.
+    BadDifficulty,
+    InvalidGuess(GuessError),
+    /// The window's game is already won or lost.
+    GameOver(Outcome),
+}
+
+impl CommandError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CommandError::NoGame => "no_game",
+            CommandError::BadDifficulty => "bad_difficulty",
+            CommandError::InvalidGuess(_) => "invalid_guess",
+            CommandError::GameOver(_) => "game_over",
+        }
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::NoGame => write!(f, "No game has been started in this window."),
+            CommandError::BadDifficulty => write!(
+                f,
+                "A custom game needs a non-empty range and at least one attempt."
+            ),
+            CommandError::InvalidGuess(e) => write!(f, "{}", e),
+            CommandError::GameOver(_) => write!(f, "The game is already over."),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl Serialize for CommandError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut error = serializer.serialize_struct("CommandError", 2)?;
+        error.serialize_field("kind", self.kind())?;
+        error.serialize_field("message", &self.to_string())?;
+        error.end()
+    }
+}
+
+impl From<RoundError> for CommandError {
+    fn from(e: RoundError) -> CommandError {
+        match e {
+            RoundError::OutOfRange { guess, range } => {
+                CommandError::InvalidGuess(GuessError::OutOfRange {
+                    value: guess.into(),
+                    range,
+                })
+            }
+            RoundError::GameOver(outcome) => CommandError::GameOver(outcome),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct GuessRecord {
+    pub guess: u32,
+    pub hint: Hint,
+}
+
+/// Everything a window needs to draw its game. The secret only shows up in
+/// `outcome`, once the game is over.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct GameState {
+    pub player: String,
+    /// As shown to the player, e.g. `medium (1-100, 10 attempts)`.
+    pub difficulty: String,
+    pub range: [u32; 2],
+    /// What is still consistent with every hint so far.
+    pub bounds: [u32; 2],
+    pub attempts: u32,
+    pub attempts_left: u32,
+    /// Oldest first.
+    pub history: Vec<GuessRecord>,
+    pub outcome: Option<Outcome>,
+}
+
+impl GameState {
+    fn of(session: &Session) -> GameState {
+        let (range, bounds) = (session.round.range(), session.round.bounds());
+        GameState {
+            player: session.player.clone(),
+            difficulty: session.difficulty.to_string(),
+            range: [*range.start(), *range.end()],
+            bounds: [*bounds.start(), *bounds.end()],
+            attempts: session.round.attempts(),
+            attempts_left: session.round.attempts_left(),
+            history: session
+                .history
+                .iter()
+                .map(|&(guess, hint)| GuessRecord { guess, hint })
+                .collect(),
+            outcome: session.round.outcome(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct GuessReply {
+    pub hint: Hint,
+    pub state: GameState,
+    /// Set when the game ended but its score could not be saved.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+/// The wins at one difficulty, best first.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Leaderboard {
+    pub difficulty: String,
+    pub entries: Vec<ScoreEntry>,
+}
+
+/// Starts a game in `window`, replacing any game it had.
+pub fn new_game(
+    manager: &GameManager,
+    window: &str,
+    difficulty: Difficulty,
+    player: Option<String>,
+) -> Result<GameState, CommandError> {
+    // A custom difficulty built by hand has not been through the checks in
+    // `Difficulty::custom`, and an empty range cannot hold a secret.
+    if let Difficulty::Custom {
+        range,
+        max_attempts,
+    } = &difficulty
+    {
+        Difficulty::custom(range.clone(), *max_attempts).ok_or(CommandError::BadDifficulty)?;
+    }
+    let player = player
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "player".to_string());
+
+    let mut inner = manager.lock();
+    let round = guessing_game::Round::new(&difficulty, &mut inner.rng);
+    let session = Session {
+        player,
+        difficulty,
+        round,
+        history: Vec::new(),
+        started: Instant::now(),
+    };
+    let state = GameState::of(&session);
+    inner.sessions.insert(window.to_string(), session);
+    Ok(state)
+}
+
+/// Checks a guess, typed as text, against the secret in `window`. A game
+/// that ends with it is recorded on the score board.
+pub fn guess(manager: &GameManager, window: &str, input: &str) -> Result<GuessReply, CommandError> {
+    let mut guard = manager.lock();
+    let inner = &mut *guard;
+    let session = inner.sessions.get_mut(window).ok_or(CommandError::NoGame)?;
+    if let Some(outcome) = session.round.outcome() {
+        return Err(CommandError::GameOver(outcome));
+    }
+    let value = parse_guess(input, &session.round.range()).map_err(CommandError::InvalidGuess)?;
+    let hint = session.round.guess(value)?;
+    session.history.push((value, hint));
+
+    let (won, attempts) = match session.round.outcome() {
+        Some(Outcome::Won { attempts, .. }) => (true, attempts),
+        Some(Outcome::Lost { attempts, .. }) => (false, attempts),
+        Some(Outcome::Quit { .. }) | None => {
+            return Ok(GuessReply {
+                hint,
+                state: GameState::of(session),
+                warning: None,
+            })
+        }
+    };
+    inner.board.record(ScoreEntry {
+        player: session.player.clone(),
+        difficulty: session.difficulty.to_string(),
+        won,
+        attempts,
+        elapsed_ms: session.started.elapsed().as_millis() as u64,
+        played_at: scores::now(),
+    });
+    inner.generation += 1;
+    let state = GameState::of(session);
+    let (board, generation) = (inner.board.clone(), inner.generation);
+    drop(guard);
+
+    let warning = manager
+        .save_scores(&board, generation)
+        .err()
+        .map(|e| format!("Could not save {}: {}", board.path().display(), e));
+    Ok(GuessReply {
+        hint,
+        state,
+        warning,
+    })
+}
+
+pub fn get_state(manager: &GameManager, window: &str) -> Result<GameState, CommandError> {
+    let inner = manager.lock();
+    let session = inner.sessions.get(window).ok_or(CommandError::NoGame)?;
+    Ok(GameState::of(session))
+}
+
+/// Every leaderboard, or only those whose difficulty starts with `filter`.
+pub fn get_scores(manager: &GameManager, filter: Option<&str>) -> Vec<Leaderboard> {
+    let inner = manager.lock();
+    inner
+        .board
+        .difficulties()
+        .into_iter()
+        .filter(|d| filter.is_none_or(|f| d.starts_with(f)))
+        .map(|difficulty| Leaderboard {
+            difficulty: difficulty.to_string(),
+            entries: inner
+                .board
+                .leaderboard(difficulty)
+                .into_iter()
+                .cloned()
+                .collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use guessing_game::scores::ScoreBoard;
+
+    /// A one-number range, so the secret is known up front.
+    fn only(secret: u32, max_attempts: u32) -> Difficulty {
+        Difficulty::custom(secret..=secret, max_attempts).unwrap()
+    }
+
+    fn manager(dir: &tempfile::TempDir) -> GameManager {
+        let (board, _) = ScoreBoard::open(dir.path().join("scores.json")).unwrap();
+        GameManager::new(board, 1)
+    }
+
+    #[test]
+    fn plays_a_round_and_records_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = manager(&dir);
+
+        let state = new_game(&manager, "main", Difficulty::Medium, Some(" ann ".into())).unwrap();
+        assert_eq!(state.player, "ann");
+        assert_eq!(state.difficulty, "medium (1-100, 10 attempts)");
+        assert_eq!(
+            (state.range, state.bounds, state.attempts_left),
+            ([1, 100], [1, 100], 10)
+        );
+        assert_eq!(get_state(&manager, "main"), Ok(state));
+
+        let state = new_game(&manager, "main", only(7, 3), None).unwrap();
+        assert_eq!(state.player, "player");
+        let reply = guess(&manager, "main", " 7 ").unwrap();
+        assert_eq!(reply.hint, Hint::Correct);
+        assert_eq!(reply.warning, None);
+        assert_eq!(
+            reply.state.outcome,
+            Some(Outcome::Won {
+                secret: 7,
+                attempts: 1
+            })
+        );
+        assert_eq!(
+            reply.state.history,
+            [GuessRecord {
+                guess: 7,
+                hint: Hint::Correct
+            }]
+        );
+
+        let boards = get_scores(&manager, None);
+        assert_eq!(boards.len(), 1);
+        assert_eq!(boards[0].difficulty, "custom (7-7, 3 attempts)");
+        assert_eq!(boards[0].entries[0].player, "player");
+        let (saved, _) = ScoreBoard::open(dir.path().join("scores.json")).unwrap();
+        assert_eq!(saved.entries().len(), 1);
+        assert_eq!(get_scores(&manager, Some("medium")), []);
+    }
+
+    #[test]
+    fn an_older_copy_of_the_board_is_not_saved_over_a_newer_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = manager(&dir);
+        new_game(&manager, "one", only(7, 3), None).unwrap();
+        new_game(&manager, "two", only(7, 3), None).unwrap();
+        guess(&manager, "one", "7").unwrap();
+        let older = manager.lock().board.clone();
+        guess(&manager, "two", "7").unwrap();
+
+        manager.save_scores(&older, 1).unwrap();
+        let (saved, _) = ScoreBoard::open(dir.path().join("scores.json")).unwrap();
+        assert_eq!(saved.entries().len(), 2);
+    }
+
+    #[test]
+    fn refuses_what_it_cannot_do() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = manager(&dir);
+        assert_eq!(get_state(&manager, "main"), Err(CommandError::NoGame));
+        assert_eq!(guess(&manager, "main", "5"), Err(CommandError::NoGame));
+
+        let hopeless = Difficulty::Custom {
+            range: 1..=10,
+            max_attempts: 0,
+        };
+        assert_eq!(
+            new_game(&manager, "main", hopeless, None),
+            Err(CommandError::BadDifficulty)
+        );
+
+        new_game(&manager, "main", Difficulty::Easy, None).unwrap();
+        assert_eq!(
+            guess(&manager, "main", "ten"),
+            Err(CommandError::InvalidGuess(GuessError::NotANumber(
+                "ten".into()
+            )))
+        );
+        let error = guess(&manager, "main", "11").unwrap_err();
+        assert_eq!(error.kind(), "invalid_guess");
+        assert_eq!(
+            error.to_string(),
+            "11 is out of range, pick a number between 1 and 10."
+        );
+        assert_eq!(get_state(&manager, "main").unwrap().attempts, 0);
+
+        new_game(&manager, "main", only(4, 1), None).unwrap();
+        assert_eq!(guess(&manager, "main", "4").unwrap().hint, Hint::Correct);
+        assert_eq!(
+            guess(&manager, "main", "4"),
+            Err(CommandError::GameOver(Outcome::Won {
+                secret: 4,
+                attempts: 1
+            }))
+        );
+
+        manager.close_window("main");
+        assert_eq!(get_state(&manager, "main"), Err(CommandError::NoGame));
+    }
+}
@@ -0,0 +1,16 @@
+//! The backend of a desktop guessing game. The commands take and return
+//! plain serializable values and know nothing about Tauri, so they are
+//! tested like any other Rust code; `src/main.rs` is the thin Tauri shell
+//! that registers them, built with `cargo run --features shell`.
+//!
+//! Each window plays its own game, keyed by its label, and every window
+//! shares one score board with the terminal game.
+
+pub mod commands;
+pub mod manager;
+
+pub use commands::{
+    get_scores, get_state, guess, new_game, CommandError, GameState, GuessRecord, GuessReply,
+    Leaderboard,
+};
+pub use manager::GameManager;
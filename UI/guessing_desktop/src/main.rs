@@ -0,0 +1,76 @@
+// Keeps a console window from opening next to the app in Windows release
+// builds.
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
+
+use std::path::PathBuf;
+use std::process;
+
+use guessing_desktop::{commands, CommandError, GameManager, GameState, GuessReply, Leaderboard};
+use guessing_game::scores::{self, LoadStatus, ScoreBoard};
+use guessing_game::Difficulty;
+use tauri::{Manager, State, Window, WindowEvent};
+
+// The commands below only unwrap what Tauri hands them; the game itself is
+// in `guessing_desktop::commands`.
+
+#[tauri::command]
+fn new_game(
+    window: Window,
+    manager: State<'_, GameManager>,
+    difficulty: Difficulty,
+    player: Option<String>,
+) -> Result<GameState, CommandError> {
+    commands::new_game(&manager, window.label(), difficulty, player)
+}
+
+#[tauri::command]
+fn guess(
+    window: Window,
+    manager: State<'_, GameManager>,
+    input: String,
+) -> Result<GuessReply, CommandError> {
+    commands::guess(&manager, window.label(), &input)
+}
+
+#[tauri::command]
+fn get_state(window: Window, manager: State<'_, GameManager>) -> Result<GameState, CommandError> {
+    commands::get_state(&manager, window.label())
+}
+
+#[tauri::command]
+fn get_scores(manager: State<'_, GameManager>, filter: Option<String>) -> Vec<Leaderboard> {
+    commands::get_scores(&manager, filter.as_deref())
+}
+
+fn main() {
+    let path = scores::default_path().unwrap_or_else(|| PathBuf::from("scores.json"));
+    let board = match ScoreBoard::open(&path) {
+        Ok((board, LoadStatus::Recovered { backup, reason })) => {
+            eprintln!(
+                "warning: ignoring {} because {}; it was moved to {}",
+                path.display(),
+                reason,
+                backup.display()
+            );
+            board
+        }
+        Ok((board, _)) => board,
+        Err(e) => {
+            eprintln!("error: could not read {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    };
+
+    tauri::Builder::default()
+        .manage(GameManager::new(board, rand::random()))
+        .on_window_event(|window, event| {
+            if let WindowEvent::Destroyed = event {
+                window.state::<GameManager>().close_window(window.label());
+            }
+        })
+        .invoke_handler(tauri::generate_handler![
+            new_game, guess, get_state, get_scores
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running the Tauri app");
+}
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
+
+use guessing_game::scores::ScoreBoard;
+use guessing_game::{Difficulty, Hint, Round};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// The state behind every command: one game per window and the shared score
+/// board. It sits behind a single lock, so windows calling in at the same
+/// time are served one after the other and never see half an update. The
+/// score file is written from a copy of the board, after that lock is
+/// released, so a slow disk does not hold up the other windows.
+#[derive(Debug)]
+pub struct GameManager {
+    inner: Mutex<Inner>,
+    /// The generation of the board last written to disk.
+    saved: Mutex<u64>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Inner {
+    pub(crate) rng: StdRng,
+    /// Keyed by window label.
+    pub(crate) sessions: HashMap<String, Session>,
+    pub(crate) board: ScoreBoard,
+    /// Goes up with every score recorded on `board`.
+    pub(crate) generation: u64,
+}
+
+/// The game being played in one window.
+#[derive(Debug)]
+pub(crate) struct Session {
+    pub(crate) player: String,
+    pub(crate) difficulty: Difficulty,
+    pub(crate) round: Round,
+    /// Every accepted guess, oldest first.
+    pub(crate) history: Vec<(u32, Hint)>,
+    pub(crate) started: Instant,
+}
+
+impl GameManager {
+    /// Finished games are recorded on `board`; secrets are drawn from a
+    /// generator seeded with `seed`.
+    pub fn new(board: ScoreBoard, seed: u64) -> GameManager {
+        GameManager {
+            inner: Mutex::new(Inner {
+                rng: StdRng::seed_from_u64(seed),
+                sessions: HashMap::new(),
+                board,
+                generation: 0,
+            }),
+            saved: Mutex::new(0),
+        }
+    }
+
+    /// Drops the game of a window that was closed.
+    pub fn close_window(&self, window: &str) {
+        self.lock().sessions.remove(window);
+    }
+
+    /// Writes a copy of the board taken at `generation`. Copies can reach
+    /// this out of order; each holds every score before it, so one older
+    /// than what is on disk already is skipped.
+    pub(crate) fn save_scores(&self, board: &ScoreBoard, generation: u64) -> io::Result<()> {
+        let mut saved = self.saved.lock().unwrap_or_else(PoisonError::into_inner);
+        if *saved >= generation {
+            return Ok(());
+        }
+        board.save()?;
+        *saved = generation;
+        Ok(())
+    }
+
+    /// Every command finishes its change before it lets go of the lock, so
+    /// the state is whole even if a command panicked while holding it.
+    pub(crate) fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
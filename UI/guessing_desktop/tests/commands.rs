@@ -0,0 +1,101 @@
+use std::sync::Arc;
+use std::thread;
+
+use guessing_desktop::{get_scores, get_state, guess, new_game, CommandError, GameManager};
+use guessing_game::scores::ScoreBoard;
+use guessing_game::Difficulty;
+use serde_json::json;
+
+fn manager(dir: &tempfile::TempDir) -> GameManager {
+    let (board, _) = ScoreBoard::open(dir.path().join("scores.json")).unwrap();
+    GameManager::new(board, 9)
+}
+
+/// Narrows a window's bounds until it finds the secret, like a player
+/// clicking through a binary search.
+fn solve(manager: &GameManager, window: &str) -> u32 {
+    loop {
+        let state = get_state(manager, window).unwrap();
+        let [low, high] = state.bounds;
+        let middle = low + (high - low) / 2;
+        let reply = guess(manager, window, &middle.to_string()).unwrap();
+        if reply.state.outcome.is_some() {
+            return reply.state.attempts;
+        }
+    }
+}
+
+#[test]
+fn windows_play_their_own_games_at_the_same_time() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = Arc::new(manager(&dir));
+
+    let players: Vec<_> = (0..4)
+        .map(|i| {
+            let manager = Arc::clone(&manager);
+            thread::spawn(move || {
+                let window = format!("window-{}", i);
+                let player = format!("player {}", i);
+                new_game(&manager, &window, Difficulty::Hard, Some(player)).unwrap();
+                solve(&manager, &window)
+            })
+        })
+        .collect();
+    for player in players {
+        // Hard gives exactly the attempts a binary search needs.
+        assert!(player.join().unwrap() <= Difficulty::Hard.max_attempts());
+    }
+
+    for i in 0..4 {
+        let state = get_state(&manager, &format!("window-{}", i)).unwrap();
+        assert_eq!(state.player, format!("player {}", i));
+        assert!(state.outcome.is_some());
+    }
+    let boards = get_scores(&manager, Some("hard"));
+    assert_eq!(boards.len(), 1);
+    assert_eq!(boards[0].entries.len(), 4);
+    let (saved, _) = ScoreBoard::open(dir.path().join("scores.json")).unwrap();
+    assert_eq!(saved.entries().len(), 4);
+}
+
+#[test]
+fn replies_serialize_for_the_front_end() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = manager(&dir);
+
+    let error = get_state(&manager, "main").unwrap_err();
+    assert_eq!(
+        serde_json::to_value(&error).unwrap(),
+        json!({
+            "kind": "no_game",
+            "message": "No game has been started in this window."
+        })
+    );
+
+    let difficulty: Difficulty = serde_json::from_value(
+        json!({ "custom": { "range": { "start": 3, "end": 3 }, "max_attempts": 2 } }),
+    )
+    .unwrap();
+    new_game(&manager, "main", difficulty, None).unwrap();
+    let reply = guess(&manager, "main", "3").unwrap();
+    assert_eq!(
+        serde_json::to_value(&reply).unwrap(),
+        json!({
+            "hint": "correct",
+            "state": {
+                "player": "player",
+                "difficulty": "custom (3-3, 2 attempts)",
+                "range": [3, 3],
+                "bounds": [3, 3],
+                "attempts": 1,
+                "attempts_left": 1,
+                "history": [{ "guess": 3, "hint": "correct" }],
+                "outcome": { "result": "won", "secret": 3, "attempts": 1 }
+            }
+        })
+    );
+
+    let error = guess(&manager, "main", "3").unwrap_err();
+    assert!(matches!(error, CommandError::GameOver(_)));
+    assert_eq!(serde_json::to_value(&error).unwrap()["kind"], "game_over");
+}
@@ -0,0 +1,108 @@
+use std::rc::Rc;
+
+use guessing_game::{Difficulty, Outcome};
+use yew::prelude::*;
+
+use crate::components::{DifficultyPicker, GuessForm, History};
+use crate::state::{GameAction, GameState};
+
+#[derive(Properties, PartialEq, Default)]
+pub struct AppProps {
+    #[prop_or_default]
+    pub difficulty: Difficulty,
+    /// Seed for the first secret; a random one if `None`. Later rounds
+    /// always get a random seed.
+    #[prop_or_default]
+    pub seed: Option<u64>,
+}
+
+/// The whole page: owns the reducer and hands its state to [`GameView`].
+#[function_component]
+pub fn App(props: &AppProps) -> Html {
+    let state = {
+        let (difficulty, seed) = (props.difficulty.clone(), props.seed);
+        use_reducer(move || GameState::new(difficulty, seed.unwrap_or_else(rand::random)))
+    };
+
+    let onpick = {
+        let state = state.clone();
+        Callback::from(move |difficulty| {
+            state.dispatch(GameAction::NewGame {
+                difficulty,
+                seed: rand::random(),
+            })
+        })
+    };
+    let onguess = {
+        let state = state.clone();
+        Callback::from(move |text| state.dispatch(GameAction::Guess(text)))
+    };
+    let ongiveup = {
+        let state = state.clone();
+        Callback::from(move |()| state.dispatch(GameAction::GiveUp))
+    };
+
+    html! {
+        <GameView state={Rc::new((*state).clone())} {onpick} {onguess} {ongiveup} />
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct GameViewProps {
+    pub state: Rc<GameState>,
+    pub onpick: Callback<Difficulty>,
+    pub onguess: Callback<String>,
+    pub ongiveup: Callback<()>,
+}
+
+/// Draws a [`GameState`] without owning it, so any state can be rendered.
+#[function_component]
+pub fn GameView(props: &GameViewProps) -> Html {
+    let state = &props.state;
+    let bounds = state.bounds();
+    let over = state.outcome().is_some();
+
+    let status = match state.outcome() {
+        None => html! {
+            <p class="status">
+                { format!(
+                    "Still possible: {}-{}. {} attempts left.",
+                    bounds.start(),
+                    bounds.end(),
+                    state.attempts_left()
+                ) }
+            </p>
+        },
+        Some(Outcome::Won { attempts, .. }) => html! {
+            <p class="outcome won">{ format!("You win! It took you {} attempts.", attempts) }</p>
+        },
+        Some(Outcome::Lost { secret, .. }) => html! {
+            <p class="outcome lost">{ format!("You lose! The secret number was {}.", secret) }</p>
+        },
+        Some(Outcome::Quit { secret, .. }) => html! {
+            <p class="outcome quit">{ format!("You gave up. The secret number was {}.", secret) }</p>
+        },
+    };
+    let giveup = {
+        let ongiveup = props.ongiveup.clone();
+        Callback::from(move |_: MouseEvent| ongiveup.emit(()))
+    };
+
+    html! {
+        <main class="guessing-game">
+            <h1>{ "Guess the number!" }</h1>
+            <DifficultyPicker selected={state.difficulty().clone()} onpick={props.onpick.clone()} />
+            { status }
+            <GuessForm
+                range={state.range()}
+                error={state.error().map(|e| AttrValue::from(e.to_string()))}
+                disabled={over}
+                onguess={props.onguess.clone()}
+            />
+            <button type="button" class="give-up" disabled={over} onclick={giveup}>
+                { "Give up" }
+            </button>
+            <History guesses={state.history().to_vec()} />
+        </main>
+    }
+}
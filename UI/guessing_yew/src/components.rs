@@ -0,0 +1,135 @@
+use std::ops::RangeInclusive;
+
+use guessing_game::{Difficulty, Hint};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// The levels the picker offers. Custom ranges are left to the terminal.
+pub const LEVELS: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+#[derive(Properties, PartialEq)]
+pub struct DifficultyPickerProps {
+    pub selected: Difficulty,
+    /// Called with the level that was clicked, even if it is already
+    /// selected, so the picker doubles as "new game".
+    pub onpick: Callback<Difficulty>,
+}
+
+/// One button per level.
+#[function_component]
+pub fn DifficultyPicker(props: &DifficultyPickerProps) -> Html {
+    let buttons = LEVELS.iter().map(|level| {
+        let selected = *level == props.selected;
+        let onclick = {
+            let onpick = props.onpick.clone();
+            let level = level.clone();
+            Callback::from(move |_: MouseEvent| onpick.emit(level.clone()))
+        };
+        html! {
+            <button
+                type="button"
+                class={classes!("level", selected.then_some("selected"))}
+                aria-pressed={selected.to_string()}
+                {onclick}
+            >
+                { level.to_string() }
+            </button>
+        }
+    });
+    html! {
+        <fieldset class="difficulty">
+            <legend>{ "Difficulty" }</legend>
+            { for buttons }
+        </fieldset>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct GuessFormProps {
+    pub range: RangeInclusive<u32>,
+    /// Why the last guess was refused.
+    #[prop_or_default]
+    pub error: Option<AttrValue>,
+    /// Set once the round is over.
+    #[prop_or_default]
+    pub disabled: bool,
+    /// Called with the text as typed; checking it is the reducer's job.
+    pub onguess: Callback<String>,
+}
+
+/// A number box and a button. The box is cleared after every guess.
+#[function_component]
+pub fn GuessForm(props: &GuessFormProps) -> Html {
+    let text = use_state(String::new);
+
+    let oninput = {
+        let text = text.clone();
+        Callback::from(move |e: InputEvent| {
+            text.set(e.target_unchecked_into::<HtmlInputElement>().value())
+        })
+    };
+    let onsubmit = {
+        let text = text.clone();
+        let onguess = props.onguess.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            onguess.emit((*text).clone());
+            text.set(String::new());
+        })
+    };
+
+    html! {
+        <form class="guess" {onsubmit}>
+            <label for="guess">
+                { format!("Your guess ({}-{})", props.range.start(), props.range.end()) }
+            </label>
+            <input
+                id="guess"
+                type="number"
+                min={props.range.start().to_string()}
+                max={props.range.end().to_string()}
+                value={(*text).clone()}
+                disabled={props.disabled}
+                {oninput}
+            />
+            <button type="submit" disabled={props.disabled}>{ "Guess" }</button>
+            if let Some(error) = &props.error {
+                <p class="error" role="alert">{ error.clone() }</p>
+            }
+        </form>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct HistoryProps {
+    /// Oldest first, as the reducer keeps them.
+    pub guesses: Vec<(u32, Hint)>,
+}
+
+/// The guesses so far, newest at the top.
+#[function_component]
+pub fn History(props: &HistoryProps) -> Html {
+    if props.guesses.is_empty() {
+        return html! { <p class="history empty">{ "No guesses yet." }</p> };
+    }
+    let items = props
+        .guesses
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, &(guess, hint))| {
+            let (class, text) = match hint {
+                Hint::TooSmall => ("too-small", "too small"),
+                Hint::TooBig => ("too-big", "too big"),
+                Hint::Correct => ("correct", "correct!"),
+            };
+            html! {
+                <li class={class} value={(i + 1).to_string()}>
+                    { format!("{} is {}", guess, text) }
+                </li>
+            }
+        });
+    html! {
+        <ol class="history" reversed=true>{ for items }</ol>
+    }
+}
@@ -0,0 +1,12 @@
+//! The guessing game in the browser, built with Yew. The rules come from
+//! `guessing_game`, so a round here plays exactly like one in the terminal.
+//!
+//! Build and serve it with `trunk serve`. The components also render on the
+//! server, which is how the tests check their HTML without a browser.
+
+pub mod app;
+pub mod components;
+pub mod state;
+
+pub use app::{App, AppProps, GameView};
+pub use state::{GameAction, GameState};
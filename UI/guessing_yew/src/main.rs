@@ -0,0 +1,3 @@
+fn main() {
+    yew::Renderer::<guessing_yew::App>::new().render();
+}
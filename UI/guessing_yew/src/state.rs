@@ -0,0 +1,173 @@
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+use guessing_game::{parse_guess, Difficulty, Hint, Outcome, Round};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use yew::Reducible;
+
+/// Something the player did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameAction {
+    /// Starts a new round. The seed is picked by the caller so the reducer
+    /// itself stays deterministic.
+    NewGame {
+        difficulty: Difficulty,
+        seed: u64,
+    },
+    /// What was typed in the guess box, exactly as typed.
+    Guess(String),
+    GiveUp,
+}
+
+/// One round in the browser. Guesses are checked by the same [`Round`]
+/// engine and [`parse_guess`] as the terminal game, so the rules and the
+/// error messages match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameState {
+    difficulty: Difficulty,
+    round: Round,
+    /// Every accepted guess, oldest first.
+    history: Vec<(u32, Hint)>,
+    /// Why the last guess was refused, if it was.
+    error: Option<String>,
+}
+
+impl GameState {
+    pub fn new(difficulty: Difficulty, seed: u64) -> GameState {
+        let round = Round::new(&difficulty, &mut StdRng::seed_from_u64(seed));
+        GameState {
+            difficulty,
+            round,
+            history: Vec::new(),
+            error: None,
+        }
+    }
+
+    pub fn apply(&mut self, action: GameAction) {
+        match action {
+            GameAction::NewGame { difficulty, seed } => *self = GameState::new(difficulty, seed),
+            GameAction::Guess(text) => {
+                let result = parse_guess(&text, &self.round.range())
+                    .map_err(|e| e.to_string())
+                    .and_then(|guess| {
+                        self.round
+                            .guess(guess)
+                            .map(|hint| (guess, hint))
+                            .map_err(|e| e.to_string())
+                    });
+                match result {
+                    Ok(entry) => {
+                        self.history.push(entry);
+                        self.error = None;
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+            }
+            GameAction::GiveUp => {
+                self.round.quit();
+                self.error = None;
+            }
+        }
+    }
+
+    pub fn difficulty(&self) -> &Difficulty {
+        &self.difficulty
+    }
+
+    pub fn history(&self) -> &[(u32, Hint)] {
+        &self.history
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// How the round ended, or `None` while it is still being played.
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.round.outcome()
+    }
+
+    pub fn range(&self) -> RangeInclusive<u32> {
+        self.round.range()
+    }
+
+    /// The numbers still consistent with every hint.
+    pub fn bounds(&self) -> RangeInclusive<u32> {
+        self.round.bounds()
+    }
+
+    pub fn attempts_left(&self) -> u32 {
+        self.round.attempts_left()
+    }
+}
+
+impl Reducible for GameState {
+    type Action = GameAction;
+
+    fn reduce(self: Rc<Self>, action: GameAction) -> Rc<Self> {
+        let mut state = Rc::unwrap_or_clone(self);
+        state.apply(action);
+        Rc::new(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    fn secret(difficulty: &Difficulty, seed: u64) -> u32 {
+        StdRng::seed_from_u64(seed).gen_range(difficulty.range())
+    }
+
+    #[test]
+    fn plays_a_round() {
+        let state = Rc::new(GameState::new(Difficulty::Easy, 9));
+        let secret = secret(&Difficulty::Easy, 9);
+
+        let state = state.reduce(GameAction::Guess("eleven".to_string()));
+        assert_eq!(state.error(), Some("'eleven' is not a whole number."));
+        assert_eq!(state.attempts_left(), 6);
+
+        let state = state.reduce(GameAction::Guess("11".to_string()));
+        assert_eq!(
+            state.error(),
+            Some("11 is out of range, pick a number between 1 and 10.")
+        );
+
+        let state = state.reduce(GameAction::Guess(format!(" {} ", secret)));
+        assert_eq!(state.error(), None);
+        assert_eq!(state.history(), &[(secret, Hint::Correct)]);
+        assert_eq!(
+            state.outcome(),
+            Some(Outcome::Won {
+                secret,
+                attempts: 1
+            })
+        );
+
+        let state = state.reduce(GameAction::Guess("3".to_string()));
+        assert_eq!(state.error(), Some("The game is already over."));
+        assert_eq!(state.history().len(), 1);
+    }
+
+    #[test]
+    fn new_game_and_give_up() {
+        let state = Rc::new(GameState::new(Difficulty::Medium, 1));
+        let state = state.reduce(GameAction::Guess("1".to_string()));
+        let state = state.reduce(GameAction::GiveUp);
+        assert!(matches!(
+            state.outcome(),
+            Some(Outcome::Quit { attempts: 1, .. })
+        ));
+
+        let state = state.reduce(GameAction::NewGame {
+            difficulty: Difficulty::Hard,
+            seed: 2,
+        });
+        assert_eq!(*state, GameState::new(Difficulty::Hard, 2));
+        assert_eq!(state.bounds(), 1..=10_000);
+        assert_eq!(state.outcome(), None);
+    }
+}
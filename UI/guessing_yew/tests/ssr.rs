@@ -0,0 +1,164 @@
+use std::rc::Rc;
+
+use guessing_game::{Difficulty, Hint};
+use guessing_yew::app::GameViewProps;
+use guessing_yew::components::{
+    DifficultyPicker, DifficultyPickerProps, GuessForm, GuessFormProps, History, HistoryProps,
+};
+use guessing_yew::{App, AppProps, GameAction, GameState, GameView};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use yew::{AttrValue, BaseComponent, Callback, LocalServerRenderer};
+
+async fn render<C: BaseComponent>(props: C::Properties) -> String {
+    LocalServerRenderer::<C>::with_props(props)
+        .hydratable(false)
+        .render()
+        .await
+}
+
+fn view(state: GameState) -> GameViewProps {
+    GameViewProps {
+        state: Rc::new(state),
+        onpick: Callback::noop(),
+        onguess: Callback::noop(),
+        ongiveup: Callback::noop(),
+    }
+}
+
+#[tokio::test]
+async fn app_starts_a_round() {
+    let html = render::<App>(AppProps {
+        difficulty: Difficulty::Easy,
+        seed: Some(1),
+    })
+    .await;
+    assert!(html.contains("<h1>Guess the number!</h1>"), "{}", html);
+    assert!(
+        html.contains("Still possible: 1-10. 6 attempts left."),
+        "{}",
+        html
+    );
+    assert!(html.contains("Your guess (1-10)"), "{}", html);
+    assert!(html.contains("No guesses yet."), "{}", html);
+}
+
+#[tokio::test]
+async fn picker_marks_the_selected_level() {
+    let html = render::<DifficultyPicker>(DifficultyPickerProps {
+        selected: Difficulty::Hard,
+        onpick: Callback::noop(),
+    })
+    .await;
+    assert_eq!(html.matches("<button").count(), 3, "{}", html);
+    assert!(
+        html.contains(
+            r#"aria-pressed="true" class="level selected">hard (1-10000, 14 attempts)</button>"#
+        ),
+        "{}",
+        html
+    );
+    assert!(
+        html.contains(r#"aria-pressed="false" class="level">easy (1-10, 6 attempts)</button>"#),
+        "{}",
+        html
+    );
+}
+
+#[tokio::test]
+async fn form_shows_errors_and_locks_when_over() {
+    let html = render::<GuessForm>(GuessFormProps {
+        range: 1..=100,
+        error: Some(AttrValue::from("'x' is not a whole number.")),
+        disabled: false,
+        onguess: Callback::noop(),
+    })
+    .await;
+    assert!(
+        html.contains(r#"min="1""#) && html.contains(r#"max="100""#),
+        "{}",
+        html
+    );
+    assert!(
+        html.contains(r#"<p role="alert" class="error">'x' is not a whole number.</p>"#),
+        "{}",
+        html
+    );
+    assert!(!html.contains("disabled"), "{}", html);
+
+    let html = render::<GuessForm>(GuessFormProps {
+        range: 1..=100,
+        error: None,
+        disabled: true,
+        onguess: Callback::noop(),
+    })
+    .await;
+    assert!(!html.contains("class=\"error\""), "{}", html);
+    assert_eq!(
+        html.matches(r#"disabled="disabled""#).count(),
+        2,
+        "{}",
+        html
+    );
+}
+
+#[tokio::test]
+async fn history_lists_newest_first() {
+    let html = render::<History>(HistoryProps {
+        guesses: vec![
+            (50, Hint::TooBig),
+            (25, Hint::TooSmall),
+            (37, Hint::Correct),
+        ],
+    })
+    .await;
+    let newest = html.find("37 is correct!").unwrap();
+    let middle = html.find("25 is too small").unwrap();
+    let oldest = html.find("50 is too big").unwrap();
+    assert!(newest < middle && middle < oldest, "{}", html);
+    assert!(
+        html.contains(r#"<li value="1" class="too-big">50 is too big</li>"#),
+        "{}",
+        html
+    );
+}
+
+#[tokio::test]
+async fn view_follows_the_reducer() {
+    let secret = StdRng::seed_from_u64(4).gen_range(1..=100);
+    let mut state = GameState::new(Difficulty::Medium, 4);
+    state.apply(GameAction::Guess("1000".to_string()));
+    let html = render::<GameView>(view(state.clone())).await;
+    assert!(
+        html.contains("1000 is out of range, pick a number between 1 and 100."),
+        "{}",
+        html
+    );
+    assert!(html.contains("10 attempts left."), "{}", html);
+
+    state.apply(GameAction::Guess(secret.to_string()));
+    let html = render::<GameView>(view(state.clone())).await;
+    assert!(
+        html.contains("You win! It took you 1 attempts."),
+        "{}",
+        html
+    );
+    assert!(
+        html.contains(&format!("{} is correct!", secret)),
+        "{}",
+        html
+    );
+    assert!(!html.contains("class=\"error\""), "{}", html);
+
+    state.apply(GameAction::NewGame {
+        difficulty: Difficulty::Easy,
+        seed: 4,
+    });
+    state.apply(GameAction::GiveUp);
+    let html = render::<GameView>(view(state)).await;
+    assert!(
+        html.contains("You gave up. The secret number was"),
+        "{}",
+        html
+    );
+}
@@ -0,0 +1,216 @@
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+
+use crate::date::Date;
+use crate::model::{Action, Draft, Filter, Todo, TodoList};
+use crate::store::TodoStore;
+
+/// The whole app. Every change goes through [`TodoList::apply`] and is
+/// saved to `store` straight away.
+#[component]
+pub fn App(
+    store: TodoStore,
+    /// The filter to start with.
+    #[props(default)]
+    filter: Filter,
+    /// What counts as overdue; today if not given.
+    #[props(default = Date::today())]
+    today: Date,
+) -> Element {
+    // The file is read once. One that cannot be read must not be
+    // overwritten either, so saving stays off until the app is restarted.
+    let loaded = use_hook(|| Rc::new(store.load()));
+    let can_save = loaded.is_ok();
+    let mut list = use_signal(|| match loaded.as_ref() {
+        Ok(list) => list.clone(),
+        Err(_) => TodoList::default(),
+    });
+    let mut problem = use_signal(|| match loaded.as_ref() {
+        Ok(_) => None,
+        Err(e) => Some(format!("Could not read {}: {}", store.path().display(), e)),
+    });
+    let mut filter = use_signal(|| filter);
+
+    let dispatch = use_callback(move |action: Action| {
+        if let Err(e) = list.write().apply(action) {
+            problem.set(Some(e.to_string()));
+            return;
+        }
+        if !can_save {
+            return;
+        }
+        match store.save(&list.read()) {
+            Ok(()) => problem.set(None),
+            Err(e) => problem.set(Some(format!(
+                "Could not save {}: {}",
+                store.path().display(),
+                e
+            ))),
+        }
+    });
+
+    let todos = list.read();
+    rsx! {
+        section { class: "todoapp",
+            h1 { "Todos" }
+            if let Some(problem) = problem() {
+                p { class: "problem", role: "alert", "{problem}" }
+            }
+            NewTodo { on_add: move |draft| dispatch.call(Action::Add(draft)) }
+            ul { class: "todo-list",
+                for todo in todos.visible(filter()) {
+                    TodoItem {
+                        key: "{todo.id}",
+                        todo: todo.clone(),
+                        today,
+                        on_action: dispatch,
+                    }
+                }
+            }
+            FilterBar {
+                filter: filter(),
+                active: todos.count(Filter::Active),
+                completed: todos.count(Filter::Completed),
+                on_filter: move |f| filter.set(f),
+                on_clear: move |_| dispatch.call(Action::ClearCompleted),
+            }
+        }
+    }
+}
+
+/// The form for a new todo.
+#[component]
+pub fn NewTodo(on_add: EventHandler<Draft>) -> Element {
+    let mut draft = use_signal(Draft::default);
+    rsx! {
+        form {
+            class: "new-todo",
+            onsubmit: move |evt| {
+                evt.prevent_default();
+                on_add.call(draft.take());
+            },
+            DraftFields { draft }
+            button { r#type: "submit", "Add" }
+        }
+    }
+}
+
+/// Title, due date and tag inputs bound to `draft`, shared by the new-todo
+/// form and editing.
+#[component]
+fn DraftFields(draft: Signal<Draft>) -> Element {
+    let current = draft.read().clone();
+    rsx! {
+        input {
+            class: "title",
+            placeholder: "What needs to be done?",
+            value: "{current.title}",
+            oninput: move |evt| draft.write().title = evt.value(),
+        }
+        input {
+            class: "due",
+            r#type: "date",
+            value: "{current.due}",
+            oninput: move |evt| draft.write().due = evt.value(),
+        }
+        input {
+            class: "tags",
+            placeholder: "tags, comma separated",
+            value: "{current.tags}",
+            oninput: move |evt| draft.write().tags = evt.value(),
+        }
+    }
+}
+
+/// One todo: a checkbox, its details and edit and delete buttons. While it
+/// is being edited the details become a form.
+#[component]
+pub fn TodoItem(todo: Todo, today: Date, on_action: EventHandler<Action>) -> Element {
+    let mut editing = use_signal(|| false);
+    let mut draft = use_signal(Draft::default);
+    let id = todo.id;
+
+    if editing() {
+        return rsx! {
+            li { class: "todo editing",
+                form {
+                    onsubmit: move |evt| {
+                        evt.prevent_default();
+                        on_action.call(Action::Edit(id, draft()));
+                        editing.set(false);
+                    },
+                    DraftFields { draft }
+                    button { r#type: "submit", "Save" }
+                    button { r#type: "button", onclick: move |_| editing.set(false), "Cancel" }
+                }
+            }
+        };
+    }
+
+    let class = match (todo.done, todo.is_overdue(today)) {
+        (true, _) => "todo done",
+        (false, true) => "todo overdue",
+        (false, false) => "todo",
+    };
+    let mut start_editing = {
+        let current = Draft::from_todo(&todo);
+        move || {
+            draft.set(current.clone());
+            editing.set(true);
+        }
+    };
+    let mut edit_button = start_editing.clone();
+    rsx! {
+        li { class,
+            input {
+                r#type: "checkbox",
+                checked: todo.done,
+                onchange: move |_| on_action.call(Action::Toggle(id)),
+            }
+            span { class: "title", ondoubleclick: move |_| start_editing(), "{todo.title}" }
+            if let Some(due) = todo.due {
+                span { class: "due", "due {due}" }
+            }
+            for tag in todo.tags.iter() {
+                span { class: "tag", "#{tag}" }
+            }
+            button { class: "edit", onclick: move |_| edit_button(), "Edit" }
+            button {
+                class: "delete",
+                onclick: move |_| on_action.call(Action::Delete(id)),
+                "Delete"
+            }
+        }
+    }
+}
+
+/// The item count, filter links and "clear completed".
+#[component]
+pub fn FilterBar(
+    filter: Filter,
+    active: usize,
+    completed: usize,
+    on_filter: EventHandler<Filter>,
+    on_clear: EventHandler<()>,
+) -> Element {
+    rsx! {
+        footer { class: "filters",
+            span { class: "count",
+                if active == 1 { "1 item left" } else { "{active} items left" }
+            }
+            for option in Filter::ALL {
+                button {
+                    class: "filter",
+                    class: if option == filter { "selected" },
+                    aria_pressed: option == filter,
+                    onclick: move |_| on_filter.call(option),
+                    "{option.name()}"
+                }
+            }
+            if completed > 0 {
+                button { class: "clear", onclick: move |_| on_clear.call(()), "Clear completed ({completed})" }
+            }
+        }
+    }
+}
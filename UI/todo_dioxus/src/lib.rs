@@ -0,0 +1,16 @@
+//! A todo list built with Dioxus: todos with a due date and tags that can
+//! be edited, toggled, deleted and filtered, kept in a local JSON file.
+//!
+//! All changes go through [`TodoList::apply`], which knows nothing about
+//! Dioxus, and the components render on the server as well, so the whole
+//! app can be tested without a window.
+
+pub mod app;
+pub mod date;
+pub mod model;
+pub mod store;
+
+pub use app::App;
+pub use date::Date;
+pub use model::{Action, Draft, Filter, Todo, TodoError, TodoList};
+pub use store::TodoStore;
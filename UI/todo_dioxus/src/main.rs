@@ -0,0 +1,23 @@
+use std::env;
+use std::path::PathBuf;
+
+use dioxus::prelude::*;
+use todo_dioxus::{App, TodoStore};
+
+/// Usage: todo_dioxus [FILE]. The list is kept in FILE, by default
+/// `todos.json` under the platform's data directory.
+fn main() {
+    let path = env::args_os()
+        .nth(1)
+        .map(PathBuf::from)
+        .or_else(TodoStore::default_path)
+        .unwrap_or_else(|| PathBuf::from("todos.json"));
+    dioxus::LaunchBuilder::new()
+        .with_context(TodoStore::new(path))
+        .launch(Root);
+}
+
+#[component]
+fn Root() -> Element {
+    rsx! { App { store: use_context::<TodoStore>() } }
+}
@@ -0,0 +1,332 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::date::Date;
+
+/// One thing to do.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Todo {
+    /// Unique within its list and never reused, even after a delete.
+    pub id: u64,
+    pub title: String,
+    pub done: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<Date>,
+    /// Lowercase, without duplicates, in the order they were typed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Todo {
+    /// Still open with its due date in the past.
+    pub fn is_overdue(&self, today: Date) -> bool {
+        !self.done && self.due.is_some_and(|due| due < today)
+    }
+}
+
+/// A todo as typed into a form, before it is checked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Draft {
+    pub title: String,
+    /// `YYYY-MM-DD`, or blank for no due date.
+    pub due: String,
+    /// Comma separated, e.g. `home, #errands`.
+    pub tags: String,
+}
+
+impl Draft {
+    /// A form filled in with `todo`, for editing it.
+    pub fn from_todo(todo: &Todo) -> Draft {
+        Draft {
+            title: todo.title.clone(),
+            due: todo.due.map(|due| due.to_string()).unwrap_or_default(),
+            tags: todo.tags.join(", "),
+        }
+    }
+
+    fn check(&self) -> Result<(String, Option<Date>, Vec<String>), TodoError> {
+        let title = self.title.trim();
+        if title.is_empty() {
+            return Err(TodoError::EmptyTitle);
+        }
+        let due = match self.due.trim() {
+            "" => None,
+            due => Some(due.parse().map_err(TodoError::BadDate)?),
+        };
+        let mut tags: Vec<String> = Vec::new();
+        for tag in self.tags.split(',') {
+            let tag = tag.trim().trim_start_matches('#').to_lowercase();
+            if !tag.is_empty() && !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        Ok((title.to_string(), due, tags))
+    }
+}
+
+/// Which todos to show.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Filter {
+    #[default]
+    All,
+    Active,
+    Completed,
+}
+
+impl Filter {
+    pub const ALL: [Filter; 3] = [Filter::All, Filter::Active, Filter::Completed];
+
+    pub fn matches(&self, todo: &Todo) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Active => !todo.done,
+            Filter::Completed => todo.done,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Filter::All => "All",
+            Filter::Active => "Active",
+            Filter::Completed => "Completed",
+        }
+    }
+}
+
+/// Everything the interface can do to a list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Add(Draft),
+    Edit(u64, Draft),
+    Toggle(u64),
+    Delete(u64),
+    ClearCompleted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TodoError {
+    EmptyTitle,
+    BadDate(String),
+    /// No todo has this id, e.g. it was deleted in another window.
+    NotFound(u64),
+}
+
+impl fmt::Display for TodoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TodoError::EmptyTitle => write!(f, "A todo needs a title."),
+            TodoError::BadDate(reason) => write!(f, "Due date: {}.", reason),
+            TodoError::NotFound(id) => write!(f, "There is no todo #{}.", id),
+        }
+    }
+}
+
+impl std::error::Error for TodoError {}
+
+/// The todos in the order they were added.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TodoList {
+    next_id: u64,
+    todos: Vec<Todo>,
+}
+
+impl TodoList {
+    /// Carries out `action`. A failed action leaves the list untouched.
+    pub fn apply(&mut self, action: Action) -> Result<(), TodoError> {
+        match action {
+            Action::Add(draft) => self.add(&draft).map(drop),
+            Action::Edit(id, draft) => self.edit(id, &draft),
+            Action::Toggle(id) => self.toggle(id).map(drop),
+            Action::Delete(id) => self.delete(id).map(drop),
+            Action::ClearCompleted => {
+                self.clear_completed();
+                Ok(())
+            }
+        }
+    }
+
+    /// Adds a todo and returns its id.
+    pub fn add(&mut self, draft: &Draft) -> Result<u64, TodoError> {
+        let (title, due, tags) = draft.check()?;
+        let id = self.next_id;
+        self.next_id += 1;
+        self.todos.push(Todo {
+            id,
+            title,
+            done: false,
+            due,
+            tags,
+        });
+        Ok(id)
+    }
+
+    /// Replaces the title, due date and tags of a todo.
+    pub fn edit(&mut self, id: u64, draft: &Draft) -> Result<(), TodoError> {
+        let (title, due, tags) = draft.check()?;
+        let todo = self.get_mut(id)?;
+        todo.title = title;
+        todo.due = due;
+        todo.tags = tags;
+        Ok(())
+    }
+
+    /// Flips a todo between open and done, returning whether it is done now.
+    pub fn toggle(&mut self, id: u64) -> Result<bool, TodoError> {
+        let todo = self.get_mut(id)?;
+        todo.done = !todo.done;
+        Ok(todo.done)
+    }
+
+    pub fn delete(&mut self, id: u64) -> Result<Todo, TodoError> {
+        let index = self
+            .todos
+            .iter()
+            .position(|todo| todo.id == id)
+            .ok_or(TodoError::NotFound(id))?;
+        Ok(self.todos.remove(index))
+    }
+
+    /// Deletes every done todo, returning how many there were.
+    pub fn clear_completed(&mut self) -> usize {
+        let before = self.todos.len();
+        self.todos.retain(|todo| !todo.done);
+        before - self.todos.len()
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Todo> {
+        self.todos.iter().find(|todo| todo.id == id)
+    }
+
+    fn get_mut(&mut self, id: u64) -> Result<&mut Todo, TodoError> {
+        self.todos
+            .iter_mut()
+            .find(|todo| todo.id == id)
+            .ok_or(TodoError::NotFound(id))
+    }
+
+    pub fn todos(&self) -> &[Todo] {
+        &self.todos
+    }
+
+    pub fn visible(&self, filter: Filter) -> impl Iterator<Item = &Todo> {
+        self.todos.iter().filter(move |todo| filter.matches(todo))
+    }
+
+    pub fn count(&self, filter: Filter) -> usize {
+        self.visible(filter).count()
+    }
+
+    /// Checks that every id is unique and below the next one to be handed
+    /// out, as they are in any list built through [`TodoList::add`].
+    pub(crate) fn check_ids(&self) -> Result<(), String> {
+        let mut seen = HashSet::new();
+        for todo in &self.todos {
+            if todo.id >= self.next_id {
+                return Err(format!(
+                    "todo #{} is not below the next id {}",
+                    todo.id, self.next_id
+                ));
+            }
+            if !seen.insert(todo.id) {
+                return Err(format!("todo #{} appears twice", todo.id));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(title: &str, due: &str, tags: &str) -> Draft {
+        Draft {
+            title: title.to_string(),
+            due: due.to_string(),
+            tags: tags.to_string(),
+        }
+    }
+
+    #[test]
+    fn adds_and_edits_todos() {
+        let mut list = TodoList::default();
+        let id = list
+            .add(&draft(
+                "  Buy milk ",
+                "2024-03-01",
+                "Home, #errands, home,,",
+            ))
+            .unwrap();
+        let todo = list.get(id).unwrap();
+        assert_eq!(todo.title, "Buy milk");
+        assert_eq!(todo.due, Date::new(2024, 3, 1));
+        assert_eq!(todo.tags, ["home", "errands"]);
+        assert_eq!(
+            Draft::from_todo(todo),
+            draft("Buy milk", "2024-03-01", "home, errands")
+        );
+
+        assert_eq!(list.add(&draft("  ", "", "")), Err(TodoError::EmptyTitle));
+        assert!(matches!(
+            list.edit(id, &draft("Buy oat milk", "2024-02-30", "")),
+            Err(TodoError::BadDate(_))
+        ));
+        assert_eq!(list.get(id).unwrap().title, "Buy milk");
+
+        list.edit(id, &draft("Buy oat milk", "", "")).unwrap();
+        let todo = list.get(id).unwrap();
+        assert_eq!(
+            (todo.title.as_str(), todo.due, todo.tags.len()),
+            ("Buy oat milk", None, 0)
+        );
+        assert_eq!(
+            list.edit(7, &draft("x", "", "")),
+            Err(TodoError::NotFound(7))
+        );
+    }
+
+    #[test]
+    fn toggles_filters_and_deletes() {
+        let mut list = TodoList::default();
+        for title in ["a", "b", "c"] {
+            list.apply(Action::Add(draft(title, "", ""))).unwrap();
+        }
+        assert_eq!(list.toggle(1), Ok(true));
+        list.apply(Action::Toggle(2)).unwrap();
+        assert_eq!(list.toggle(2), Ok(false));
+
+        let titles = |filter| {
+            list.visible(filter)
+                .map(|todo| todo.title.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(titles(Filter::All), ["a", "b", "c"]);
+        assert_eq!(titles(Filter::Active), ["a", "c"]);
+        assert_eq!(titles(Filter::Completed), ["b"]);
+
+        assert_eq!(list.clear_completed(), 1);
+        list.apply(Action::Delete(0)).unwrap();
+        assert_eq!(list.apply(Action::Delete(0)), Err(TodoError::NotFound(0)));
+        // Ids are never handed out twice.
+        assert_eq!(list.add(&draft("d", "", "")), Ok(3));
+        assert_eq!(list.count(Filter::All), 2);
+    }
+
+    #[test]
+    fn overdue_only_while_open() {
+        let today = Date::new(2024, 3, 2).unwrap();
+        let mut todo = Todo {
+            id: 0,
+            title: "taxes".to_string(),
+            done: false,
+            due: Date::new(2024, 3, 1),
+            tags: Vec::new(),
+        };
+        assert!(todo.is_overdue(today));
+        assert!(!todo.is_overdue(Date::new(2024, 3, 1).unwrap()));
+        todo.done = true;
+        assert!(!todo.is_overdue(today));
+    }
+}
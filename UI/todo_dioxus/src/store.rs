@@ -0,0 +1,140 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::TodoList;
+
+/// Bumped whenever the layout of the todo file changes.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct TodoFile {
+    version: u32,
+    #[serde(flatten)]
+    list: TodoList,
+}
+
+/// Where a list is kept: a JSON file on the local disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoStore {
+    path: PathBuf,
+}
+
+impl TodoStore {
+    pub fn new(path: impl Into<PathBuf>) -> TodoStore {
+        TodoStore { path: path.into() }
+    }
+
+    /// `todos.json` under the platform's data directory, e.g.
+    /// `~/.local/share/todo_dioxus/todos.json` on Linux.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("todo_dioxus").join("todos.json"))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the list, or an empty one if the file does not exist yet. A
+    /// file that cannot be understood is an `InvalidData` error, so it is
+    /// never silently replaced.
+    pub fn load(&self) -> io::Result<TodoList> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(TodoList::default()),
+            Err(e) => return Err(e),
+        };
+        let file: TodoFile = serde_json::from_str(&contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if file.version != FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "format version {}, this app understands version {}",
+                    file.version, FORMAT_VERSION
+                ),
+            ));
+        }
+        file.list
+            .check_ids()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(file.list)
+    }
+
+    /// Writes the list, replacing the old file in one step so a crash never
+    /// leaves a half-written file behind.
+    pub fn save(&self, list: &TodoList) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = TodoFile {
+            version: FORMAT_VERSION,
+            list: list.clone(),
+        };
+        let tmp = self.path.with_extension("json.tmp");
+        let mut out = fs::File::create(&tmp)?;
+        serde_json::to_writer_pretty(&mut out, &file)?;
+        out.write_all(b"\n")?;
+        out.sync_all()?;
+        fs::rename(&tmp, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Action, Draft};
+
+    #[test]
+    fn round_trips_through_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TodoStore::new(dir.path().join("nested").join("todos.json"));
+        assert_eq!(store.load().unwrap(), TodoList::default());
+
+        let mut list = TodoList::default();
+        list.apply(Action::Add(Draft {
+            title: "Write tests".to_string(),
+            due: "2024-03-01".to_string(),
+            tags: "work".to_string(),
+        }))
+        .unwrap();
+        list.apply(Action::Toggle(0)).unwrap();
+        store.save(&list).unwrap();
+        assert_eq!(store.load().unwrap(), list);
+
+        let text = fs::read_to_string(store.path()).unwrap();
+        assert!(text.contains(r#""version": 1"#), "{}", text);
+        assert!(text.contains(r#""due": "2024-03-01""#), "{}", text);
+
+        fs::write(store.path(), r#"{"version": 2, "next_id": 0, "todos": []}"#).unwrap();
+        assert_eq!(store.load().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        fs::write(store.path(), "not json").unwrap();
+        assert_eq!(store.load().unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn refuses_lists_with_bad_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TodoStore::new(dir.path().join("todos.json"));
+        let todo = |id| format!(r#"{{"id": {}, "title": "t", "done": false}}"#, id);
+
+        for (next_id, ids, ok) in [(2, [0, 1], true), (2, [0, 0], false), (1, [0, 1], false)] {
+            let todos: Vec<String> = ids.into_iter().map(todo).collect();
+            let text = format!(
+                r#"{{"version": 1, "next_id": {}, "todos": [{}]}}"#,
+                next_id,
+                todos.join(", ")
+            );
+            fs::write(store.path(), text).unwrap();
+            match store.load() {
+                Ok(list) => assert!(ok, "{:?}", list),
+                Err(e) => {
+                    assert!(!ok, "{}", e);
+                    assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use dioxus::prelude::*;
+use todo_dioxus::app::{AppProps, FilterBar, TodoItem};
+use todo_dioxus::{Action, App, Date, Draft, Filter, Todo, TodoList, TodoStore};
+
+fn draft(title: &str, due: &str, tags: &str) -> Draft {
+    Draft {
+        title: title.to_string(),
+        due: due.to_string(),
+        tags: tags.to_string(),
+    }
+}
+
+fn today() -> Date {
+    Date::new(2024, 3, 2).unwrap()
+}
+
+/// Saves a list built from `actions` and returns a store pointing at it.
+fn store_with(dir: &tempfile::TempDir, actions: Vec<Action>) -> TodoStore {
+    let mut list = TodoList::default();
+    for action in actions {
+        list.apply(action).unwrap();
+    }
+    let store = TodoStore::new(dir.path().join("todos.json"));
+    store.save(&list).unwrap();
+    store
+}
+
+/// Mounts the app in a virtual DOM and renders it to HTML.
+fn render_app(store: TodoStore, filter: Filter) -> String {
+    let mut dom = VirtualDom::new_with_props(
+        App,
+        AppProps {
+            store,
+            filter,
+            today: today(),
+        },
+    );
+    dom.rebuild_in_place();
+    dioxus_ssr::render(&dom)
+}
+
+#[test]
+fn app_renders_the_saved_list() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = store_with(
+        &dir,
+        vec![
+            Action::Add(draft("File taxes", "2024-03-01", "home, money")),
+            Action::Add(draft("Water plants", "", "")),
+            Action::Add(draft("Buy milk", "2024-03-05", "errands")),
+            Action::Toggle(1),
+        ],
+    );
+
+    let html = render_app(store.clone(), Filter::All);
+    assert_eq!(html.matches("<li").count(), 3, "{}", html);
+    assert!(html.contains(r#"<li class="todo overdue">"#), "{}", html);
+    assert!(html.contains(r#"<li class="todo done">"#), "{}", html);
+    assert!(html.contains("due 2024-03-01"), "{}", html);
+    assert!(html.contains("#money"), "{}", html);
+    assert!(html.contains("2 items left"), "{}", html);
+    assert!(html.contains("Clear completed (1)"), "{}", html);
+
+    let html = render_app(store.clone(), Filter::Active);
+    assert!(!html.contains("Water plants"), "{}", html);
+    assert!(
+        html.contains("File taxes") && html.contains("Buy milk"),
+        "{}",
+        html
+    );
+
+    let html = render_app(store, Filter::Completed);
+    assert_eq!(html.matches("<li").count(), 1, "{}", html);
+    assert!(html.contains("Water plants"), "{}", html);
+}
+
+#[test]
+fn app_refuses_to_overwrite_an_unreadable_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("todos.json");
+    std::fs::write(&path, "{ broken").unwrap();
+
+    let html = render_app(TodoStore::new(&path), Filter::All);
+    assert!(html.contains(r#"role="alert""#), "{}", html);
+    assert!(html.contains("Could not read"), "{}", html);
+    assert!(html.contains("0 items left"), "{}", html);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "{ broken");
+}
+
+/// Renders a root without props; event handlers need a live runtime, so
+/// components are mounted this way rather than through `render_element`.
+fn render_root(root: fn() -> Element) -> String {
+    let mut dom = VirtualDom::new(root);
+    dom.rebuild_in_place();
+    dioxus_ssr::render(&dom)
+}
+
+#[test]
+fn item_shows_its_details() {
+    let html = render_root(|| {
+        let todo = Todo {
+            id: 4,
+            title: "Ship <release>".to_string(),
+            done: true,
+            due: Date::new(2024, 3, 9),
+            tags: vec!["work".to_string()],
+        };
+        rsx! {
+            TodoItem { todo, today: today(), on_action: |_| {} }
+        }
+    });
+    assert!(html.contains(r#"<li class="todo done">"#), "{}", html);
+    assert!(html.contains("checked"), "{}", html);
+    assert!(html.contains("Ship &#60;release&#62;"), "{}", html);
+    assert!(
+        html.contains(r#"<span class="tag">#work</span>"#),
+        "{}",
+        html
+    );
+    assert!(html.contains(">Edit</button>"), "{}", html);
+    assert!(html.contains(">Delete</button>"), "{}", html);
+}
+
+#[test]
+fn filter_bar_marks_the_current_filter() {
+    let html = render_root(|| {
+        rsx! {
+            FilterBar {
+                filter: Filter::Active,
+                active: 1,
+                completed: 0,
+                on_filter: |_| {},
+                on_clear: |_| {},
+            }
+        }
+    });
+    assert!(html.contains("1 item left"), "{}", html);
+    assert_eq!(html.matches("selected").count(), 1, "{}", html);
+    assert!(
+        html.contains(r#"<button class="filter selected" aria-pressed=true>Active</button>"#),
+        "{}",
+        html
+    );
+    assert!(!html.contains("Clear completed"), "{}", html);
+}